@@ -0,0 +1,371 @@
+//! Launches a local network of `node-template` processes for integration tests.
+//!
+//! [`Network::start`] runs one node per development account with the `local` chain spec: Alice
+//! and Bob author and finalize blocks, and the other nodes follow them. Every node gets free
+//! ports, a fixed node key and its own base path, and writes its log next to it. The nodes are
+//! killed and their data removed when the network is dropped.
+
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+use sp_core::{Bytes, H256};
+use std::{
+	fmt,
+	fs::{self, File},
+	net::TcpListener,
+	path::{Path, PathBuf},
+	process::{Child, Command, Stdio},
+	thread,
+	time::{Duration, Instant},
+};
+use tempfile::TempDir;
+
+/// Names of the nodes in start order. Each node runs with the keys of that development account.
+pub const NODE_NAMES: [&str; 6] = ["alice", "bob", "charlie", "dave", "eve", "ferdie"];
+
+/// How long a node may take to start its network.
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(60);
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Marker of the log line announcing the node's peer id.
+const IDENTITY_MARKER: &str = "Local node identity is: ";
+
+/// Why the network could not be started or did not reach the expected state.
+#[derive(Debug)]
+pub enum Error {
+	/// More nodes were requested than there are development accounts.
+	TooManyNodes(usize),
+	/// Setting up or launching the nodes failed.
+	Io(std::io::Error),
+	/// A condition was not met in time.
+	Timeout(String),
+	/// An RPC request failed.
+	Rpc(String),
+}
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Error::TooManyNodes(count) =>
+				write!(f, "{} nodes requested, at most {} are supported", count, NODE_NAMES.len()),
+			Error::Io(e) => write!(f, "I/O error: {}", e),
+			Error::Timeout(what) => write!(f, "timed out waiting for {}", what),
+			Error::Rpc(e) => write!(f, "RPC error: {}", e),
+		}
+	}
+}
+
+impl std::error::Error for Error {}
+
+impl From<std::io::Error> for Error {
+	fn from(e: std::io::Error) -> Self {
+		Error::Io(e)
+	}
+}
+
+/// A JSON-RPC client of a node's HTTP server.
+#[derive(Clone)]
+pub struct RpcClient {
+	url: String,
+	agent: ureq::Agent,
+}
+
+#[derive(Deserialize)]
+struct Header {
+	number: String,
+}
+
+#[derive(Deserialize)]
+struct Health {
+	peers: usize,
+}
+
+impl RpcClient {
+	pub fn new(url: impl Into<String>) -> Self {
+		let agent = ureq::AgentBuilder::new().timeout(Duration::from_secs(10)).build();
+		RpcClient { url: url.into(), agent }
+	}
+
+	pub fn url(&self) -> &str {
+		&self.url
+	}
+
+	/// Call `method` with `params` and decode its result.
+	pub fn request<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T, Error> {
+		let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+		let response: Value = self
+			.agent
+			.post(&self.url)
+			.send_json(request)
+			.map_err(|e| Error::Rpc(format!("{}: {}", method, e)))?
+			.into_json()
+			.map_err(|e| Error::Rpc(format!("{}: {}", method, e)))?;
+		if let Some(error) = response.get("error") {
+			return Err(Error::Rpc(format!("{}: {}", method, error)))
+		}
+		serde_json::from_value(response["result"].clone())
+			.map_err(|e| Error::Rpc(format!("{}: unexpected result: {}", method, e)))
+	}
+
+	fn header_number(&self, at: Option<H256>) -> Result<u32, Error> {
+		let header: Header = self.request("chain_getHeader", json!([at]))?;
+		parse_number(&header.number)
+	}
+
+	/// Number of the best block.
+	pub fn best_number(&self) -> Result<u32, Error> {
+		self.header_number(None)
+	}
+
+	/// Hash of the last finalized block.
+	pub fn finalized_head(&self) -> Result<H256, Error> {
+		self.request("chain_getFinalizedHead", json!([]))
+	}
+
+	/// Number of the last finalized block.
+	pub fn finalized_number(&self) -> Result<u32, Error> {
+		self.header_number(Some(self.finalized_head()?))
+	}
+
+	/// Hash of the block `number` of the best chain, if the node has it.
+	pub fn block_hash(&self, number: u32) -> Result<Option<H256>, Error> {
+		self.request("chain_getBlockHash", json!([number]))
+	}
+
+	/// Number of peers the node is connected to.
+	pub fn peers(&self) -> Result<usize, Error> {
+		let health: Health = self.request("system_health", json!([]))?;
+		Ok(health.peers)
+	}
+
+	/// Submit a signed, SCALE encoded extrinsic and return its hash.
+	pub fn submit_extrinsic(&self, extrinsic: Bytes) -> Result<H256, Error> {
+		self.request("author_submitExtrinsic", json!([extrinsic]))
+	}
+}
+
+/// A node of a [`Network`], killed when dropped.
+pub struct Node {
+	name: &'static str,
+	process: Child,
+	rpc: RpcClient,
+	log: PathBuf,
+	p2p_port: u16,
+	peer_id: String,
+}
+
+impl Node {
+	/// Start node `index` in `base_path`, connecting to `bootnode` if given, and wait until it
+	/// announces its peer id.
+	fn launch(
+		binary: &Path,
+		base_path: &Path,
+		index: usize,
+		ports: &[u16],
+		bootnode: Option<String>,
+	) -> Result<Self, Error> {
+		let name = NODE_NAMES[index];
+		let (p2p_port, rpc_port, ws_port) = (ports[0], ports[1], ports[2]);
+		let log = base_path.join(format!("{}.log", name));
+		let mut command = Command::new(binary);
+		command
+			.args(["--chain", "local", "--no-mdns", "--no-prometheus", "--no-telemetry"])
+			.args(["--disable-log-color", &format!("--{}", name)])
+			.arg("--base-path")
+			.arg(base_path.join(name))
+			// Fixed node keys keep the peer ids the same from one run to the next.
+			.args(["--node-key", &format!("{:064x}", index + 1)])
+			.args(["--port", &p2p_port.to_string()])
+			.args(["--rpc-port", &rpc_port.to_string(), "--ws-port", &ws_port.to_string()])
+			.stdout(Stdio::null())
+			.stderr(File::create(&log)?);
+		if let Some(bootnode) = bootnode {
+			command.args(["--bootnodes", &bootnode]);
+		}
+
+		let process = command.spawn()?;
+		let rpc = RpcClient::new(format!("http://127.0.0.1:{}", rpc_port));
+		let mut node = Node { name, process, rpc, log, p2p_port, peer_id: String::new() };
+		node.peer_id = wait_until(
+			STARTUP_TIMEOUT,
+			|| format!("{} to start", name),
+			|| Ok(fs::read_to_string(&node.log)?.lines().find_map(parse_identity)),
+		)?;
+		Ok(node)
+	}
+
+	pub fn name(&self) -> &str {
+		self.name
+	}
+
+	pub fn rpc(&self) -> &RpcClient {
+		&self.rpc
+	}
+
+	/// File the node writes its log to.
+	pub fn log_path(&self) -> &Path {
+		&self.log
+	}
+
+	pub fn peer_id(&self) -> &str {
+		&self.peer_id
+	}
+
+	/// Address other nodes connect to, as passed to `--bootnodes`.
+	pub fn multiaddr(&self) -> String {
+		format!("/ip4/127.0.0.1/tcp/{}/p2p/{}", self.p2p_port, self.peer_id)
+	}
+}
+
+impl Drop for Node {
+	fn drop(&mut self) {
+		let _ = self.process.kill();
+		let _ = self.process.wait();
+	}
+}
+
+/// Nodes of the `local` chain, each running in its own process.
+pub struct Network {
+	nodes: Vec<Node>,
+	// Declared after `nodes`, so that the nodes are stopped before their data is removed.
+	base_path: TempDir,
+}
+
+impl Network {
+	/// Start `count` nodes running `binary`. The first node is the bootnode of the others.
+	pub fn start(binary: impl AsRef<Path>, count: usize) -> Result<Self, Error> {
+		if count > NODE_NAMES.len() {
+			return Err(Error::TooManyNodes(count))
+		}
+		let base_path = tempfile::tempdir()?;
+		let ports = free_ports(3 * count)?;
+		let mut nodes: Vec<Node> = Vec::with_capacity(count);
+		for (index, ports) in ports.chunks(3).enumerate() {
+			let bootnode = nodes.first().map(Node::multiaddr);
+			nodes.push(Node::launch(binary.as_ref(), base_path.path(), index, ports, bootnode)?);
+		}
+		Ok(Network { nodes, base_path })
+	}
+
+	pub fn nodes(&self) -> &[Node] {
+		&self.nodes
+	}
+
+	/// Directory holding the base paths and logs of the nodes.
+	pub fn base_path(&self) -> &Path {
+		self.base_path.path()
+	}
+
+	/// Wait until every node is connected to all the others.
+	pub fn wait_for_peers(&self, timeout: Duration) -> Result<(), Error> {
+		let expected = self.nodes.len().saturating_sub(1);
+		self.wait_for_all(timeout, &format!("{} peers", expected), |rpc| {
+			Ok(rpc.peers()? >= expected)
+		})
+	}
+
+	/// Wait until every node has finalized block `number`.
+	pub fn wait_for_finalized(&self, number: u32, timeout: Duration) -> Result<(), Error> {
+		self.wait_for_all(timeout, &format!("block #{} to be finalized", number), |rpc| {
+			Ok(rpc.finalized_number()? >= number)
+		})
+	}
+
+	fn wait_for_all(
+		&self,
+		timeout: Duration,
+		description: &str,
+		reached: impl Fn(&RpcClient) -> Result<bool, Error>,
+	) -> Result<(), Error> {
+		let deadline = Instant::now() + timeout;
+		for node in &self.nodes {
+			let remaining = deadline.saturating_duration_since(Instant::now());
+			let what =
+				|| format!("{} on {} (log in {})", description, node.name, node.log.display());
+			// RPC errors are expected while the node is starting.
+			wait_until(remaining, what, || Ok(reached(&node.rpc).unwrap_or(false).then_some(())))?;
+		}
+		Ok(())
+	}
+}
+
+/// Poll `check` until it returns a value, for at most `timeout`.
+fn wait_until<T>(
+	timeout: Duration,
+	what: impl Fn() -> String,
+	mut check: impl FnMut() -> Result<Option<T>, Error>,
+) -> Result<T, Error> {
+	let deadline = Instant::now() + timeout;
+	loop {
+		if let Some(value) = check()? {
+			return Ok(value)
+		}
+		if Instant::now() >= deadline {
+			return Err(Error::Timeout(what()))
+		}
+		thread::sleep(POLL_INTERVAL);
+	}
+}
+
+/// `count` distinct ports that are free on the loopback interface.
+fn free_ports(count: usize) -> Result<Vec<u16>, Error> {
+	// Holding every listener until all ports are chosen keeps them distinct.
+	let listeners = (0..count)
+		.map(|_| TcpListener::bind("127.0.0.1:0"))
+		.collect::<Result<Vec<_>, _>>()?;
+	let ports = listeners
+		.iter()
+		.map(|listener| listener.local_addr().map(|address| address.port()))
+		.collect::<Result<_, _>>()?;
+	Ok(ports)
+}
+
+/// The peer id announced by a log line of the node.
+fn parse_identity(line: &str) -> Option<String> {
+	let (_, rest) = line.split_once(IDENTITY_MARKER)?;
+	rest.split_whitespace().next().map(Into::into)
+}
+
+/// A block number as serialized in headers, a hex string.
+fn parse_number(number: &str) -> Result<u32, Error> {
+	u32::from_str_radix(number.trim_start_matches("0x"), 16)
+		.map_err(|e| Error::Rpc(format!("invalid block number `{}`: {}", number, e)))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_identity() {
+		let line =
+			"🏷  Local node identity is: 12D3KooWEyoppNCUx8Yx66oV9fJnriXwCcXwDDUA2kj6vnc6iDEp";
+		assert_eq!(
+			parse_identity(line).as_deref(),
+			Some("12D3KooWEyoppNCUx8Yx66oV9fJnriXwCcXwDDUA2kj6vnc6iDEp")
+		);
+		assert_eq!(parse_identity("2022-10-18 12:00:00 Imported #1"), None);
+	}
+
+	#[test]
+	fn parses_header_numbers() {
+		assert_eq!(parse_number("0x1f").unwrap(), 31);
+		assert!(matches!(parse_number("0xzz"), Err(Error::Rpc(_))));
+	}
+
+	#[test]
+	fn free_ports_are_distinct() {
+		let mut ports = free_ports(6).unwrap();
+		ports.sort_unstable();
+		ports.dedup();
+		assert_eq!(ports.len(), 6);
+	}
+
+	#[test]
+	fn rejects_too_many_nodes() {
+		assert!(matches!(
+			Network::start("node-template", NODE_NAMES.len() + 1),
+			Err(Error::TooManyNodes(_))
+		));
+	}
+}
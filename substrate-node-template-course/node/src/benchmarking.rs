@@ -23,7 +23,7 @@
 use crate::service::FullClient;
 
 use node_template_runtime as runtime;
-use runtime::{AccountId, Balance, BalancesCall, SystemCall};
+use runtime::{AccountId, Balance, BalancesCall, SystemCall, TemplateModuleCall};
 use sc_cli::Result;
 use sc_client_api::BlockBackend;
 use sp_core::{Encode, Pair};
@@ -113,6 +113,103 @@ impl frame_benchmarking_cli::ExtrinsicBuilder for TransferKeepAliveBuilder {
 	}
 }
 
+/// Generates extrinsics of any call for the benchmarks, signed by Bob.
+///
+/// `call` builds the call of the extrinsic with the given nonce, so that calls creating entries
+/// can use a new id for every extrinsic.
+///
+/// Note: Should only be used for benchmarking.
+pub struct CallBuilder<F> {
+	client: Arc<FullClient>,
+	pallet: &'static str,
+	extrinsic: &'static str,
+	call: F,
+}
+
+impl<F: Fn(u32) -> runtime::RuntimeCall> CallBuilder<F> {
+	/// Creates a new [`Self`] building `pallet::extrinsic` calls with `call`.
+	pub fn new(
+		client: Arc<FullClient>,
+		pallet: &'static str,
+		extrinsic: &'static str,
+		call: F,
+	) -> Self {
+		Self { client, pallet, extrinsic, call }
+	}
+}
+
+impl<F: Fn(u32) -> runtime::RuntimeCall> frame_benchmarking_cli::ExtrinsicBuilder
+	for CallBuilder<F>
+{
+	fn pallet(&self) -> &str {
+		self.pallet
+	}
+
+	fn extrinsic(&self) -> &str {
+		self.extrinsic
+	}
+
+	fn build(&self, nonce: u32) -> std::result::Result<OpaqueExtrinsic, &'static str> {
+		let acc = Sr25519Keyring::Bob.pair();
+		let extrinsic: OpaqueExtrinsic =
+			create_benchmark_extrinsic(self.client.as_ref(), acc, (self.call)(nonce), nonce).into();
+
+		Ok(extrinsic)
+	}
+}
+
+/// Builds extrinsics re-registering Bob's company under a new id, with a name and description of
+/// typical length.
+pub fn register_company_builder(
+	client: Arc<FullClient>,
+) -> CallBuilder<impl Fn(u32) -> runtime::RuntimeCall> {
+	CallBuilder::new(client, "template_module", "register_company", |nonce| {
+		TemplateModuleCall::register_company {
+			name: b"Benchmark Logistics Ltd".to_vec(),
+			id: nonce as u64 + 1,
+			about_me: [b'x'; 256].to_vec(),
+		}
+		.into()
+	})
+}
+
+/// Builds extrinsics creating a new product each.
+pub fn create_product_builder(
+	client: Arc<FullClient>,
+) -> CallBuilder<impl Fn(u32) -> runtime::RuntimeCall> {
+	CallBuilder::new(client, "template_module", "create_product", |nonce| {
+		TemplateModuleCall::create_product {
+			id: nonce as u64 + 1,
+			name: b"Pallet of steel coils".to_vec(),
+			description: [b'x'; 256].to_vec(),
+			owner: 1,
+			previous_owners: vec![2, 3, 4],
+		}
+		.into()
+	})
+}
+
+/// Builds extrinsics creating a new supply contract each. The contract value stays at the
+/// multisig threshold, so no multisig is needed.
+pub fn create_supply_contract_builder(
+	client: Arc<FullClient>,
+) -> CallBuilder<impl Fn(u32) -> runtime::RuntimeCall> {
+	CallBuilder::new(client, "template_module", "create_supply_contract", |nonce| {
+		TemplateModuleCall::create_supply_contract {
+			id: nonce as u64 + 1,
+			seller_id: 1,
+			buyer_id: 2,
+			products: (1..=10).collect(),
+			delivered: false,
+			iou: 0,
+			contract_value: runtime::MultisigContractThreshold::get(),
+			contract_fulfilled: false,
+			asset_id: 0,
+		}
+		.into()
+	})
+}
+
 /// Create a transaction using the given `call`.
 ///
 /// Note: Should only be used for benchmarking.
@@ -141,7 +238,7 @@ pub fn create_benchmark_extrinsic(
 		)),
 		frame_system::CheckNonce::<runtime::Runtime>::from(nonce),
 		frame_system::CheckWeight::<runtime::Runtime>::new(),
-		pallet_transaction_payment::ChargeTransactionPayment::<runtime::Runtime>::from(0),
+		runtime::ChargeSponsoredTransactionPayment::from(0),
 	);
 
 	let raw_payload = runtime::SignedPayload::from_raw(
@@ -0,0 +1,193 @@
+//! A stand-in remote signer for `--keystore-uri`.
+//!
+//! Serves the signing protocol described in `node_template::remote_keystore` from a local
+//! keystore, so that tests and small deployments can keep validator keys off the node host.
+//! Populate the keystore with `node-template key insert --keystore-path <dir>`, or through the
+//! `/generate` request.
+//!
+//! Only requests with the shared secret of `--token-file` are served, and only for the key types
+//! of `--key-types`, so that a node cannot have the signer sign with other keys in its keystore.
+
+use clap::Parser;
+use node_template::remote_keystore::protocol::*;
+use sc_keystore::LocalKeystore;
+use serde::{de::DeserializeOwned, Serialize};
+use sp_core::{
+	crypto::{ByteArray, CryptoTypeId, CryptoTypePublicPair, KeyTypeId},
+	ecdsa, ed25519, sr25519,
+};
+use sp_keystore::SyncCryptoStore;
+use std::{
+	io::{Cursor, Read, Write},
+	path::PathBuf,
+};
+use tiny_http::{Method, Request, Response, Server};
+
+#[derive(Debug, Parser)]
+struct Args {
+	/// Address to listen on. With port 0 a free port is picked and printed.
+	#[clap(long, default_value = "127.0.0.1:9955")]
+	listen: String,
+
+	/// Directory of the keystore. Keys are only kept in memory if it is not given.
+	#[clap(long)]
+	keystore_path: Option<PathBuf>,
+
+	/// File holding the shared secret that nodes send as `Authorization: Bearer <secret>`.
+	#[clap(long, value_name = "PATH")]
+	token_file: PathBuf,
+
+	/// Key types that may be listed, generated and signed with.
+	#[clap(long, value_name = "ID", use_value_delimiter = true, default_value = "aura,gran")]
+	key_types: Vec<String>,
+}
+
+/// What the signer serves.
+struct Signer {
+	keystore: LocalKeystore,
+	token: String,
+	key_types: Vec<KeyTypeId>,
+}
+
+fn main() -> Result<(), String> {
+	let args = Args::parse();
+	let keystore = match &args.keystore_path {
+		Some(path) => LocalKeystore::open(path, None)
+			.map_err(|e| format!("Error opening keystore `{}`: {}", path.display(), e))?,
+		None => LocalKeystore::in_memory(),
+	};
+	let key_types = args
+		.key_types
+		.iter()
+		.map(|id| id_from_str(id).map(KeyTypeId))
+		.collect::<Result<_, _>>()?;
+	let signer = Signer { keystore, token: read_token(&args.token_file)?, key_types };
+	let server = Server::http(&args.listen)
+		.map_err(|e| format!("Error listening on {}: {}", args.listen, e))?;
+
+	// Tests read the address from the first line of output.
+	println!("Listening on {}", server.server_addr());
+	std::io::stdout().flush().map_err(|e| e.to_string())?;
+
+	for mut request in server.incoming_requests() {
+		let response = if !authorized(&signer.token, &request) {
+			Response::from_string("missing or wrong token").with_status_code(401)
+		} else if request.body_length().map_or(false, |length| length > MAX_REQUEST_SIZE) {
+			too_large()
+		} else {
+			match read_body(&mut request) {
+				Ok(None) => too_large(),
+				Ok(Some(body)) => match handle(&signer, &request, &body) {
+					Ok(body) => Response::from_string(body),
+					Err(e) => Response::from_string(e).with_status_code(400),
+				},
+				Err(e) => Response::from_string(e).with_status_code(400),
+			}
+		};
+		if let Err(e) = request.respond(response) {
+			eprintln!("Error responding: {}", e);
+		}
+	}
+	Ok(())
+}
+
+/// Whether `request` carries the bearer `token`.
+fn authorized(token: &str, request: &Request) -> bool {
+	let expected = format!("Bearer {}", token);
+	request.headers().iter().any(|header| {
+		header.field.equiv("Authorization") && equal(header.value.as_bytes(), expected.as_bytes())
+	})
+}
+
+/// Read the body of `request`, or `None` if it is longer than `MAX_REQUEST_SIZE`, which chunked
+/// requests do not announce up front.
+fn read_body(request: &mut Request) -> Result<Option<String>, String> {
+	let mut body = String::new();
+	request
+		.as_reader()
+		.take(MAX_REQUEST_SIZE as u64 + 1)
+		.read_to_string(&mut body)
+		.map_err(|e| e.to_string())?;
+	Ok(if body.len() > MAX_REQUEST_SIZE { None } else { Some(body) })
+}
+
+fn too_large() -> Response<Cursor<Vec<u8>>> {
+	Response::from_string(format!("request body exceeds {} bytes", MAX_REQUEST_SIZE))
+		.with_status_code(413)
+}
+
+/// Compare `a` and `b` in a time that does not depend on where they differ.
+fn equal(a: &[u8], b: &[u8]) -> bool {
+	a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+fn handle(signer: &Signer, request: &Request, body: &str) -> Result<String, String> {
+	if request.method() != &Method::Post {
+		return Err("only POST requests are supported".into())
+	}
+	let keystore = &signer.keystore;
+	let key_type = |id: &str| {
+		let id = KeyTypeId(id_from_str(id)?);
+		if signer.key_types.contains(&id) {
+			Ok(id)
+		} else {
+			Err(format!("key type `{}` is not served", id_to_string(id.0)))
+		}
+	};
+
+	match request.url() {
+		"/keys" => {
+			let request: KeysRequest = parse(body)?;
+			let id = key_type(&request.key_type)?;
+			let keys = SyncCryptoStore::keys(keystore, id).map_err(|e| e.to_string())?;
+			let keys = keys
+				.into_iter()
+				.map(|key| Key { crypto: id_to_string(key.0 .0), public: key.1.into() })
+				.collect();
+			json(&KeysResponse { keys })
+		},
+		"/generate" => {
+			let request: GenerateRequest = parse(body)?;
+			let id = key_type(&request.key_type)?;
+			let seed = request.seed.as_deref();
+			let public = match CryptoTypeId(id_from_str(&request.crypto)?) {
+				sr25519::CRYPTO_ID =>
+					keystore.sr25519_generate_new(id, seed).map(|public| public.to_raw_vec()),
+				ed25519::CRYPTO_ID =>
+					keystore.ed25519_generate_new(id, seed).map(|public| public.to_raw_vec()),
+				ecdsa::CRYPTO_ID =>
+					keystore.ecdsa_generate_new(id, seed).map(|public| public.to_raw_vec()),
+				_ => return Err(format!("unsupported crypto `{}`", request.crypto)),
+			}
+			.map_err(|e| e.to_string())?;
+			json(&GenerateResponse { public: public.into() })
+		},
+		"/sign" => {
+			let request: SignRequest = parse(body)?;
+			let id = key_type(&request.key_type)?;
+			let crypto = CryptoTypeId(id_from_str(&request.crypto)?);
+			let key = CryptoTypePublicPair(crypto, request.public.0);
+			let signature = SyncCryptoStore::sign_with(keystore, id, &key, &request.message)
+				.map_err(|e| e.to_string())?;
+			json(&SignResponse { signature: signature.map(Into::into) })
+		},
+		"/has_keys" => {
+			let request: HasKeysRequest = parse(body)?;
+			let keys = request
+				.keys
+				.into_iter()
+				.map(|key| Ok((key.public.0, key_type(&key.key_type)?)))
+				.collect::<Result<Vec<_>, String>>()?;
+			json(&HasKeysResponse { has_keys: SyncCryptoStore::has_keys(keystore, &keys) })
+		},
+		path => Err(format!("unknown path `{}`", path)),
+	}
+}
+
+fn parse<T: DeserializeOwned>(body: &str) -> Result<T, String> {
+	serde_json::from_str(body).map_err(|e| format!("invalid request: {}", e))
+}
+
+fn json<T: Serialize>(response: &T) -> Result<String, String> {
+	serde_json::to_string(response).map_err(|e| e.to_string())
+}
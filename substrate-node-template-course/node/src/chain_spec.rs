@@ -1,12 +1,16 @@
 use node_template_runtime::{
-	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig, Signature, SudoConfig,
-	SystemConfig, WASM_BINARY,
+	opaque::SessionKeys, AccountId, AuraConfig, Balance, BalancesConfig, CouncilMembershipConfig,
+	GenesisConfig, GrandpaConfig, SessionConfig, Signature, SystemConfig, TemplateModuleConfig,
+	ValidatorSetConfig, MILLISECS_PER_BLOCK, WASM_BINARY,
 };
-use sc_service::ChainType;
+use sc_service::{ChainType, Properties};
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_core::{sr25519, Pair, Public};
 use sp_finality_grandpa::AuthorityId as GrandpaId;
 use sp_runtime::traits::{IdentifyAccount, Verify};
+use std::{fs, path::Path};
+
+mod consortium;
 
 // The URL for the telemetry server.
 // const STAGING_TELEMETRY_URL: &str = "wss://telemetry.polkadot.io/submit/";
@@ -31,9 +35,57 @@ where
 	AccountPublic::from(get_from_seed::<TPublic>(seed)).into_account()
 }
 
-/// Generate an Aura authority key.
-pub fn authority_keys_from_seed(s: &str) -> (AuraId, GrandpaId) {
-	(get_from_seed::<AuraId>(s), get_from_seed::<GrandpaId>(s))
+/// Generate the validator account and its Aura and Grandpa session keys.
+pub fn authority_keys_from_seed(s: &str) -> (AccountId, AuraId, GrandpaId) {
+	(
+		get_account_id_from_seed::<sr25519::Public>(s),
+		get_from_seed::<AuraId>(s),
+		get_from_seed::<GrandpaId>(s),
+	)
+}
+
+fn session_keys(aura: AuraId, grandpa: GrandpaId) -> SessionKeys {
+	SessionKeys { aura, grandpa }
+}
+
+/// The chain spec property holding the block time in milliseconds.
+const BLOCK_TIME_PROPERTY: &str = "blockTime";
+
+/// Chain spec properties of a chain producing a block every `block_time` milliseconds.
+fn properties(block_time: u64) -> Properties {
+	let mut properties = Properties::new();
+	properties.insert(BLOCK_TIME_PROPERTY.into(), block_time.into());
+	properties
+}
+
+/// Load a chain spec from a JSON file.
+///
+/// The `blockTime` property of a non-raw spec overrides the block time of its validator set
+/// genesis, so that operators only need to edit the properties. Raw specs already hold the
+/// block time in their genesis storage and are loaded unchanged.
+pub fn from_json_file(path: &Path) -> Result<ChainSpec, String> {
+	let bytes = fs::read(path)
+		.map_err(|e| format!("Error opening spec file `{}`: {}", path.display(), e))?;
+	let mut json: serde_json::Value =
+		serde_json::from_slice(&bytes).map_err(|e| format!("Error parsing spec file: {}", e))?;
+
+	let block_time = match json.pointer(&format!("/properties/{}", BLOCK_TIME_PROPERTY)) {
+		Some(value) => Some(
+			value
+				.as_u64()
+				.filter(|block_time| *block_time > 0)
+				.ok_or_else(|| format!("`{}` must be a positive integer", BLOCK_TIME_PROPERTY))?,
+		),
+		None => None,
+	};
+	if let (Some(block_time), Some(validator_set)) =
+		(block_time, json.pointer_mut("/genesis/runtime/validatorSet"))
+	{
+		validator_set["blockTime"] = block_time.into();
+	}
+
+	let bytes = serde_json::to_vec(&json).map_err(|e| format!("Error encoding spec: {}", e))?;
+	ChainSpec::from_json_bytes(bytes)
 }
 
 pub fn development_config() -> Result<ChainSpec, String> {
@@ -50,8 +102,10 @@ pub fn development_config() -> Result<ChainSpec, String> {
 				wasm_binary,
 				// Initial PoA authorities
 				vec![authority_keys_from_seed("Alice")],
-				// Sudo account
-				get_account_id_from_seed::<sr25519::Public>("Alice"),
+				// Block time in milliseconds
+				MILLISECS_PER_BLOCK,
+				// Initial council members
+				vec![get_account_id_from_seed::<sr25519::Public>("Alice")],
 				// Pre-funded accounts
 				vec![
 					get_account_id_from_seed::<sr25519::Public>("Alice"),
@@ -70,7 +124,7 @@ pub fn development_config() -> Result<ChainSpec, String> {
 		None,
 		None,
 		// Properties
-		None,
+		Some(properties(MILLISECS_PER_BLOCK)),
 		// Extensions
 		None,
 	))
@@ -90,8 +144,13 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 				wasm_binary,
 				// Initial PoA authorities
 				vec![authority_keys_from_seed("Alice"), authority_keys_from_seed("Bob")],
-				// Sudo account
-				get_account_id_from_seed::<sr25519::Public>("Alice"),
+				// Block time in milliseconds
+				MILLISECS_PER_BLOCK,
+				// Initial council members
+				vec![
+					get_account_id_from_seed::<sr25519::Public>("Alice"),
+					get_account_id_from_seed::<sr25519::Public>("Bob"),
+				],
 				// Pre-funded accounts
 				vec![
 					get_account_id_from_seed::<sr25519::Public>("Alice"),
@@ -116,9 +175,55 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 		None,
 		// Protocol ID
 		None,
+		None,
 		// Properties
+		Some(properties(MILLISECS_PER_BLOCK)),
+		// Extensions
 		None,
+	))
+}
+
+/// Load the consortium file at `path` and build the chain spec of the network it describes.
+///
+/// See the `consortium` module for the file format.
+pub fn consortium_config(path: &Path) -> Result<ChainSpec, String> {
+	let wasm_binary = WASM_BINARY.ok_or_else(|| "Consortium wasm not available".to_string())?;
+	let consortium = consortium::load(path)?;
+
+	let mut properties = properties(consortium.block_time);
+	properties.insert("tokenSymbol".into(), consortium.token_symbol.clone().into());
+	properties.insert("tokenDecimals".into(), consortium.token_decimals.into());
+	properties.insert("ss58Format".into(), consortium.ss58_prefix.into());
+
+	Ok(ChainSpec::from_genesis(
+		// Name
+		&consortium.name,
+		// ID
+		&consortium.id,
+		consortium.chain_type.clone(),
+		{
+			let consortium = consortium.clone();
+			move || {
+				genesis(
+					wasm_binary,
+					consortium.authorities.clone(),
+					consortium.block_time,
+					consortium.ss58_prefix,
+					consortium.council.clone(),
+					consortium.balances.clone(),
+					consortium.companies.clone(),
+				)
+			}
+		},
+		// Bootnodes
+		vec![],
+		// Telemetry
 		None,
+		// Protocol ID
+		consortium.protocol_id.as_deref(),
+		None,
+		// Properties
+		Some(properties),
 		// Extensions
 		None,
 	))
@@ -127,30 +232,65 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 /// Configure initial storage state for FRAME modules.
 fn testnet_genesis(
 	wasm_binary: &[u8],
-	initial_authorities: Vec<(AuraId, GrandpaId)>,
-	root_key: AccountId,
+	initial_authorities: Vec<(AccountId, AuraId, GrandpaId)>,
+	block_time: u64,
+	council_members: Vec<AccountId>,
 	endowed_accounts: Vec<AccountId>,
 	_enable_println: bool,
+) -> GenesisConfig {
+	genesis(
+		wasm_binary,
+		initial_authorities,
+		block_time,
+		// The generic substrate prefix.
+		42,
+		council_members,
+		// Configure endowed accounts with initial balance of 1 << 60.
+		endowed_accounts.into_iter().map(|k| (k, 1 << 60)).collect(),
+		vec![],
+	)
+}
+
+/// Genesis of a chain with the given validators, address prefix, council, balances and founding
+/// companies.
+fn genesis(
+	wasm_binary: &[u8],
+	initial_authorities: Vec<(AccountId, AuraId, GrandpaId)>,
+	block_time: u64,
+	ss58_prefix: u16,
+	council_members: Vec<AccountId>,
+	balances: Vec<(AccountId, Balance)>,
+	companies: Vec<(AccountId, u64, Vec<u8>, Vec<u8>)>,
 ) -> GenesisConfig {
 	GenesisConfig {
 		system: SystemConfig {
 			// Add Wasm runtime to storage.
 			code: wasm_binary.to_vec(),
 		},
-		balances: BalancesConfig {
-			// Configure endowed accounts with initial balance of 1 << 60.
-			balances: endowed_accounts.iter().cloned().map(|k| (k, 1 << 60)).collect(),
-		},
-		aura: AuraConfig {
-			authorities: initial_authorities.iter().map(|x| (x.0.clone())).collect(),
+		balances: BalancesConfig { balances },
+		validator_set: ValidatorSetConfig {
+			initial_validators: initial_authorities.iter().map(|x| x.0.clone()).collect(),
+			block_time,
+			ss58_prefix,
 		},
-		grandpa: GrandpaConfig {
-			authorities: initial_authorities.iter().map(|x| (x.1.clone(), 1)).collect(),
+		session: SessionConfig {
+			keys: initial_authorities
+				.iter()
+				.map(|x| (x.0.clone(), x.0.clone(), session_keys(x.1.clone(), x.2.clone())))
+				.collect(),
 		},
-		sudo: SudoConfig {
-			// Assign network admin rights.
-			key: Some(root_key),
+		// Aura and Grandpa take their authorities from the session keys.
+		aura: AuraConfig { authorities: vec![] },
+		grandpa: GrandpaConfig { authorities: vec![] },
+		council: Default::default(),
+		council_membership: CouncilMembershipConfig {
+			// Council motions replace sudo for runtime upgrades and other root calls.
+			members: council_members.try_into().expect("too many initial council members"),
+			phantom: Default::default(),
 		},
 		transaction_payment: Default::default(),
+		// Assets for supply contracts and IOUs are created on chain with `Assets::create`.
+		assets: Default::default(),
+		template_module: TemplateModuleConfig { companies },
 	}
 }
@@ -0,0 +1,422 @@
+//! The file format of consortium deployments.
+//!
+//! A consortium file lists the validators, council, founding companies and token of a network,
+//! in TOML or JSON. Accounts and keys are SS58 addresses encoded with the `ss58_prefix` of the
+//! token:
+//!
+//! ```toml
+//! name = "Acme Supply Network"
+//! id = "acme"
+//! block_time = 6000
+//! council = ["5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"]
+//!
+//! [token]
+//! symbol = "ACME"
+//! decimals = 12
+//! ss58_prefix = 42
+//!
+//! [[authorities]]
+//! account = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+//! aura = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+//! grandpa = "5FA9nQDVg267DEd8m1ZypXLBnvN7SFxYwV7ndqSYGiN9TTpu"
+//!
+//! [[companies]]
+//! account = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+//! id = 1
+//! name = "Acme Logistics"
+//!
+//! [[balances]]
+//! account = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+//! amount = 1000000000000000
+//! ```
+
+use node_template_runtime::{
+	AccountId, Balance, CouncilMaxMembers, EXISTENTIAL_DEPOSIT, MILLISECS_PER_BLOCK,
+};
+use sc_service::ChainType;
+use serde::Deserialize;
+use sp_consensus_aura::sr25519::AuthorityId as AuraId;
+use sp_core::{crypto::Ss58Codec, ed25519, sr25519};
+use sp_finality_grandpa::AuthorityId as GrandpaId;
+use std::{collections::BTreeSet, fs, path::Path};
+
+/// A consortium file as written by the operator.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConsortiumFile {
+	pub name: String,
+	pub id: String,
+	#[serde(default)]
+	pub chain_type: Option<String>,
+	#[serde(default)]
+	pub protocol_id: Option<String>,
+	#[serde(default)]
+	pub block_time: Option<u64>,
+	pub token: Token,
+	pub authorities: Vec<Authority>,
+	pub council: Vec<String>,
+	#[serde(default)]
+	pub companies: Vec<FoundingCompany>,
+	#[serde(default)]
+	pub balances: Vec<Endowment>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Token {
+	pub symbol: String,
+	pub decimals: u8,
+	pub ss58_prefix: u16,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Authority {
+	pub account: String,
+	pub aura: String,
+	pub grandpa: String,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FoundingCompany {
+	pub account: String,
+	pub id: u64,
+	pub name: String,
+	#[serde(default)]
+	pub about: String,
+}
+
+/// An amount of native tokens given to an account at genesis. TOML integers are 64-bit, so
+/// amounts are too. Amounts below the existential deposit would not create the account.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Endowment {
+	pub account: String,
+	pub amount: u64,
+}
+
+/// A validated consortium file.
+#[derive(Clone)]
+pub struct Consortium {
+	pub name: String,
+	pub id: String,
+	pub chain_type: ChainType,
+	pub protocol_id: Option<String>,
+	pub block_time: u64,
+	pub token_symbol: String,
+	pub token_decimals: u8,
+	pub ss58_prefix: u16,
+	pub authorities: Vec<(AccountId, AuraId, GrandpaId)>,
+	pub council: Vec<AccountId>,
+	pub companies: Vec<(AccountId, u64, Vec<u8>, Vec<u8>)>,
+	pub balances: Vec<(AccountId, Balance)>,
+}
+
+/// Read and validate the consortium file at `path`. `.toml` files are read as TOML, all others
+/// as JSON.
+pub fn load(path: &Path) -> Result<Consortium, String> {
+	let contents = fs::read_to_string(path)
+		.map_err(|e| format!("Error opening consortium file `{}`: {}", path.display(), e))?;
+	parse(&contents, path.extension().map_or(false, |ext| ext == "toml"))?.validate()
+}
+
+/// Parse the `contents` of a consortium file, as TOML if `toml` is set and as JSON otherwise.
+fn parse(contents: &str, toml: bool) -> Result<ConsortiumFile, String> {
+	if toml {
+		toml::from_str(contents).map_err(|e| format!("Error parsing consortium file: {}", e))
+	} else {
+		serde_json::from_str(contents).map_err(|e| format!("Error parsing consortium file: {}", e))
+	}
+}
+
+impl ConsortiumFile {
+	/// Check the file and decode its accounts and keys.
+	pub fn validate(self) -> Result<Consortium, String> {
+		if self.name.trim().is_empty() {
+			return Err("`name` must not be empty".into())
+		}
+		if self.id.is_empty() || !self.id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+			return Err("`id` must be one or more ASCII letters, digits or `_`".into())
+		}
+		let chain_type = match self.chain_type.as_deref() {
+			None | Some("live") => ChainType::Live,
+			Some("local") => ChainType::Local,
+			Some("development") => ChainType::Development,
+			Some(other) =>
+				return Err(format!(
+					"unknown `chain_type` `{}`, expected `live`, `local` or `development`",
+					other
+				)),
+		};
+		let block_time = self.block_time.unwrap_or(MILLISECS_PER_BLOCK);
+		if block_time < 1000 {
+			return Err(format!("`block_time` must be at least 1000 ms, got {}", block_time))
+		}
+		if self.token.symbol.is_empty() || self.token.symbol.len() > 12 {
+			return Err("`token.symbol` must be 1 to 12 characters long".into())
+		}
+		if self.token.decimals > 18 {
+			return Err(format!("`token.decimals` must be at most 18, got {}", self.token.decimals))
+		}
+		// Prefixes above 16383 cannot be encoded; 46 and 47 are reserved.
+		if self.token.ss58_prefix > 16383 || matches!(self.token.ss58_prefix, 46 | 47) {
+			return Err(format!("`token.ss58_prefix` {} is not usable", self.token.ss58_prefix))
+		}
+
+		let prefix = self.token.ss58_prefix;
+		if self.authorities.is_empty() {
+			return Err("at least one entry in `authorities` is required".into())
+		}
+		let mut authorities = Vec::new();
+		for (i, authority) in self.authorities.iter().enumerate() {
+			let field = |name: &str| format!("authorities[{}].{}", i, name);
+			authorities.push((
+				decode::<AccountId>(&authority.account, prefix, &field("account"))?,
+				decode::<sr25519::Public>(&authority.aura, prefix, &field("aura"))?.into(),
+				decode::<ed25519::Public>(&authority.grandpa, prefix, &field("grandpa"))?.into(),
+			));
+		}
+		unique(authorities.iter().map(|a| &a.0), "authorities", "account")?;
+		unique(authorities.iter().map(|a| &a.1), "authorities", "aura key")?;
+		unique(authorities.iter().map(|a| &a.2), "authorities", "grandpa key")?;
+
+		if self.council.is_empty() || self.council.len() > CouncilMaxMembers::get() as usize {
+			return Err(format!(
+				"`council` must have between 1 and {} members",
+				CouncilMaxMembers::get()
+			))
+		}
+		let council = self
+			.council
+			.iter()
+			.enumerate()
+			.map(|(i, member)| decode::<AccountId>(member, prefix, &format!("council[{}]", i)))
+			.collect::<Result<Vec<_>, _>>()?;
+		unique(council.iter(), "council", "member")?;
+
+		let mut companies = Vec::new();
+		for (i, company) in self.companies.iter().enumerate() {
+			let field = format!("companies[{}].account", i);
+			let account = decode::<AccountId>(&company.account, prefix, &field)?;
+			// The same limits as `register_company`.
+			if company.id == 0 || company.id >= 10000000000000 {
+				return Err(format!("companies[{}].id must be between 1 and 9999999999999", i))
+			}
+			if company.name.is_empty() || company.name.len() > 64 {
+				return Err(format!("companies[{}].name must be 1 to 64 bytes long", i))
+			}
+			if company.about.len() > 2000 {
+				return Err(format!("companies[{}].about must be at most 2000 bytes long", i))
+			}
+			companies.push((
+				account,
+				company.id,
+				company.name.clone().into_bytes(),
+				company.about.clone().into_bytes(),
+			));
+		}
+		unique(companies.iter().map(|c| &c.0), "companies", "account")?;
+		unique(companies.iter().map(|c| &c.1), "companies", "id")?;
+
+		let balances = self
+			.balances
+			.iter()
+			.enumerate()
+			.map(|(i, endowment)| {
+				let account =
+					decode::<AccountId>(&endowment.account, prefix, &format!("balances[{}]", i))?;
+				if (endowment.amount as Balance) < EXISTENTIAL_DEPOSIT {
+					return Err(format!(
+						"balances[{}].amount {} is below the existential deposit {}",
+						i, endowment.amount, EXISTENTIAL_DEPOSIT
+					))
+				}
+				Ok((account, endowment.amount as Balance))
+			})
+			.collect::<Result<Vec<_>, String>>()?;
+		unique(balances.iter().map(|b| &b.0), "balances", "account")?;
+
+		Ok(Consortium {
+			name: self.name,
+			id: self.id,
+			chain_type,
+			protocol_id: self.protocol_id,
+			block_time,
+			token_symbol: self.token.symbol,
+			token_decimals: self.token.decimals,
+			ss58_prefix: prefix,
+			authorities,
+			council,
+			companies,
+			balances,
+		})
+	}
+}
+
+/// Decode the SS58 address `value` of `field`, which must be encoded with `prefix`.
+fn decode<T: Ss58Codec>(value: &str, prefix: u16, field: &str) -> Result<T, String> {
+	let (decoded, version) = T::from_ss58check_with_version(value)
+		.map_err(|e| format!("`{}`: invalid SS58 address `{}`: {:?}", field, value, e))?;
+	if u16::from(version) != prefix {
+		return Err(format!(
+			"`{}`: address `{}` uses SS58 prefix {}, expected {}",
+			field,
+			value,
+			u16::from(version),
+			prefix
+		))
+	}
+	Ok(decoded)
+}
+
+/// Fail if `values` contains a duplicate.
+fn unique<'a, T: Ord + 'a>(
+	values: impl Iterator<Item = &'a T>,
+	list: &str,
+	what: &str,
+) -> Result<(), String> {
+	let mut seen = BTreeSet::new();
+	for (i, value) in values.enumerate() {
+		if !seen.insert(value) {
+			return Err(format!("{}[{}] repeats an earlier {}", list, i, what))
+		}
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const ALICE: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+	const ALICE_GRANDPA: &str = "5FA9nQDVg267DEd8m1ZypXLBnvN7SFxYwV7ndqSYGiN9TTpu";
+	const BOB: &str = "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty";
+
+	fn toml(balances: &str) -> String {
+		format!(
+			r#"
+name = "Acme Supply Network"
+id = "acme"
+council = ["{alice}"]
+
+[token]
+symbol = "ACME"
+decimals = 12
+ss58_prefix = 42
+
+[[authorities]]
+account = "{alice}"
+aura = "{alice}"
+grandpa = "{grandpa}"
+
+[[companies]]
+account = "{bob}"
+id = 1
+name = "Acme Logistics"
+{balances}
+"#,
+			alice = ALICE,
+			grandpa = ALICE_GRANDPA,
+			bob = BOB,
+			balances = balances,
+		)
+	}
+
+	fn endowment(account: &str, amount: u64) -> String {
+		format!("\n[[balances]]\naccount = \"{}\"\namount = {}\n", account, amount)
+	}
+
+	#[test]
+	fn parses_and_validates_toml() {
+		let file = parse(&toml(&endowment(BOB, 1_000_000)), true).unwrap();
+		let consortium = file.validate().unwrap();
+		assert_eq!(consortium.chain_type, ChainType::Live);
+		assert_eq!(consortium.block_time, MILLISECS_PER_BLOCK);
+		assert_eq!(consortium.ss58_prefix, 42);
+		assert_eq!(consortium.authorities.len(), 1);
+		assert_eq!(consortium.companies[0].1, 1);
+		assert_eq!(consortium.companies[0].2, b"Acme Logistics".to_vec());
+		assert_eq!(consortium.balances, vec![(decode(BOB, 42, "bob").unwrap(), 1_000_000)]);
+	}
+
+	#[test]
+	fn parses_json() {
+		let json = format!(
+			r#"{{
+				"name": "Acme Supply Network",
+				"id": "acme",
+				"block_time": 3000,
+				"council": ["{alice}"],
+				"token": {{ "symbol": "ACME", "decimals": 12, "ss58_prefix": 42 }},
+				"authorities": [
+					{{ "account": "{alice}", "aura": "{alice}", "grandpa": "{grandpa}" }}
+				]
+			}}"#,
+			alice = ALICE,
+			grandpa = ALICE_GRANDPA,
+		);
+		let consortium = parse(&json, false).unwrap().validate().unwrap();
+		assert_eq!(consortium.block_time, 3000);
+		assert!(consortium.companies.is_empty());
+		assert!(consortium.balances.is_empty());
+	}
+
+	#[test]
+	fn rejects_unknown_fields() {
+		let err = parse(&toml("colour = \"blue\""), true).err().unwrap();
+		assert!(err.contains("colour"), "{}", err);
+	}
+
+	#[test]
+	fn rejects_endowments_below_the_existential_deposit() {
+		let amount = EXISTENTIAL_DEPOSIT as u64 - 1;
+		let err = parse(&toml(&endowment(BOB, amount)), true).unwrap().validate().err().unwrap();
+		assert_eq!(
+			err,
+			format!(
+				"balances[0].amount {} is below the existential deposit {}",
+				amount, EXISTENTIAL_DEPOSIT
+			)
+		);
+		let ed = EXISTENTIAL_DEPOSIT as u64;
+		assert!(parse(&toml(&endowment(BOB, ed)), true).unwrap().validate().is_ok());
+	}
+
+	#[test]
+	fn rejects_duplicate_and_foreign_accounts() {
+		let balances = endowment(BOB, 1_000_000) + &endowment(BOB, 1_000_000);
+		let err = parse(&toml(&balances), true).unwrap().validate().err().unwrap();
+		assert_eq!(err, "balances[1] repeats an earlier account");
+
+		let mut file = parse(&toml(""), true).unwrap();
+		file.token.ss58_prefix = 2;
+		let err = file.validate().err().unwrap();
+		assert!(err.contains("uses SS58 prefix 42, expected 2"), "{}", err);
+	}
+
+	#[test]
+	fn rejects_invalid_settings() {
+		let mut file = parse(&toml(""), true).unwrap();
+		file.block_time = Some(999);
+		assert_eq!(
+			file.validate().err().unwrap(),
+			"`block_time` must be at least 1000 ms, got 999"
+		);
+
+		let mut file = parse(&toml(""), true).unwrap();
+		file.token.ss58_prefix = 46;
+		assert_eq!(file.validate().err().unwrap(), "`token.ss58_prefix` 46 is not usable");
+
+		let mut file = parse(&toml(""), true).unwrap();
+		file.companies[0].id = 0;
+		assert_eq!(
+			file.validate().err().unwrap(),
+			"companies[0].id must be between 1 and 9999999999999"
+		);
+
+		let mut file = parse(&toml(""), true).unwrap();
+		file.council.clear();
+		assert!(file.validate().err().unwrap().starts_with("`council` must have between 1 and"));
+	}
+}
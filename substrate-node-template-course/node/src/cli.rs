@@ -1,4 +1,10 @@
+use crate::export_checkpoint::ExportCheckpointCmd;
+use crate::export_supply_chain::ExportSupplyChainCmd;
+use crate::inspect::InspectCmd;
+use crate::service::Sealing;
+use crate::sign_transaction::SignTransactionCmd;
 use sc_cli::RunCmd;
+use std::path::PathBuf;
 
 #[derive(Debug, clap::Parser)]
 pub struct Cli {
@@ -7,6 +13,28 @@ pub struct Cli {
 
 	#[clap(flatten)]
 	pub run: RunCmd,
+
+	/// Build blocks with manual seal instead of Aura and Grandpa, for development and tests.
+	///
+	/// `instant` builds a block for every transaction, `manual` only when the
+	/// `engine_createBlock` RPC is called, and `interval=<ms>` every given number of
+	/// milliseconds. The `engine_createBlock` and `engine_finalizeBlock` RPCs are available in
+	/// every mode.
+	///
+	/// Blocks sealed faster than one per slot are stamped ahead of the wall clock, so this
+	/// requires `--dev` and cannot be combined with `--keystore-uri`.
+	#[clap(long, value_name = "instant|manual|interval=<ms>", requires = "dev")]
+	pub sealing: Option<Sealing>,
+
+	/// Index the supply-chain events of finalized blocks in a local database and serve them
+	/// with the `supplychain_eventsByCompany` RPC.
+	#[clap(long)]
+	pub event_index: bool,
+
+	/// File holding the shared secret that authenticates the node to the `--keystore-uri`
+	/// signer.
+	#[clap(long, value_name = "PATH", requires = "keystore-uri")]
+	pub keystore_token_file: Option<PathBuf>,
 }
 
 #[derive(Debug, clap::Subcommand)]
@@ -27,6 +55,17 @@ pub enum Subcommand {
 	/// Export the state of a given block into a chain spec.
 	ExportState(sc_cli::ExportStateCmd),
 
+	/// Export the companies, products, supply contracts and IOUs of a given block as JSON or CSV.
+	ExportSupplyChain(ExportSupplyChainCmd),
+
+	/// Export a finalized header with its Grandpa justification and authority set as JSON, for
+	/// light clients to start from.
+	ExportCheckpoint(ExportCheckpointCmd),
+
+	/// Decode the extrinsics, events and supply-chain storage changes of a block, or one of its
+	/// extrinsics, with the runtime metadata.
+	Inspect(InspectCmd),
+
 	/// Import blocks.
 	ImportBlocks(sc_cli::ImportBlocksCmd),
 
@@ -36,6 +75,9 @@ pub enum Subcommand {
 	/// Revert the chain to a previous state.
 	Revert(sc_cli::RevertCmd),
 
+	/// Build and sign a supply-chain transaction offline and print it as a hex extrinsic.
+	SignTransaction(SignTransactionCmd),
+
 	/// Sub-commands concerned with benchmarking.
 	#[clap(subcommand)]
 	Benchmark(frame_benchmarking_cli::BenchmarkCmd),
@@ -1,7 +1,11 @@
 use crate::{
-	benchmarking::{inherent_benchmark_data, RemarkBuilder, TransferKeepAliveBuilder},
+	benchmarking::{
+		create_product_builder, create_supply_contract_builder, inherent_benchmark_data,
+		register_company_builder, RemarkBuilder, TransferKeepAliveBuilder,
+	},
 	chain_spec,
 	cli::{Cli, Subcommand},
+	remote_keystore::protocol::read_token,
 	service,
 };
 use frame_benchmarking_cli::{BenchmarkCmd, ExtrinsicFactory, SUBSTRATE_REFERENCE_HARDWARE};
@@ -39,8 +43,10 @@ impl SubstrateCli for Cli {
 		Ok(match id {
 			"dev" => Box::new(chain_spec::development_config()?),
 			"" | "local" => Box::new(chain_spec::local_testnet_config()?),
-			path =>
-				Box::new(chain_spec::ChainSpec::from_json_file(std::path::PathBuf::from(path))?),
+			path if path.starts_with("consortium:") => Box::new(chain_spec::consortium_config(
+				std::path::Path::new(&path["consortium:".len()..]),
+			)?),
+			path => Box::new(chain_spec::from_json_file(std::path::Path::new(path))?),
 		})
 	}
 
@@ -81,6 +87,41 @@ pub fn run() -> sc_cli::Result<()> {
 				Ok((cmd.run(client, config.chain_spec), task_manager))
 			})
 		},
+		Some(Subcommand::ExportSupplyChain(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|config| {
+				let ss58_prefix = config
+					.chain_spec
+					.properties()
+					.get("ss58Format")
+					.and_then(|prefix| prefix.as_u64())
+					.unwrap_or(42) as u16;
+				let PartialComponents { client, .. } = service::new_partial(&config)?;
+				cmd.run(client, ss58_prefix)
+			})
+		},
+		Some(Subcommand::ExportCheckpoint(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|config| {
+				let chain = config.chain_spec.id().to_string();
+				let PartialComponents { client, backend, other: (_, grandpa_link, _), .. } =
+					service::new_partial(&config)?;
+				cmd.run(client, backend, grandpa_link.shared_authority_set().clone(), chain)
+			})
+		},
+		Some(Subcommand::Inspect(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|config| {
+				let ss58_prefix = config
+					.chain_spec
+					.properties()
+					.get("ss58Format")
+					.and_then(|prefix| prefix.as_u64())
+					.unwrap_or(42) as u16;
+				let PartialComponents { client, .. } = service::new_partial(&config)?;
+				cmd.run(client, ss58_prefix)
+			})
+		},
 		Some(Subcommand::ImportBlocks(cmd)) => {
 			let runner = cli.create_runner(cmd)?;
 			runner.async_run(|config| {
@@ -105,6 +146,7 @@ pub fn run() -> sc_cli::Result<()> {
 				Ok((cmd.run(client, backend, Some(aux_revert)), task_manager))
 			})
 		},
+		Some(Subcommand::SignTransaction(cmd)) => cmd.run(),
 		Some(Subcommand::Benchmark(cmd)) => {
 			let runner = cli.create_runner(cmd)?;
 
@@ -155,7 +197,7 @@ pub fn run() -> sc_cli::Result<()> {
 					},
 					BenchmarkCmd::Extrinsic(cmd) => {
 						let PartialComponents { client, .. } = service::new_partial(&config)?;
-						// Register the *Remark* and *TKA* builders.
+						// Register the *Remark*, *TKA* and supply-chain builders.
 						let ext_factory = ExtrinsicFactory(vec![
 							Box::new(RemarkBuilder::new(client.clone())),
 							Box::new(TransferKeepAliveBuilder::new(
@@ -163,6 +205,9 @@ pub fn run() -> sc_cli::Result<()> {
 								Sr25519Keyring::Alice.to_account_id(),
 								EXISTENTIAL_DEPOSIT,
 							)),
+							Box::new(register_company_builder(client.clone())),
+							Box::new(create_product_builder(client.clone())),
+							Box::new(create_supply_contract_builder(client.clone())),
 						]);
 
 						cmd.run(client, inherent_benchmark_data()?, Vec::new(), &ext_factory)
@@ -195,8 +240,11 @@ pub fn run() -> sc_cli::Result<()> {
 		},
 		None => {
 			let runner = cli.create_runner(&cli.run)?;
+			let (sealing, event_index) = (cli.sealing, cli.event_index);
+			let keystore_token = cli.keystore_token_file.as_deref().map(read_token).transpose()?;
 			runner.run_node_until_exit(|config| async move {
-				service::new_full(config).map_err(sc_cli::Error::Service)
+				service::new_full(config, sealing, event_index, keystore_token)
+					.map_err(sc_cli::Error::Service)
 			})
 		},
 	}
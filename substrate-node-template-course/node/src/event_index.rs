@@ -0,0 +1,446 @@
+//! An index of the supply-chain events of finalized blocks, for front ends that would otherwise
+//! scan every block.
+//!
+//! With `--event-index`, the node decodes the `TemplateModule` events of every finalized block
+//! into a sled database under `<base-path>/chains/<chain>/event-index`, and serves them with the
+//! `supplychain_*` RPC methods. Only finalized blocks are indexed, so the index never holds
+//! events of a retracted fork.
+//!
+//! Events are decoded with the node's native runtime. Blocks of older runtimes whose events it
+//! cannot decode are skipped and recorded, so that indexing moves on; `supplychain_skippedBlocks`
+//! lists them.
+
+use crate::service::FullClient;
+use frame_support::{codec::DecodeAll, storage::storage_prefix};
+use frame_system::EventRecord;
+use futures::StreamExt;
+use jsonrpsee::{
+	core::RpcResult,
+	proc_macros::rpc,
+	types::error::{CallError, ErrorObject},
+};
+use node_template_runtime::{
+	opaque::Block, pallet_template, AccountId, BlockNumber, Hash, Runtime, RuntimeEvent,
+};
+use sc_client_api::{BlockBackend, BlockchainEvents, StorageProvider, UsageProvider};
+use scale_info::{TypeDef, TypeInfo};
+use serde::{Deserialize, Serialize};
+use sled::{transaction::TransactionResult, Transactional};
+use sp_core::{storage::StorageKey, Bytes, Decode, Encode};
+use sp_runtime::generic::BlockId;
+use std::{path::Path, sync::Arc};
+
+/// Events returned by one `supplychain_eventsByCompany` call if no limit is given.
+pub const DEFAULT_PAGE_SIZE: u32 = 20;
+
+/// Most events returned by one `supplychain_eventsByCompany` call.
+pub const MAX_PAGE_SIZE: u32 = 100;
+
+/// The position of an event: its block and its index among the events of that block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct EventId {
+	pub block: BlockNumber,
+	pub index: u32,
+}
+
+impl EventId {
+	/// Big-endian, so that keys sort like ids.
+	fn key(&self) -> [u8; 8] {
+		let mut key = [0; 8];
+		key[..4].copy_from_slice(&self.block.to_be_bytes());
+		key[4..].copy_from_slice(&self.index.to_be_bytes());
+		key
+	}
+}
+
+/// A supply-chain event as stored in the index.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexedEvent {
+	pub id: EventId,
+	pub block_hash: Hash,
+	/// Name of the `TemplateModule` event, like `ProductCreated`.
+	pub name: String,
+	/// The SCALE encoded `TemplateModule` event, to be decoded with the runtime metadata.
+	pub data: Bytes,
+	/// Accounts of the companies the event concerns, including the parties to the supply
+	/// contract or IOU it is about.
+	pub companies: Vec<AccountId>,
+}
+
+/// One page of events, oldest first.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventsPage {
+	pub events: Vec<IndexedEvent>,
+	/// Pass as `after` to get the next page. `None` on the last page.
+	pub next: Option<EventId>,
+}
+
+/// The event database.
+pub struct EventIndex {
+	db: sled::Db,
+	/// Events by `EventId::key`.
+	events: sled::Tree,
+	/// Empty values keyed by company account followed by `EventId::key`.
+	by_company: sled::Tree,
+	/// Empty values keyed by the big-endian number of blocks whose events were not decodable.
+	skipped: sled::Tree,
+}
+
+const LAST_INDEXED: &[u8] = b"last_indexed";
+
+impl EventIndex {
+	/// Open the index at `path`, creating it if needed.
+	pub fn open(path: &Path) -> Result<Self, String> {
+		let open = || -> sled::Result<Self> {
+			let db = sled::open(path)?;
+			Ok(Self {
+				events: db.open_tree("events")?,
+				by_company: db.open_tree("by_company")?,
+				skipped: db.open_tree("skipped")?,
+				db,
+			})
+		};
+		open().map_err(|e| format!("Error opening event index `{}`: {}", path.display(), e))
+	}
+
+	/// The last block whose events are in the index.
+	pub fn last_indexed(&self) -> Result<Option<BlockNumber>, String> {
+		let last = self.db.get(LAST_INDEXED).map_err(|e| e.to_string())?;
+		last.map(|last| BlockNumber::decode(&mut &last[..]).map_err(|e| e.to_string()))
+			.transpose()
+	}
+
+	/// Add the events of block `number`. Indexing a block again replaces its events.
+	pub fn insert_block(&self, number: BlockNumber, events: &[IndexedEvent]) -> Result<(), String> {
+		self.write_block(number, events, false)
+	}
+
+	/// Record that the events of block `number` could not be decoded, and move past it.
+	pub fn skip_block(&self, number: BlockNumber) -> Result<(), String> {
+		self.write_block(number, &[], true)
+	}
+
+	/// The blocks whose events could not be decoded, in order.
+	pub fn skipped_blocks(&self) -> Result<Vec<BlockNumber>, String> {
+		self.skipped
+			.iter()
+			.keys()
+			.map(|key| {
+				let key = key.map_err(|e| e.to_string())?;
+				let number = key[..].try_into().map_err(|_| "event index is inconsistent")?;
+				Ok(BlockNumber::from_be_bytes(number))
+			})
+			.collect()
+	}
+
+	/// Replace the events of block `number` with `events`, record whether they were
+	/// `undecodable` and make it the last indexed block, all in one transaction.
+	fn write_block(
+		&self,
+		number: BlockNumber,
+		events: &[IndexedEvent],
+		undecodable: bool,
+	) -> Result<(), String> {
+		let stale = self
+			.events
+			.scan_prefix(number.to_be_bytes())
+			.values()
+			.map(|value| {
+				let value = value.map_err(|e| e.to_string())?;
+				serde_json::from_slice::<IndexedEvent>(&value).map_err(|e| e.to_string())
+			})
+			.collect::<Result<Vec<_>, String>>()?;
+		let values = events
+			.iter()
+			.map(|event| serde_json::to_vec(event).map_err(|e| e.to_string()))
+			.collect::<Result<Vec<_>, String>>()?;
+
+		let trees = (&self.events, &self.by_company, &self.skipped, &*self.db);
+		let result: TransactionResult<()> = trees.transaction(|(by_id, by_company, skipped, db)| {
+			for event in &stale {
+				let key = event.id.key();
+				by_id.remove(&key[..])?;
+				for company in &event.companies {
+					by_company.remove([company.as_ref(), &key[..]].concat())?;
+				}
+			}
+			for (event, value) in events.iter().zip(&values) {
+				let key = event.id.key();
+				by_id.insert(&key[..], value.clone())?;
+				for company in &event.companies {
+					by_company.insert([company.as_ref(), &key[..]].concat(), &[][..])?;
+				}
+			}
+			if undecodable {
+				skipped.insert(&number.to_be_bytes()[..], &[][..])?;
+			} else {
+				skipped.remove(&number.to_be_bytes()[..])?;
+			}
+			db.insert(LAST_INDEXED, number.encode())?;
+			Ok(())
+		});
+		result.map_err(|e| format!("{:?}", e))?;
+		self.db.flush().map_err(|e| e.to_string())?;
+		Ok(())
+	}
+
+	/// Up to `limit` events of `company` after `after`, oldest first.
+	pub fn events_by_company(
+		&self,
+		company: &AccountId,
+		after: Option<EventId>,
+		limit: u32,
+	) -> Result<EventsPage, String> {
+		let prefix: &[u8] = company.as_ref();
+		let start = match after {
+			// The next possible id, so that `after` itself is skipped.
+			Some(after) => [prefix, &after.key()[..], &[0][..]].concat(),
+			None => prefix.to_vec(),
+		};
+		let mut events = Vec::new();
+		let mut more = false;
+		for entry in self.by_company.range(start..) {
+			let (key, _) = entry.map_err(|e| e.to_string())?;
+			if !key.starts_with(prefix) {
+				break
+			}
+			if events.len() == limit as usize {
+				more = true;
+				break
+			}
+			let value = self
+				.events
+				.get(&key[prefix.len()..])
+				.map_err(|e| e.to_string())?
+				.ok_or("event index is inconsistent")?;
+			let event = serde_json::from_slice(&value).map_err(|e| e.to_string())?;
+			events.push(event);
+		}
+		let next = if more { events.last().map(|event| event.id) } else { None };
+		Ok(EventsPage { events, next })
+	}
+}
+
+/// Name of the variant of the encoded `TemplateModule` event.
+fn event_name(encoded: &[u8]) -> String {
+	let index = encoded.first();
+	match pallet_template::Event::<Runtime>::type_info().type_def() {
+		TypeDef::Variant(def) => def
+			.variants()
+			.iter()
+			.find(|variant| Some(&variant.index()) == index)
+			.map(|variant| variant.name().to_string()),
+		_ => None,
+	}
+	.unwrap_or_else(|| "Unknown".into())
+}
+
+/// The storage value at `key` in block `at`.
+fn stored<V: Decode>(
+	client: &FullClient,
+	at: &BlockId<Block>,
+	key: Vec<u8>,
+) -> Result<Option<V>, String> {
+	client
+		.storage(at, &StorageKey(key))
+		.map_err(|e| e.to_string())?
+		.map(|value| V::decode(&mut &value.0[..]).map_err(|e| e.to_string()))
+		.transpose()
+}
+
+/// Accounts of the companies `event` of block `at` concerns: the account it names and the
+/// companies party to the supply contract or IOU it is about.
+///
+/// Supply contracts, products and IOUs are stored under the account of the company that created
+/// them. Their parties are looked up at `at`, or at its `parent` for an IOU the block paid off.
+fn companies(
+	client: &FullClient,
+	at: &BlockId<Block>,
+	parent: &BlockId<Block>,
+	event: &pallet_template::Event<Runtime>,
+) -> Result<Vec<AccountId>, String> {
+	use pallet_template::Event::*;
+
+	let contract_parties = |contract: &AccountId| -> Result<Vec<u64>, String> {
+		let key = pallet_template::AccountToSupplyContract::<Runtime>::hashed_key_for(contract);
+		let contract = match stored::<pallet_template::SupplyContract>(client, at, key.clone())? {
+			Some(contract) => Some(contract),
+			None => stored(client, parent, key)?,
+		};
+		Ok(contract.map_or_else(Vec::new, |c| vec![c.seller_id, c.buyer_id]))
+	};
+	let iou_parties = |iou: &AccountId| -> Result<Vec<u64>, String> {
+		let key = pallet_template::AccountToIOU::<Runtime>::hashed_key_for(iou);
+		let iou = match stored::<pallet_template::IOU>(client, at, key.clone())? {
+			Some(iou) => Some(iou),
+			None => stored(client, parent, key)?,
+		};
+		Ok(iou.map_or_else(Vec::new, |iou| vec![iou.debtor, iou.creditor]))
+	};
+
+	let (mut accounts, ids) = match event {
+		CompanyCreated { company } |
+		CompanyVerified { company, .. } |
+		StaffSponsored { company, .. } |
+		StaffUnsponsored { company, .. } |
+		CompanyMultisigSet { company, .. } => (vec![company.clone()], Vec::new()),
+		ProductCreated { product } => (vec![product.clone()], Vec::new()),
+		SupplyContractCreated { contract } |
+		ArbiterRuling { contract, .. } |
+		SupplyContractStatusChanged { contract, .. } |
+		SupplyContractDelegateSet { contract, .. } |
+		SupplyContractFunded { contract, .. } =>
+			(vec![contract.clone()], contract_parties(contract)?),
+		EscrowReleased { contract, seller, .. } =>
+			(vec![contract.clone(), seller.clone()], contract_parties(contract)?),
+		IOUCreated { iou } |
+		IOURepaid { iou, .. } |
+		InstallmentPlanCreated { iou, .. } |
+		InstallmentPaid { iou, .. } |
+		InstallmentMissed { iou, .. } => (vec![iou.clone()], iou_parties(iou)?),
+		IOUConfirmed { iou, company } => {
+			let mut ids = iou_parties(iou)?;
+			ids.push(*company);
+			(vec![iou.clone()], ids)
+		},
+		Netted { company_a, company_b, .. } => (Vec::new(), vec![*company_a, *company_b]),
+		CallPaused { .. } | CallUnpaused { .. } => (Vec::new(), Vec::new()),
+		__Ignore(..) => (Vec::new(), Vec::new()),
+	};
+	for id in ids {
+		let key = pallet_template::CompanyIdToAccount::<Runtime>::hashed_key_for(id);
+		if let Some(account) = stored::<AccountId>(client, at, key)? {
+			if !accounts.contains(&account) {
+				accounts.push(account);
+			}
+		}
+	}
+	accounts.dedup();
+	Ok(accounts)
+}
+
+/// Add the supply-chain events of the finalized block `number` to `index`.
+fn index_block(client: &FullClient, index: &EventIndex, number: BlockNumber) -> Result<(), String> {
+	let hash = client
+		.block_hash(number)
+		.map_err(|e| e.to_string())?
+		.ok_or_else(|| format!("Block #{} not found", number))?;
+	let at = BlockId::Hash(hash);
+	// Finalized, so its parent is the canonical block before it.
+	let parent = BlockId::Number(number.saturating_sub(1));
+	let key = StorageKey(storage_prefix(b"System", b"Events").to_vec());
+	let records = match client.storage(&at, &key).map_err(|e| e.to_string())? {
+		Some(records) =>
+			match Vec::<EventRecord<RuntimeEvent, Hash>>::decode_all(&mut &records.0[..]) {
+				Ok(records) => records,
+				// Retrying would fail again, so the block is left out of the index.
+				Err(e) => {
+					log::warn!(
+						target: "event-index",
+						"Skipping block #{}, whose events the native runtime cannot decode: {}",
+						number,
+						e
+					);
+					return index.skip_block(number)
+				},
+			},
+		None => Vec::new(),
+	};
+
+	let mut events = Vec::new();
+	for (i, record) in records.into_iter().enumerate() {
+		if let RuntimeEvent::TemplateModule(event) = record.event {
+			let data = event.encode();
+			events.push(IndexedEvent {
+				id: EventId { block: number, index: i as u32 },
+				block_hash: hash,
+				name: event_name(&data),
+				data: data.into(),
+				companies: companies(client, &at, &parent, &event)?,
+			});
+		}
+	}
+	index.insert_block(number, &events)
+}
+
+/// Index every finalized block, starting after the last block already in `index`.
+pub async fn run(client: Arc<FullClient>, index: Arc<EventIndex>) {
+	let mut finality = client.finality_notification_stream();
+	loop {
+		let finalized = client.usage_info().chain.finalized_number;
+		let result = index.last_indexed().and_then(|last| {
+			let first = last.map_or(0, |last| last + 1);
+			(first..=finalized).try_for_each(|number| index_block(&client, &index, number))
+		});
+		if let Err(e) = result {
+			log::error!(target: "event-index", "Indexing events failed: {}", e);
+		}
+		// Blocks finalized together only notify once, so catch up with the finalized number.
+		if finality.next().await.is_none() {
+			break
+		}
+	}
+}
+
+/// Queries of the event index.
+#[rpc(server)]
+pub trait SupplyChainApi {
+	/// Events concerning the company registered by `company`, oldest first, starting after the
+	/// event `after`. At most `limit` events are returned, 20 if no limit is given and never
+	/// more than 100. A limit of 0 is an error.
+	#[method(name = "supplychain_eventsByCompany")]
+	fn events_by_company(
+		&self,
+		company: AccountId,
+		after: Option<EventId>,
+		limit: Option<u32>,
+	) -> RpcResult<EventsPage>;
+
+	/// The last block whose events are indexed.
+	#[method(name = "supplychain_lastIndexedBlock")]
+	fn last_indexed_block(&self) -> RpcResult<Option<BlockNumber>>;
+
+	/// The blocks whose events could not be decoded and are missing from the index.
+	#[method(name = "supplychain_skippedBlocks")]
+	fn skipped_blocks(&self) -> RpcResult<Vec<BlockNumber>>;
+}
+
+/// Serves `SupplyChainApi` from an event index.
+pub struct SupplyChain {
+	index: Arc<EventIndex>,
+}
+
+impl SupplyChain {
+	pub fn new(index: Arc<EventIndex>) -> Self {
+		Self { index }
+	}
+}
+
+fn rpc_error(message: String) -> jsonrpsee::core::Error {
+	CallError::Custom(ErrorObject::owned(1, message, None::<()>)).into()
+}
+
+impl SupplyChainApiServer for SupplyChain {
+	fn events_by_company(
+		&self,
+		company: AccountId,
+		after: Option<EventId>,
+		limit: Option<u32>,
+	) -> RpcResult<EventsPage> {
+		if limit == Some(0) {
+			return Err(rpc_error("limit must be at least 1".into()))
+		}
+		let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE);
+		self.index.events_by_company(&company, after, limit).map_err(rpc_error)
+	}
+
+	fn last_indexed_block(&self) -> RpcResult<Option<BlockNumber>> {
+		self.index.last_indexed().map_err(rpc_error)
+	}
+
+	fn skipped_blocks(&self) -> RpcResult<Vec<BlockNumber>> {
+		self.index.skipped_blocks().map_err(rpc_error)
+	}
+}
@@ -0,0 +1,175 @@
+//! The `export-checkpoint` subcommand: a finalized header with its Grandpa justification and
+//! authority sets, from which a light client can follow the chain without syncing from genesis.
+
+use crate::service::{FullBackend, FullClient};
+use finality_grandpa::voter_set::VoterSet;
+use node_template_runtime::{
+	opaque::{Block, Header},
+	BlockNumber, Hash,
+};
+use sc_cli::{BlockNumberOrHash, CliConfiguration, DatabaseParams, PruningParams, SharedParams};
+use sc_client_api::UsageProvider;
+use sc_finality_grandpa::{
+	FinalityProof, FinalityProofProvider, GrandpaJustification, SharedAuthoritySet,
+};
+use serde::{Deserialize, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::{Bytes, Decode};
+use sp_finality_grandpa::{AuthorityList, GrandpaApi, SetId};
+use sp_runtime::{generic::BlockId, traits::Header as HeaderT};
+use std::{fs, io::Write, path::PathBuf, sync::Arc};
+
+/// Export a finalized header, its Grandpa justification and the authority sets around it.
+#[derive(Debug, Clone, clap::Parser)]
+pub struct ExportCheckpointCmd {
+	/// Export the first block at or after this one that has a justification. Defaults to the
+	/// last finalized block.
+	#[clap(value_name = "HASH or NUMBER")]
+	pub input: Option<BlockNumberOrHash>,
+
+	/// File to write the checkpoint to. It is written to stdout if no output is given.
+	#[clap(long, short = 'o')]
+	pub output: Option<PathBuf>,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub pruning_params: PruningParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+/// A Grandpa authority set and its id.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthoritySet {
+	pub set_id: SetId,
+	pub authorities: AuthorityList,
+}
+
+/// A trusted starting point for a light client.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Checkpoint {
+	/// Id of the chain spec.
+	pub chain: String,
+	pub genesis_hash: Hash,
+	/// The finalized header.
+	pub header: Header,
+	/// SCALE encoded Grandpa justification finalizing `header`.
+	pub justification: Bytes,
+	/// The authority set that signed `justification`.
+	pub signers: AuthoritySet,
+	/// The authority set after `header`, which finalizes the following blocks. It differs from
+	/// `signers` if `header` enacts a change of authorities.
+	pub authorities: AuthoritySet,
+}
+
+impl Checkpoint {
+	/// Check that `signers` finalized `header` with `justification`.
+	pub fn verify(&self) -> Result<(), String> {
+		let voters = VoterSet::new(self.signers.authorities.iter().cloned())
+			.ok_or("The checkpoint has no signers")?;
+		GrandpaJustification::<Block>::decode_and_verify_finalizes(
+			&self.justification,
+			(self.header.hash(), *self.header.number()),
+			self.signers.set_id,
+			&voters,
+		)
+		.map_err(|e| format!("Invalid justification: {}", e))?;
+		Ok(())
+	}
+}
+
+fn authority_set(client: &FullClient, at: Hash) -> sc_cli::Result<AuthoritySet> {
+	let api = client.runtime_api();
+	let at = BlockId::Hash(at);
+	let error = |e: sp_api::ApiError| format!("Error reading the authority set: {}", e);
+	Ok(AuthoritySet {
+		set_id: api.current_set_id(&at).map_err(error)?,
+		authorities: api.grandpa_authorities(&at).map_err(error)?,
+	})
+}
+
+impl ExportCheckpointCmd {
+	/// Export the checkpoint of the chain `chain` from `client`.
+	pub fn run(
+		&self,
+		client: Arc<FullClient>,
+		backend: Arc<FullBackend>,
+		authority_set_state: SharedAuthoritySet<Hash, BlockNumber>,
+		chain: String,
+	) -> sc_cli::Result<()> {
+		let info = client.usage_info().chain;
+		let number = match &self.input {
+			Some(input) => {
+				let id = input.parse::<Block>()?;
+				client
+					.block_number_from_id(&id)?
+					.ok_or_else(|| format!("Block {} not found", id))?
+			},
+			None => info.finalized_number,
+		};
+		if number > info.finalized_number {
+			return Err(format!(
+				"Block #{} is not finalized yet, the last finalized block is #{}",
+				number, info.finalized_number
+			)
+			.into())
+		}
+
+		// The justification of the block, or of the next block the node has one for.
+		let provider = FinalityProofProvider::new(backend, Some(authority_set_state));
+		let proof = provider
+			.prove_finality(number)
+			.map_err(|e| format!("Error proving finality: {}", e))?
+			.ok_or("No justification available yet, the chain has not been finalized by Grandpa")?;
+		let proof = FinalityProof::<Header>::decode(&mut &proof[..])
+			.map_err(|e| format!("Invalid finality proof: {}", e))?;
+		let header = client
+			.header(BlockId::Hash(proof.block))?
+			.ok_or_else(|| format!("Block {:?} not found", proof.block))?;
+
+		let checkpoint = Checkpoint {
+			chain,
+			genesis_hash: info.genesis_hash,
+			signers: authority_set(&client, *header.parent_hash())?,
+			authorities: authority_set(&client, header.hash())?,
+			header,
+			justification: proof.justification.into(),
+		};
+		checkpoint.verify()?;
+
+		let json = serde_json::to_vec_pretty(&checkpoint)
+			.map_err(|e| format!("Error encoding checkpoint: {}", e))?;
+		match &self.output {
+			Some(path) => fs::write(path, json)?,
+			None => {
+				let mut stdout = std::io::stdout();
+				stdout.write_all(&json)?;
+				stdout.write_all(b"\n")?;
+			},
+		}
+		Ok(())
+	}
+}
+
+impl CliConfiguration for ExportCheckpointCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
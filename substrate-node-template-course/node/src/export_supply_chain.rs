@@ -0,0 +1,367 @@
+//! The `export-supply-chain` subcommand: dumps the supply-chain pallet's storage for auditors.
+
+use crate::service::FullClient;
+use frame_support::storage::StoragePrefixedMap;
+use node_template_runtime::{
+	opaque::Block,
+	pallet_template::{self, Company, Product, SupplyContract, IOU},
+	AccountId, Runtime,
+};
+use sc_cli::{BlockNumberOrHash, CliConfiguration, DatabaseParams, PruningParams, SharedParams};
+use sc_client_api::{StorageProvider, UsageProvider};
+use serde::Serialize;
+use sp_blockchain::HeaderBackend;
+use sp_core::{
+	crypto::{Ss58AddressFormat, Ss58Codec},
+	storage::StorageKey,
+	Decode,
+};
+use sp_runtime::{generic::BlockId, traits::Header as HeaderT};
+use std::{fs, io::Write, path::PathBuf, sync::Arc};
+
+/// Output format of `export-supply-chain`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+	/// One JSON document holding every table.
+	Json,
+	/// One CSV file per table: `companies.csv`, `products.csv`, `contracts.csv` and `ious.csv`.
+	Csv,
+}
+
+/// Export the companies, products, supply contracts and IOUs stored at a block.
+#[derive(Debug, Clone, clap::Parser)]
+pub struct ExportSupplyChainCmd {
+	/// Block to read the supply-chain storage at. Defaults to the best block.
+	#[clap(value_name = "HASH or NUMBER")]
+	pub input: Option<BlockNumberOrHash>,
+
+	/// Output format.
+	#[clap(long, value_enum, default_value = "json")]
+	pub format: ExportFormat,
+
+	/// File to write the JSON document to, or directory to write the CSV files to.
+	///
+	/// JSON is written to stdout if no output is given; CSV requires one.
+	#[clap(long, short = 'o')]
+	pub output: Option<PathBuf>,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub pruning_params: PruningParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+#[derive(Serialize)]
+struct Export {
+	block_hash: String,
+	block_number: u32,
+	companies: Vec<CompanyRow>,
+	products: Vec<ProductRow>,
+	contracts: Vec<ContractRow>,
+	ious: Vec<IouRow>,
+}
+
+#[derive(Serialize)]
+struct CompanyRow {
+	account: String,
+	id: u64,
+	name: String,
+	about_me: String,
+	verified: bool,
+}
+
+#[derive(Serialize)]
+struct ProductRow {
+	account: String,
+	id: u64,
+	name: String,
+	description: String,
+	owner: u64,
+	previous_owners: Vec<u64>,
+}
+
+#[derive(Serialize)]
+struct ContractRow {
+	account: String,
+	id: u64,
+	seller_id: u64,
+	buyer_id: u64,
+	products: Vec<u64>,
+	delivered: bool,
+	iou: u64,
+	contract_value: u64,
+	contract_fulfilled: bool,
+	asset_id: u32,
+	escrowed: bool,
+}
+
+#[derive(Serialize)]
+struct IouRow {
+	account: String,
+	id: u64,
+	debtor: u64,
+	creditor: u64,
+	amount: u64,
+	asset_id: u32,
+	delinquent: bool,
+}
+
+/// A row of a CSV table. Lists are joined with `;`.
+trait CsvRow {
+	const HEADER: &'static [&'static str];
+
+	fn record(&self) -> Vec<String>;
+}
+
+fn join(ids: &[u64]) -> String {
+	ids.iter().map(u64::to_string).collect::<Vec<_>>().join(";")
+}
+
+impl CsvRow for CompanyRow {
+	const HEADER: &'static [&'static str] = &["account", "id", "name", "about_me", "verified"];
+
+	fn record(&self) -> Vec<String> {
+		vec![
+			self.account.clone(),
+			self.id.to_string(),
+			self.name.clone(),
+			self.about_me.clone(),
+			self.verified.to_string(),
+		]
+	}
+}
+
+impl CsvRow for ProductRow {
+	const HEADER: &'static [&'static str] =
+		&["account", "id", "name", "description", "owner", "previous_owners"];
+
+	fn record(&self) -> Vec<String> {
+		vec![
+			self.account.clone(),
+			self.id.to_string(),
+			self.name.clone(),
+			self.description.clone(),
+			self.owner.to_string(),
+			join(&self.previous_owners),
+		]
+	}
+}
+
+impl CsvRow for ContractRow {
+	const HEADER: &'static [&'static str] = &[
+		"account",
+		"id",
+		"seller_id",
+		"buyer_id",
+		"products",
+		"delivered",
+		"iou",
+		"contract_value",
+		"contract_fulfilled",
+		"asset_id",
+		"escrowed",
+	];
+
+	fn record(&self) -> Vec<String> {
+		vec![
+			self.account.clone(),
+			self.id.to_string(),
+			self.seller_id.to_string(),
+			self.buyer_id.to_string(),
+			join(&self.products),
+			self.delivered.to_string(),
+			self.iou.to_string(),
+			self.contract_value.to_string(),
+			self.contract_fulfilled.to_string(),
+			self.asset_id.to_string(),
+			self.escrowed.to_string(),
+		]
+	}
+}
+
+impl CsvRow for IouRow {
+	const HEADER: &'static [&'static str] =
+		&["account", "id", "debtor", "creditor", "amount", "asset_id", "delinquent"];
+
+	fn record(&self) -> Vec<String> {
+		vec![
+			self.account.clone(),
+			self.id.to_string(),
+			self.debtor.to_string(),
+			self.creditor.to_string(),
+			self.amount.to_string(),
+			self.asset_id.to_string(),
+			self.delinquent.to_string(),
+		]
+	}
+}
+
+fn text(bytes: &[u8]) -> String {
+	String::from_utf8_lossy(bytes).into_owned()
+}
+
+impl ExportSupplyChainCmd {
+	/// Run the export against `client`. Accounts are written as SS58 addresses with
+	/// `ss58_prefix`.
+	pub fn run(&self, client: Arc<FullClient>, ss58_prefix: u16) -> sc_cli::Result<()> {
+		let block_id = match &self.input {
+			Some(input) => input.parse::<Block>()?,
+			None => BlockId::Hash(client.usage_info().chain.best_hash),
+		};
+		let header = client
+			.header(block_id)?
+			.ok_or_else(|| format!("Block {} not found", block_id))?;
+		let block_id = BlockId::Hash(header.hash());
+
+		let reader = Reader { client: &client, block_id, ss58_prefix };
+		let export = Export {
+			block_hash: format!("{:?}", header.hash()),
+			block_number: *header.number(),
+			companies: reader.read(
+				pallet_template::AccountToCompany::<Runtime>::final_prefix(),
+				|account, company: Company| CompanyRow {
+					account,
+					id: company.id,
+					name: text(&company.name),
+					about_me: text(&company.about_me),
+					verified: company.verified,
+				},
+			)?,
+			products: reader.read(
+				pallet_template::AccountToProduct::<Runtime>::final_prefix(),
+				|account, product: Product| ProductRow {
+					account,
+					id: product.id,
+					name: text(&product.name),
+					description: text(&product.description),
+					owner: product.owner,
+					previous_owners: product.previous_owners,
+				},
+			)?,
+			contracts: reader.read(
+				pallet_template::AccountToSupplyContract::<Runtime>::final_prefix(),
+				|account, contract: SupplyContract| ContractRow {
+					account,
+					id: contract.id,
+					seller_id: contract.seller_id,
+					buyer_id: contract.buyer_id,
+					products: contract.products,
+					delivered: contract.delivered,
+					iou: contract.iou,
+					contract_value: contract.contract_value,
+					contract_fulfilled: contract.contract_fulfilled,
+					asset_id: contract.asset_id,
+					escrowed: contract.escrowed,
+				},
+			)?,
+			ious: reader.read(
+				pallet_template::AccountToIOU::<Runtime>::final_prefix(),
+				|account, iou: IOU| IouRow {
+					account,
+					id: iou.id,
+					debtor: iou.debtor,
+					creditor: iou.creditor,
+					amount: iou.amount,
+					asset_id: iou.asset_id,
+					delinquent: iou.delinquent,
+				},
+			)?,
+		};
+
+		match self.format {
+			ExportFormat::Json => {
+				let json = serde_json::to_vec_pretty(&export)
+					.map_err(|e| format!("Error encoding export: {}", e))?;
+				match &self.output {
+					Some(path) => fs::write(path, json)?,
+					None => {
+						let mut stdout = std::io::stdout();
+						stdout.write_all(&json)?;
+						stdout.write_all(b"\n")?;
+					},
+				}
+			},
+			ExportFormat::Csv => {
+				let dir = self.output.as_ref().ok_or("CSV export requires `--output <DIR>`")?;
+				fs::create_dir_all(dir)?;
+				write_csv(dir.join("companies.csv"), &export.companies)?;
+				write_csv(dir.join("products.csv"), &export.products)?;
+				write_csv(dir.join("contracts.csv"), &export.contracts)?;
+				write_csv(dir.join("ious.csv"), &export.ious)?;
+			},
+		}
+		Ok(())
+	}
+}
+
+/// Reads the `Blake2_128Concat`-keyed maps of the supply-chain pallet at one block.
+struct Reader<'a> {
+	client: &'a FullClient,
+	block_id: BlockId<Block>,
+	ss58_prefix: u16,
+}
+
+impl Reader<'_> {
+	fn read<V: Decode, R>(
+		&self,
+		prefix: [u8; 32],
+		row: impl Fn(String, V) -> R,
+	) -> sc_cli::Result<Vec<R>> {
+		let pairs = self.client.storage_pairs(&self.block_id, &StorageKey(prefix.to_vec()))?;
+		pairs
+			.into_iter()
+			.map(|(key, value)| {
+				// The map prefix is followed by the 16 byte hash of the account and the account.
+				let account = key
+					.0
+					.get(prefix.len() + 16..)
+					.and_then(|mut encoded| AccountId::decode(&mut encoded).ok())
+					.ok_or_else(|| format!("Invalid storage key 0x{}", hex(&key.0)))?;
+				let value = V::decode(&mut &value.0[..])
+					.map_err(|e| format!("Invalid value for {}: {}", account, e))?;
+				let account =
+					account.to_ss58check_with_version(Ss58AddressFormat::custom(self.ss58_prefix));
+				Ok(row(account, value))
+			})
+			.collect()
+	}
+}
+
+fn hex(bytes: &[u8]) -> String {
+	bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn write_csv<R: CsvRow>(path: PathBuf, rows: &[R]) -> sc_cli::Result<()> {
+	let mut writer = csv::Writer::from_path(&path)
+		.map_err(|e| format!("Error creating {}: {}", path.display(), e))?;
+	let write = |writer: &mut csv::Writer<fs::File>| -> csv::Result<()> {
+		writer.write_record(R::HEADER)?;
+		for row in rows {
+			writer.write_record(row.record())?;
+		}
+		writer.flush()?;
+		Ok(())
+	};
+	write(&mut writer).map_err(|e| format!("Error writing {}: {}", path.display(), e).into())
+}
+
+impl CliConfiguration for ExportSupplyChainCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
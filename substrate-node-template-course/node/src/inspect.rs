@@ -0,0 +1,657 @@
+//! The `inspect` subcommand: decodes a block, or one of its extrinsics, from the node's database
+//! with the metadata of the runtime at that block, and prints its extrinsics, their events and
+//! the changes the block made to the supply-chain storage.
+
+use crate::service::FullClient;
+use frame_metadata::{
+	v14::{RuntimeMetadataV14, StorageEntryType, StorageHasher},
+	RuntimeMetadata, RuntimeMetadataPrefixed,
+};
+use frame_support::storage::storage_prefix;
+use frame_system::Phase;
+use node_template_runtime::{opaque::Block, AccountId, Hash};
+use sc_cli::{BlockNumberOrHash, CliConfiguration, DatabaseParams, PruningParams, SharedParams};
+use sc_client_api::{BlockBackend, StorageProvider};
+use scale_info::{form::PortableForm, Field, PortableRegistry, TypeDef, TypeDefPrimitive};
+use serde::Serialize;
+use serde_json::{Map, Value as Json};
+use sp_api::{Metadata, ProvideRuntimeApi};
+use sp_blockchain::HeaderBackend;
+use sp_core::{
+	crypto::{Ss58AddressFormat, Ss58Codec},
+	hashing::twox_128,
+	hexdisplay::HexDisplay,
+	storage::StorageKey,
+	Decode, Encode,
+};
+use sp_runtime::{codec::Compact, generic::BlockId, traits::Header as HeaderT};
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	fmt,
+	str::FromStr,
+	sync::Arc,
+};
+
+/// Name of the supply-chain pallet in the runtime, whose storage changes are shown.
+const PALLET: &str = "TemplateModule";
+
+/// A block, optionally followed by `:<index>` to select one of its extrinsics.
+#[derive(Debug, Clone)]
+pub struct InspectTarget {
+	pub block: BlockNumberOrHash,
+	pub extrinsic: Option<u32>,
+}
+
+impl FromStr for InspectTarget {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, String> {
+		let (block, extrinsic) = match s.split_once(':') {
+			Some((block, index)) => {
+				let index = index
+					.parse()
+					.map_err(|e| format!("Invalid extrinsic index `{}`: {}", index, e))?;
+				(block, Some(index))
+			},
+			None => (s, None),
+		};
+		Ok(InspectTarget { block: block.parse()?, extrinsic })
+	}
+}
+
+/// Decode a block or an extrinsic with the runtime metadata and print it.
+#[derive(Debug, Clone, clap::Parser)]
+pub struct InspectCmd {
+	/// Number or hash of the block, followed by `:<index>` to show only that extrinsic.
+	#[clap(value_name = "BLOCK[:INDEX]")]
+	pub input: InspectTarget,
+
+	/// Print JSON instead of text.
+	#[clap(long)]
+	pub json: bool,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub pruning_params: PruningParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BlockReport {
+	number: u32,
+	hash: Hash,
+	parent_hash: Hash,
+	extrinsics: Vec<ExtrinsicReport>,
+	/// Events emitted while initializing or finalizing the block. Left out for one extrinsic.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	block_events: Option<Vec<EventReport>>,
+	/// Changes to the supply-chain storage. Left out for one extrinsic.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	storage_changes: Option<Vec<StorageChange>>,
+}
+
+#[derive(Serialize)]
+struct ExtrinsicReport {
+	index: u32,
+	pallet: String,
+	call: String,
+	args: Json,
+	/// Address of the signer, for signed extrinsics.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	signer: Option<Json>,
+	/// Signed extensions, for signed extrinsics.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	extra: Option<Json>,
+	events: Vec<EventReport>,
+}
+
+#[derive(Serialize)]
+struct EventReport {
+	pallet: String,
+	name: String,
+	fields: Json,
+}
+
+#[derive(Serialize)]
+struct StorageChange {
+	item: String,
+	/// The decoded key of a map entry, or the hash for hashers that do not keep the key.
+	key: Json,
+	before: Option<Json>,
+	after: Option<Json>,
+}
+
+impl fmt::Display for EventReport {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}.{}", self.pallet, self.name)?;
+		if !self.fields.is_null() {
+			write!(f, " {}", self.fields)?;
+		}
+		Ok(())
+	}
+}
+
+fn or_none(value: &Option<Json>) -> String {
+	value.as_ref().map_or_else(|| "none".into(), Json::to_string)
+}
+
+impl fmt::Display for BlockReport {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		writeln!(f, "Block #{} {:?}", self.number, self.hash)?;
+		writeln!(f, "  parent {:?}", self.parent_hash)?;
+		for extrinsic in &self.extrinsics {
+			write!(f, "Extrinsic {}: {}.{}", extrinsic.index, extrinsic.pallet, extrinsic.call)?;
+			match &extrinsic.signer {
+				Some(signer) => writeln!(f, " signed by {}", signer)?,
+				None => writeln!(f)?,
+			}
+			writeln!(f, "  args: {}", extrinsic.args)?;
+			if let Some(extra) = &extrinsic.extra {
+				writeln!(f, "  extra: {}", extra)?;
+			}
+			for event in &extrinsic.events {
+				writeln!(f, "  event {}", event)?;
+			}
+		}
+		if let Some(events) = &self.block_events {
+			writeln!(f, "Block events:")?;
+			for event in events {
+				writeln!(f, "  event {}", event)?;
+			}
+		}
+		if let Some(changes) = &self.storage_changes {
+			writeln!(f, "Storage changes of {}:", PALLET)?;
+			for change in changes {
+				write!(f, "  {}", change.item)?;
+				if !change.key.is_null() {
+					write!(f, "[{}]", change.key)?;
+				}
+				writeln!(f, ": {} -> {}", or_none(&change.before), or_none(&change.after))?;
+			}
+		}
+		Ok(())
+	}
+}
+
+fn read<T: Decode>(input: &mut &[u8]) -> Result<T, String> {
+	T::decode(input).map_err(|e| e.to_string())
+}
+
+fn hex(bytes: &[u8]) -> Json {
+	Json::String(format!("0x{}", HexDisplay::from(&bytes)))
+}
+
+/// Split a decoded enum into the name of its variant and its fields.
+fn split_variant(value: Json) -> (String, Json) {
+	match value {
+		Json::String(name) => (name, Json::Null),
+		Json::Object(variant) if variant.len() == 1 =>
+			variant.into_iter().next().unwrap_or_default(),
+		other => ("Unknown".into(), other),
+	}
+}
+
+fn primitive(primitive: &TypeDefPrimitive, input: &mut &[u8]) -> Result<Json, String> {
+	// JSON numbers cannot hold 128 bit integers, so these are written as strings.
+	Ok(match primitive {
+		TypeDefPrimitive::Bool => read::<bool>(input)?.into(),
+		TypeDefPrimitive::Char =>
+			char::from_u32(read(input)?).ok_or("Invalid character")?.to_string().into(),
+		TypeDefPrimitive::Str => read::<String>(input)?.into(),
+		TypeDefPrimitive::U8 => read::<u8>(input)?.into(),
+		TypeDefPrimitive::U16 => read::<u16>(input)?.into(),
+		TypeDefPrimitive::U32 => read::<u32>(input)?.into(),
+		TypeDefPrimitive::U64 => read::<u64>(input)?.into(),
+		TypeDefPrimitive::U128 => read::<u128>(input)?.to_string().into(),
+		TypeDefPrimitive::I8 => read::<i8>(input)?.into(),
+		TypeDefPrimitive::I16 => read::<i16>(input)?.into(),
+		TypeDefPrimitive::I32 => read::<i32>(input)?.into(),
+		TypeDefPrimitive::I64 => read::<i64>(input)?.into(),
+		TypeDefPrimitive::I128 => read::<i128>(input)?.to_string().into(),
+		TypeDefPrimitive::U256 | TypeDefPrimitive::I256 => hex(&read::<[u8; 32]>(input)?),
+	})
+}
+
+/// Decodes SCALE encoded values into JSON by following the type registry of the runtime
+/// metadata. Accounts are shown as SS58 addresses, byte vectors as text if they are printable
+/// UTF-8 and as hex otherwise.
+struct Decoder<'a> {
+	metadata: &'a RuntimeMetadataV14,
+	format: Ss58AddressFormat,
+}
+
+impl Decoder<'_> {
+	fn types(&self) -> &PortableRegistry {
+		&self.metadata.types
+	}
+
+	fn decode(&self, id: u32, input: &mut &[u8]) -> Result<Json, String> {
+		let ty = self.types().resolve(id).ok_or_else(|| format!("Unknown type {}", id))?;
+		if ty.path().segments().last().map(String::as_str) == Some("AccountId32") {
+			let account: AccountId = read(input)?;
+			return Ok(account.to_ss58check_with_version(self.format).into())
+		}
+		match ty.type_def() {
+			TypeDef::Composite(composite) => self.fields(composite.fields(), input),
+			TypeDef::Variant(variants) => {
+				let index: u8 = read(input)?;
+				let variant =
+					variants.variants().iter().find(|v| v.index() == index).ok_or_else(|| {
+						format!("Unknown variant {} of {}", index, ty.path().segments().join("::"))
+					})?;
+				Ok(match self.fields(variant.fields(), input)? {
+					Json::Null => variant.name().clone().into(),
+					fields => Json::Object(Map::from_iter([(variant.name().clone(), fields)])),
+				})
+			},
+			TypeDef::Sequence(sequence) => {
+				let len: Compact<u32> = read(input)?;
+				self.items(sequence.type_param().id(), len.0, true, input)
+			},
+			TypeDef::Array(array) => self.items(array.type_param().id(), array.len(), false, input),
+			TypeDef::Tuple(tuple) => tuple
+				.fields()
+				.iter()
+				.map(|field| self.decode(field.id(), input))
+				.collect::<Result<_, _>>()
+				.map(Json::Array),
+			TypeDef::Primitive(def) => primitive(def, input),
+			TypeDef::Compact(compact) => self.compact(compact.type_param().id(), input),
+			TypeDef::BitSequence(_) => Err("Bit sequences are not supported".into()),
+		}
+	}
+
+	/// Named fields as an object, a single unnamed field as its value and other unnamed fields
+	/// as an array.
+	fn fields(&self, fields: &[Field<PortableForm>], input: &mut &[u8]) -> Result<Json, String> {
+		let mut values = fields
+			.iter()
+			.map(|field| Ok((field.name().cloned(), self.decode(field.ty().id(), input)?)))
+			.collect::<Result<Vec<_>, String>>()?;
+		let named = values.iter().all(|(name, _)| name.is_some());
+		Ok(match values.len() {
+			0 => Json::Null,
+			1 if !named => values.remove(0).1,
+			_ if named => Json::Object(
+				values
+					.into_iter()
+					.map(|(name, value)| (name.unwrap_or_default(), value))
+					.collect(),
+			),
+			_ => Json::Array(values.into_iter().map(|(_, value)| value).collect()),
+		})
+	}
+
+	fn items(&self, id: u32, len: u32, text: bool, input: &mut &[u8]) -> Result<Json, String> {
+		let bytes = matches!(
+			self.types().resolve(id).map(|ty| ty.type_def()),
+			Some(TypeDef::Primitive(TypeDefPrimitive::U8))
+		);
+		if !bytes {
+			return (0..len)
+				.map(|_| self.decode(id, input))
+				.collect::<Result<_, _>>()
+				.map(Json::Array)
+		}
+
+		let data: &[u8] = *input;
+		if data.len() < len as usize {
+			return Err("Not enough data".into())
+		}
+		let (bytes, rest) = data.split_at(len as usize);
+		*input = rest;
+		Ok(match std::str::from_utf8(bytes) {
+			Ok(s) if text && !s.chars().any(char::is_control) => s.into(),
+			_ => hex(bytes),
+		})
+	}
+
+	fn compact(&self, id: u32, input: &mut &[u8]) -> Result<Json, String> {
+		let ty = self.types().resolve(id).ok_or_else(|| format!("Unknown type {}", id))?;
+		match ty.type_def() {
+			TypeDef::Primitive(TypeDefPrimitive::U8) => Ok(read::<Compact<u8>>(input)?.0.into()),
+			TypeDef::Primitive(TypeDefPrimitive::U16) => Ok(read::<Compact<u16>>(input)?.0.into()),
+			TypeDef::Primitive(TypeDefPrimitive::U32) => Ok(read::<Compact<u32>>(input)?.0.into()),
+			TypeDef::Primitive(TypeDefPrimitive::U64) => Ok(read::<Compact<u64>>(input)?.0.into()),
+			TypeDef::Primitive(TypeDefPrimitive::U128) =>
+				Ok(read::<Compact<u128>>(input)?.0.to_string().into()),
+			// Wrappers such as `Perbill` are encoded as their single field.
+			TypeDef::Composite(composite) if composite.fields().len() == 1 =>
+				self.compact(composite.fields()[0].ty().id(), input),
+			_ => Err(format!("Type {} has no compact encoding", id)),
+		}
+	}
+
+	/// Decode an encoded `UncheckedExtrinsic`, whose type parameters give the types of its
+	/// address, signature, signed extensions and call.
+	fn extrinsic(&self, index: u32, encoded: &[u8]) -> Result<ExtrinsicReport, String> {
+		let ty = self
+			.types()
+			.resolve(self.metadata.extrinsic.ty.id())
+			.ok_or("Unknown extrinsic type")?;
+		let param = |name: &str| {
+			ty.type_params()
+				.iter()
+				.find(|param| param.name() == name)
+				.and_then(|param| param.ty())
+				.map(|ty| ty.id())
+				.ok_or_else(|| format!("The extrinsic type has no `{}` parameter", name))
+		};
+
+		let input = &mut &encoded[..];
+		let _length: Compact<u32> = read(input)?;
+		let version: u8 = read(input)?;
+		let (signer, extra) = if version & 0b1000_0000 != 0 {
+			let signer = self.decode(param("Address")?, input)?;
+			self.decode(param("Signature")?, input)?;
+			(Some(signer), Some(self.decode(param("Extra")?, input)?))
+		} else {
+			(None, None)
+		};
+		let (pallet, call) = split_variant(self.decode(param("Call")?, input)?);
+		let (call, args) = split_variant(call);
+		Ok(ExtrinsicReport { index, pallet, call, args, signer, extra, events: Vec::new() })
+	}
+
+	/// Decode the `System::Events` of a block, with the phase each event was emitted in.
+	fn events(&self, encoded: &[u8]) -> Result<Vec<(Phase, EventReport)>, String> {
+		let input = &mut &encoded[..];
+		let count: Compact<u32> = read(input)?;
+		let event = self.event_type()?;
+		(0..count.0)
+			.map(|_| {
+				let phase: Phase = read(input)?;
+				let (pallet, event) = split_variant(self.decode(event, input)?);
+				let (name, fields) = split_variant(event);
+				let _topics: Vec<Hash> = read(input)?;
+				Ok((phase, EventReport { pallet, name, fields }))
+			})
+			.collect()
+	}
+
+	/// The runtime's event type, the `event` field of the records in `System::Events`.
+	fn event_type(&self) -> Result<u32, String> {
+		let records = self
+			.metadata
+			.pallets
+			.iter()
+			.find(|pallet| pallet.name == "System")
+			.and_then(|pallet| pallet.storage.as_ref())
+			.and_then(|storage| storage.entries.iter().find(|entry| entry.name == "Events"))
+			.and_then(|entry| match &entry.ty {
+				StorageEntryType::Plain(ty) => self.types().resolve(ty.id()),
+				_ => None,
+			});
+		let record = match records.map(|ty| ty.type_def()) {
+			Some(TypeDef::Sequence(sequence)) => self.types().resolve(sequence.type_param().id()),
+			_ => None,
+		};
+		match record.map(|ty| ty.type_def()) {
+			Some(TypeDef::Composite(composite)) => composite
+				.fields()
+				.iter()
+				.find(|field| field.name().map(String::as_str) == Some("event"))
+				.map(|field| field.ty().id()),
+			_ => None,
+		}
+		.ok_or_else(|| "The metadata has no `System::Events` storage".into())
+	}
+
+	/// Decode the parts of a map key that follow the storage prefix.
+	fn storage_key(
+		&self,
+		hashers: &[StorageHasher],
+		key: u32,
+		mut input: &[u8],
+	) -> Result<Json, String> {
+		// Maps with several hashers have a tuple of the key types.
+		let key_types = match self.types().resolve(key).map(|ty| ty.type_def()) {
+			Some(TypeDef::Tuple(tuple)) if hashers.len() > 1 =>
+				tuple.fields().iter().map(|field| field.id()).collect(),
+			_ => vec![key],
+		};
+		let mut parts = Vec::new();
+		for (hasher, key) in hashers.iter().zip(key_types) {
+			let (hash_len, concat) = match hasher {
+				StorageHasher::Blake2_128 | StorageHasher::Twox128 => (16, false),
+				StorageHasher::Blake2_256 | StorageHasher::Twox256 => (32, false),
+				StorageHasher::Blake2_128Concat => (16, true),
+				StorageHasher::Twox64Concat => (8, true),
+				StorageHasher::Identity => (0, true),
+			};
+			if input.len() < hash_len {
+				return Err("Storage key too short".into())
+			}
+			let (hash, rest) = input.split_at(hash_len);
+			input = rest;
+			parts.push(if concat { self.decode(key, &mut input)? } else { hex(hash) });
+		}
+		Ok(if parts.len() == 1 { parts.remove(0) } else { Json::Array(parts) })
+	}
+
+	/// Decode the entry of `pallet` at `key` holding `data`, or `None` if the metadata does not
+	/// describe it.
+	fn storage_entry(
+		&self,
+		pallet: &str,
+		key: &[u8],
+		data: Option<&Vec<u8>>,
+	) -> Result<Option<DecodedEntry>, String> {
+		let storage = match self
+			.metadata
+			.pallets
+			.iter()
+			.find(|p| p.name == pallet)
+			.and_then(|p| p.storage.as_ref())
+		{
+			Some(storage) => storage,
+			None => return Ok(None),
+		};
+		let entry = storage.entries.iter().find(|entry| {
+			let prefix = storage_prefix(storage.prefix.as_bytes(), entry.name.as_bytes());
+			key.starts_with(&prefix[..])
+		});
+		let entry = match entry {
+			Some(entry) => entry,
+			None => return Ok(None),
+		};
+		let (key, value) = match &entry.ty {
+			StorageEntryType::Plain(value) => (Json::Null, value.id()),
+			StorageEntryType::Map { hashers, key: key_type, value } =>
+				(self.storage_key(hashers, key_type.id(), &key[32..])?, value.id()),
+		};
+		let value = data.map(|data| self.decode(value, &mut &data[..])).transpose()?;
+		Ok(Some(DecodedEntry { item: entry.name.clone(), key, value }))
+	}
+
+	/// The entries of `pallet` that differ between `before` and `after`. `parent` decodes the
+	/// values before the block, which differ in type from those after it if the block upgraded the
+	/// runtime; values it cannot decode are shown as hex.
+	fn storage_changes(
+		&self,
+		parent: &Decoder,
+		pallet: &str,
+		before: &BTreeMap<Vec<u8>, Vec<u8>>,
+		after: &BTreeMap<Vec<u8>, Vec<u8>>,
+	) -> Result<Vec<StorageChange>, String> {
+		let keys: BTreeSet<_> = before.keys().chain(after.keys()).collect();
+
+		let mut changes = Vec::new();
+		for key in keys {
+			let (old, new) = (before.get(key), after.get(key));
+			if old == new {
+				continue
+			}
+			let raw = |value: Option<&Vec<u8>>| value.map(|value| hex(value));
+			let decoded_old = parent.storage_entry(pallet, key, old).ok().flatten();
+			changes.push(match (decoded_old, self.storage_entry(pallet, key, new)?) {
+				(decoded_old, Some(entry)) => StorageChange {
+					item: entry.item,
+					key: entry.key,
+					before: match decoded_old {
+						Some(decoded_old) => decoded_old.value,
+						None => raw(old),
+					},
+					after: entry.value,
+				},
+				// The block removed the entry from the metadata.
+				(Some(entry), None) => StorageChange {
+					item: entry.item,
+					key: entry.key,
+					before: entry.value,
+					after: raw(new),
+				},
+				(None, None) => StorageChange {
+					item: "Unknown".into(),
+					key: hex(key),
+					before: raw(old),
+					after: raw(new),
+				},
+			});
+		}
+		Ok(changes)
+	}
+}
+
+/// A storage entry decoded with the metadata.
+struct DecodedEntry {
+	item: String,
+	key: Json,
+	value: Option<Json>,
+}
+
+/// The V14 metadata of the runtime at block `at`.
+fn runtime_metadata(client: &FullClient, at: Hash) -> sc_cli::Result<RuntimeMetadataV14> {
+	let metadata = client
+		.runtime_api()
+		.metadata(&BlockId::Hash(at))
+		.map_err(|e| format!("Error reading the runtime metadata: {}", e))?;
+	match RuntimeMetadataPrefixed::decode(&mut &metadata[..])
+		.map_err(|e| format!("Invalid runtime metadata: {}", e))?
+		.1
+	{
+		RuntimeMetadata::V14(metadata) => Ok(metadata),
+		_ => Err("Only version 14 of the runtime metadata is supported".into()),
+	}
+}
+
+/// The storage of `pallet` at block `at`, by key.
+fn pallet_storage(
+	client: &FullClient,
+	at: Hash,
+	pallet: &str,
+) -> sc_cli::Result<BTreeMap<Vec<u8>, Vec<u8>>> {
+	let prefix = StorageKey(twox_128(pallet.as_bytes()).to_vec());
+	let pairs = client.storage_pairs(&BlockId::Hash(at), &prefix)?;
+	Ok(pairs.into_iter().map(|(key, value)| (key.0, value.0)).collect())
+}
+
+impl InspectCmd {
+	/// Inspect the block of `client`. Accounts are written as SS58 addresses with `ss58_prefix`.
+	pub fn run(&self, client: Arc<FullClient>, ss58_prefix: u16) -> sc_cli::Result<()> {
+		let block_id = self.input.block.parse::<Block>()?;
+		let header = client
+			.header(block_id)?
+			.ok_or_else(|| format!("Block {} not found", block_id))?;
+		let hash = header.hash();
+		let at = BlockId::Hash(hash);
+
+		let metadata = runtime_metadata(&client, hash)?;
+		let decoder =
+			Decoder { metadata: &metadata, format: Ss58AddressFormat::custom(ss58_prefix) };
+
+		let mut block_events = Vec::new();
+		let mut extrinsic_events: BTreeMap<u32, Vec<EventReport>> = BTreeMap::new();
+		let events_key = StorageKey(storage_prefix(b"System", b"Events").to_vec());
+		if let Some(events) = client.storage(&at, &events_key)? {
+			for (phase, event) in decoder.events(&events.0)? {
+				match phase {
+					Phase::ApplyExtrinsic(index) =>
+						extrinsic_events.entry(index).or_default().push(event),
+					_ => block_events.push(event),
+				}
+			}
+		}
+
+		let body =
+			client.block_body(&at)?.ok_or_else(|| format!("Block {:?} has no body", hash))?;
+		if let Some(index) = self.input.extrinsic {
+			if index as usize >= body.len() {
+				return Err(format!("Block {:?} has only {} extrinsics", hash, body.len()).into())
+			}
+		}
+		let mut extrinsics = Vec::new();
+		for (index, extrinsic) in body.iter().enumerate() {
+			let index = index as u32;
+			if self.input.extrinsic.map_or(false, |selected| selected != index) {
+				continue
+			}
+			let mut report = decoder
+				.extrinsic(index, &extrinsic.encode())
+				.map_err(|e| format!("Error decoding extrinsic {}: {}", index, e))?;
+			report.events = extrinsic_events.remove(&index).unwrap_or_default();
+			extrinsics.push(report);
+		}
+
+		let (block_events, storage_changes) = match self.input.extrinsic {
+			Some(_) => (None, None),
+			None => {
+				// The genesis block has no parent state, so all of its storage is new.
+				let (before, parent_metadata) = match *header.number() {
+					0 => (BTreeMap::new(), None),
+					_ => {
+						let parent = *header.parent_hash();
+						let metadata = runtime_metadata(&client, parent)?;
+						(pallet_storage(&client, parent, PALLET)?, Some(metadata))
+					},
+				};
+				let parent = Decoder {
+					metadata: parent_metadata.as_ref().unwrap_or(&metadata),
+					format: decoder.format,
+				};
+				let after = pallet_storage(&client, hash, PALLET)?;
+				let changes = decoder.storage_changes(&parent, PALLET, &before, &after)?;
+				(Some(block_events), Some(changes))
+			},
+		};
+
+		let report = BlockReport {
+			number: *header.number(),
+			hash,
+			parent_hash: *header.parent_hash(),
+			extrinsics,
+			block_events,
+			storage_changes,
+		};
+		if self.json {
+			let json = serde_json::to_string_pretty(&report)
+				.map_err(|e| format!("Error encoding the report: {}", e))?;
+			println!("{}", json);
+		} else {
+			print!("{}", report);
+		}
+		Ok(())
+	}
+}
+
+impl CliConfiguration for InspectCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
@@ -1,3 +1,7 @@
 pub mod chain_spec;
+pub mod event_index;
+pub mod metrics;
+pub mod provenance;
+pub mod remote_keystore;
 pub mod rpc;
 pub mod service;
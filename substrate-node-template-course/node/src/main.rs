@@ -7,7 +7,15 @@ mod service;
 mod benchmarking;
 mod cli;
 mod command;
+mod event_index;
+mod export_checkpoint;
+mod export_supply_chain;
+mod inspect;
+mod metrics;
+mod provenance;
+mod remote_keystore;
 mod rpc;
+mod sign_transaction;
 
 fn main() -> sc_cli::Result<()> {
 	command::run()
@@ -0,0 +1,202 @@
+//! Prometheus metrics of supply-chain activity, read from the blocks the node finalizes.
+//!
+//! Counters follow the events of every finalized block, so that blocks of retracted forks are
+//! never counted. Gauges are recomputed from the storage of the last finalized block, which scans
+//! the supply contracts and IOUs; the scan runs on a blocking thread and is skipped while the node
+//! is catching up with the network.
+
+use crate::service::FullClient;
+use frame_support::storage::{storage_prefix, StoragePrefixedMap};
+use frame_system::EventRecord;
+use futures::StreamExt;
+use node_template_runtime::{
+	opaque::Block,
+	pallet_template::{self, Product, SupplyContract, IOU},
+	Hash, Runtime, RuntimeEvent,
+};
+use sc_client_api::{BlockchainEvents, StorageProvider};
+use sp_blockchain::HeaderBackend;
+use sp_consensus::SyncOracle;
+use sp_core::{storage::StorageKey, Decode};
+use sp_runtime::{generic::BlockId, traits::Header as HeaderT};
+use std::{collections::BTreeMap, iter, sync::Arc};
+use substrate_prometheus_endpoint::{
+	register, Counter, Gauge, GaugeVec, Opts, PrometheusError, Registry, U64,
+};
+
+/// Supply-chain metrics of the node.
+pub struct SupplyChainMetrics {
+	companies_registered: Counter<U64>,
+	products_transferred: Counter<U64>,
+	open_contracts: Gauge<U64>,
+	outstanding_iou_value: GaugeVec<U64>,
+}
+
+impl SupplyChainMetrics {
+	/// Create the metrics and add them to `registry`.
+	pub fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			companies_registered: register(
+				Counter::new(
+					"substrate_supply_chain_companies_registered_total",
+					"Companies registered or re-registered",
+				)?,
+				registry,
+			)?,
+			products_transferred: register(
+				Counter::new(
+					"substrate_supply_chain_products_transferred_total",
+					"Products recorded again with a different owner",
+				)?,
+				registry,
+			)?,
+			open_contracts: register(
+				Gauge::new(
+					"substrate_supply_chain_open_contracts",
+					"Supply contracts at the last finalized block that are not fulfilled",
+				)?,
+				registry,
+			)?,
+			outstanding_iou_value: register(
+				GaugeVec::new(
+					Opts::new(
+						"substrate_supply_chain_outstanding_iou_value",
+						"Amount still owed on IOUs at the last finalized block, per asset",
+					),
+					&["asset_id"],
+				)?,
+				registry,
+			)?,
+		})
+	}
+
+	/// Count the events of the finalized `blocks`, oldest first, and with `gauges` set recompute
+	/// the gauges from the storage of the last one.
+	fn update(&self, client: &FullClient, blocks: &[Hash], gauges: bool) -> Result<(), String> {
+		for hash in blocks {
+			self.count_events(client, *hash)?;
+		}
+		match blocks.last() {
+			Some(hash) if gauges => self.update_gauges(client, *hash),
+			_ => Ok(()),
+		}
+	}
+
+	/// Count the events of the block `hash`.
+	fn count_events(&self, client: &FullClient, hash: Hash) -> Result<(), String> {
+		let at = BlockId::Hash(hash);
+		let parent_hash = *client
+			.header(at)
+			.map_err(|e| e.to_string())?
+			.ok_or_else(|| format!("Block {} not found", hash))?
+			.parent_hash();
+		let events: Vec<EventRecord<RuntimeEvent, Hash>> =
+			read_value(client, &at, &storage_prefix(b"System", b"Events"))?.unwrap_or_default();
+		for record in events {
+			match record.event {
+				RuntimeEvent::TemplateModule(pallet_template::Event::CompanyCreated { .. }) =>
+					self.companies_registered.inc(),
+				// There is no transfer call: a product changes hands when its account records
+				// it again with another owner.
+				RuntimeEvent::TemplateModule(pallet_template::Event::ProductCreated {
+					product,
+				}) => {
+					let key =
+						pallet_template::AccountToProduct::<Runtime>::hashed_key_for(&product);
+					let before: Option<Product> =
+						read_value(client, &BlockId::Hash(parent_hash), &key)?;
+					let after: Option<Product> = read_value(client, &at, &key)?;
+					if let (Some(before), Some(after)) = (before, after) {
+						if before.id == after.id && before.owner != after.owner {
+							self.products_transferred.inc();
+						}
+					}
+				},
+				_ => (),
+			}
+		}
+		Ok(())
+	}
+
+	/// Recompute the gauges from the storage of the block `hash`.
+	fn update_gauges(&self, client: &FullClient, hash: Hash) -> Result<(), String> {
+		let at = BlockId::Hash(hash);
+		let contracts: Vec<SupplyContract> = read_map(
+			client,
+			&at,
+			pallet_template::AccountToSupplyContract::<Runtime>::final_prefix(),
+		)?;
+		self.open_contracts
+			.set(contracts.iter().filter(|contract| !contract.contract_fulfilled).count() as u64);
+
+		let ious: Vec<IOU> =
+			read_map(client, &at, pallet_template::AccountToIOU::<Runtime>::final_prefix())?;
+		let mut outstanding = BTreeMap::<_, u64>::new();
+		for iou in ious {
+			let total = outstanding.entry(iou.asset_id).or_default();
+			*total = total.saturating_add(iou.amount);
+		}
+		// Assets without IOUs left should not keep reporting their last value.
+		self.outstanding_iou_value.reset();
+		for (asset_id, amount) in outstanding {
+			self.outstanding_iou_value
+				.with_label_values(&[&asset_id.to_string()])
+				.set(amount);
+		}
+		Ok(())
+	}
+}
+
+/// Update `metrics` with every block that `client` finalizes. The gauges are left alone while
+/// `sync_oracle` reports a major sync.
+pub async fn run(
+	client: Arc<FullClient>,
+	metrics: SupplyChainMetrics,
+	sync_oracle: impl SyncOracle + Send + 'static,
+) {
+	let metrics = Arc::new(metrics);
+	let mut finality = client.finality_notification_stream();
+	while let Some(notification) = finality.next().await {
+		// Grandpa may finalize several blocks at once; the tree route holds those before the
+		// notified one, oldest first.
+		let blocks: Vec<Hash> =
+			notification.tree_route.iter().copied().chain(iter::once(notification.hash)).collect();
+		let gauges = !sync_oracle.is_major_syncing();
+		let (client, metrics) = (client.clone(), metrics.clone());
+		let result =
+			tokio::task::spawn_blocking(move || metrics.update(&client, &blocks, gauges)).await;
+		if let Err(e) = result.map_err(|e| e.to_string()).and_then(|result| result) {
+			log::warn!(
+				target: "supply-chain-metrics",
+				"Updating metrics at {} failed: {}",
+				notification.hash,
+				e
+			);
+		}
+	}
+}
+
+fn read_value<V: Decode>(
+	client: &FullClient,
+	at: &BlockId<Block>,
+	key: &[u8],
+) -> Result<Option<V>, String> {
+	client
+		.storage(at, &StorageKey(key.to_vec()))
+		.map_err(|e| e.to_string())?
+		.map(|value| V::decode(&mut &value.0[..]).map_err(|e| e.to_string()))
+		.transpose()
+}
+
+fn read_map<V: Decode>(
+	client: &FullClient,
+	at: &BlockId<Block>,
+	prefix: [u8; 32],
+) -> Result<Vec<V>, String> {
+	client
+		.storage_pairs(at, &StorageKey(prefix.to_vec()))
+		.map_err(|e| e.to_string())?
+		.into_iter()
+		.map(|(_, value)| V::decode(&mut &value.0[..]).map_err(|e| e.to_string()))
+		.collect()
+}
@@ -0,0 +1,98 @@
+//! The `supplychain_productProvenance` RPC: storage proofs of a product's ownership history that
+//! auditors check with the `provenance-verifier` crate, without running a node.
+
+use crate::service::{FullBackend, FullClient};
+use jsonrpsee::{
+	core::RpcResult,
+	proc_macros::rpc,
+	types::error::{CallError, ErrorObject},
+};
+use node_template_runtime::{opaque::Block, pallet_template::Product, AccountId, Hash};
+use provenance_verifier::{company_account_key, owner_ids, product_key, ProvenanceProof};
+use sc_client_api::{ProofProvider, StorageProvider, UsageProvider};
+use sc_finality_grandpa::FinalityProofProvider;
+use sp_blockchain::HeaderBackend;
+use sp_core::{storage::StorageKey, Decode};
+use sp_runtime::generic::BlockId;
+use std::sync::Arc;
+
+/// Provenance proofs of products.
+#[rpc(server)]
+pub trait ProvenanceApi {
+	/// Prove the product held by `account` and the accounts of the companies that owned it, at
+	/// the finalized block `at` or the last finalized block. The result includes a Grandpa
+	/// finality proof of the block if the node has one.
+	#[method(name = "supplychain_productProvenance")]
+	fn product_provenance(
+		&self,
+		account: AccountId,
+		at: Option<Hash>,
+	) -> RpcResult<ProvenanceProof>;
+}
+
+/// Serves `ProvenanceApi` from the node's database.
+pub struct Provenance {
+	client: Arc<FullClient>,
+	finality_proof_provider: Arc<FinalityProofProvider<FullBackend, Block>>,
+}
+
+impl Provenance {
+	pub fn new(
+		client: Arc<FullClient>,
+		finality_proof_provider: Arc<FinalityProofProvider<FullBackend, Block>>,
+	) -> Self {
+		Self { client, finality_proof_provider }
+	}
+
+	fn prove(&self, account: AccountId, at: Option<Hash>) -> Result<ProvenanceProof, String> {
+		let info = self.client.usage_info().chain;
+		let hash = at.unwrap_or(info.finalized_hash);
+		let number = self
+			.client
+			.number(hash)
+			.map_err(|e| e.to_string())?
+			.ok_or_else(|| format!("Block {:?} not found", hash))?;
+		let canonical = self.client.hash(number).map_err(|e| e.to_string())?;
+		if number > info.finalized_number || canonical != Some(hash) {
+			return Err(format!("Block {:?} is not finalized", hash))
+		}
+		let block_id = BlockId::Hash(hash);
+
+		let product_key = product_key(&account);
+		let product: Product = self
+			.client
+			.storage(&block_id, &StorageKey(product_key.clone()))
+			.map_err(|e| e.to_string())?
+			.map(|value| Product::decode(&mut &value.0[..]).map_err(|e| e.to_string()))
+			.transpose()?
+			.ok_or_else(|| format!("Account {} holds no product", account))?;
+		let keys: Vec<_> = [product_key]
+			.into_iter()
+			.chain(owner_ids(&product).into_iter().map(company_account_key))
+			.collect();
+		let proof = self
+			.client
+			.read_proof(&block_id, &mut keys.iter().map(|key| &key[..]))
+			.map_err(|e| e.to_string())?;
+
+		let finality_proof =
+			self.finality_proof_provider.prove_finality(number).map_err(|e| e.to_string())?;
+		Ok(ProvenanceProof {
+			block_hash: hash,
+			account,
+			proof: proof.into_iter_nodes().map(Into::into).collect(),
+			finality_proof: finality_proof.map(Into::into),
+		})
+	}
+}
+
+impl ProvenanceApiServer for Provenance {
+	fn product_provenance(
+		&self,
+		account: AccountId,
+		at: Option<Hash>,
+	) -> RpcResult<ProvenanceProof> {
+		self.prove(account, at)
+			.map_err(|e| CallError::Custom(ErrorObject::owned(1, e, None::<()>)).into())
+	}
+}
@@ -0,0 +1,479 @@
+//! A keystore that keeps no keys on the node host and asks a remote signer to sign instead.
+//!
+//! The node talks to the signer at `--keystore-uri` with JSON over HTTP `POST` requests. Every
+//! request carries the shared secret of `--keystore-token-file` as an `Authorization: Bearer`
+//! header, and the signer rejects requests without it with status `401`. The secret does not
+//! protect the requests in transit, so the signer should be reached over HTTPS or a private link.
+//! Key types are their four character ids (`aura`, `gran`), crypto types are `sr25`, `ed25` and
+//! `ecds`, and public keys, messages and signatures are `0x`-prefixed hex:
+//!
+//! | Path        | Request                                         | Response                        |
+//! |-------------|-------------------------------------------------|---------------------------------|
+//! | `/keys`     | `{ key_type }`                                  | `{ keys: [{ crypto, public }] }` |
+//! | `/generate` | `{ key_type, crypto, seed }`                    | `{ public }`                    |
+//! | `/sign`     | `{ key_type, crypto, public, message }`         | `{ signature }`                 |
+//! | `/has_keys` | `{ keys: [{ key_type, public }] }`              | `{ has_keys }`                  |
+//!
+//! `seed` is an optional secret URI. `signature` is `null` if the signer does not hold the key,
+//! and `has_keys` is `true` only if it holds all of `keys`. Request bodies are limited to
+//! `MAX_REQUEST_SIZE`. Any other status than `200` is an error, with a plain text reason in the
+//! body.
+//!
+//! Requests time out after `REQUEST_TIMEOUT`, so that a slow signer makes a validator miss a
+//! signature rather than its whole slot. The `async` methods run the requests on tokio's blocking
+//! thread pool, and the blocking methods, which consensus calls from async tasks, run them with
+//! `block_in_place` so that the other tasks of the worker thread move to another one meanwhile.
+//!
+//! The `remote-signer` binary of this crate implements the protocol on top of a local keystore.
+
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sp_core::{
+	crypto::{CryptoTypeId, CryptoTypePublicPair, KeyTypeId},
+	ecdsa, ed25519, sr25519, Bytes,
+};
+use sp_keystore::{
+	vrf::{VRFSignature, VRFTranscriptData},
+	CryptoStore, Error, SyncCryptoStore,
+};
+use std::{fs, path::Path, sync::Arc, time::Duration};
+use tokio::runtime::RuntimeFlavor;
+
+/// Timeout of a request to the signer, well under the 6 second slot.
+pub const REQUEST_TIMEOUT: Duration = Duration::from_millis(1500);
+
+/// Wire types of the signing protocol.
+pub mod protocol {
+	use super::*;
+
+	/// Largest request body the signer reads, far more than a message to sign needs.
+	pub const MAX_REQUEST_SIZE: usize = 64 * 1024;
+
+	#[derive(Serialize, Deserialize)]
+	pub struct KeysRequest {
+		pub key_type: String,
+	}
+
+	#[derive(Serialize, Deserialize)]
+	pub struct Key {
+		pub crypto: String,
+		pub public: Bytes,
+	}
+
+	#[derive(Serialize, Deserialize)]
+	pub struct KeysResponse {
+		pub keys: Vec<Key>,
+	}
+
+	#[derive(Serialize, Deserialize)]
+	pub struct GenerateRequest {
+		pub key_type: String,
+		pub crypto: String,
+		pub seed: Option<String>,
+	}
+
+	#[derive(Serialize, Deserialize)]
+	pub struct GenerateResponse {
+		pub public: Bytes,
+	}
+
+	#[derive(Serialize, Deserialize)]
+	pub struct SignRequest {
+		pub key_type: String,
+		pub crypto: String,
+		pub public: Bytes,
+		pub message: Bytes,
+	}
+
+	#[derive(Serialize, Deserialize)]
+	pub struct SignResponse {
+		pub signature: Option<Bytes>,
+	}
+
+	#[derive(Serialize, Deserialize)]
+	pub struct PublicKey {
+		pub key_type: String,
+		pub public: Bytes,
+	}
+
+	#[derive(Serialize, Deserialize)]
+	pub struct HasKeysRequest {
+		pub keys: Vec<PublicKey>,
+	}
+
+	#[derive(Serialize, Deserialize)]
+	pub struct HasKeysResponse {
+		pub has_keys: bool,
+	}
+
+	/// The four character id of a key or crypto type.
+	pub fn id_to_string(id: [u8; 4]) -> String {
+		String::from_utf8_lossy(&id).into_owned()
+	}
+
+	/// Parse a four character key or crypto type id.
+	pub fn id_from_str(id: &str) -> Result<[u8; 4], String> {
+		id.as_bytes()
+			.try_into()
+			.map_err(|_| format!("`{}` is not a four character id", id))
+	}
+
+	/// Read the shared secret from the file at `path`, ignoring surrounding whitespace.
+	pub fn read_token(path: &Path) -> Result<String, String> {
+		let token = fs::read_to_string(path)
+			.map_err(|e| format!("Error reading token file `{}`: {}", path.display(), e))?;
+		let token = token.trim();
+		if token.len() < 16 {
+			return Err(format!("token in `{}` must be at least 16 characters", path.display()))
+		}
+		Ok(token.to_string())
+	}
+}
+
+use protocol::*;
+
+/// A `CryptoStore` that forwards every request to a remote signer.
+pub struct RemoteKeystore {
+	client: Arc<Client>,
+}
+
+impl RemoteKeystore {
+	/// Connect to the signer at `url` with the shared secret `token` and check that it answers.
+	pub fn open(url: &str, token: String) -> Result<Self, String> {
+		if !url.starts_with("http://") && !url.starts_with("https://") {
+			return Err(format!("`{}` is not an http or https URL", url))
+		}
+		let agent = ureq::AgentBuilder::new().timeout(REQUEST_TIMEOUT).build();
+		let client = Client { url: url.trim_end_matches('/').to_string(), token, agent };
+		client
+			.keys_of(KeyTypeId(*b"aura"))
+			.map_err(|e| format!("signer is not reachable: {}", e))?;
+		Ok(Self { client: Arc::new(client) })
+	}
+
+	/// Run `f` with the client on the blocking thread pool.
+	async fn unblock<R: Send + 'static>(
+		&self,
+		f: impl FnOnce(&Client) -> R + Send + 'static,
+	) -> Result<R, Error> {
+		let client = self.client.clone();
+		tokio::task::spawn_blocking(move || f(&client))
+			.await
+			.map_err(|e| Error::Other(format!("signer request failed: {}", e)))
+	}
+
+	/// Run `f` with the client on the current thread, handing the other tasks of a tokio worker
+	/// thread to the rest of the runtime while it blocks.
+	fn block<R>(&self, f: impl FnOnce(&Client) -> R) -> R {
+		match tokio::runtime::Handle::try_current() {
+			Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread =>
+				tokio::task::block_in_place(|| f(&self.client)),
+			_ => f(&self.client),
+		}
+	}
+}
+
+/// The blocking HTTP client of a `RemoteKeystore`.
+struct Client {
+	url: String,
+	token: String,
+	agent: ureq::Agent,
+}
+
+impl Client {
+	fn post<Req: Serialize, Resp: DeserializeOwned>(
+		&self,
+		path: &str,
+		request: &Req,
+	) -> Result<Resp, Error> {
+		let response = self
+			.agent
+			.post(&format!("{}{}", self.url, path))
+			.set("Authorization", &format!("Bearer {}", self.token))
+			.send_json(request)
+			.map_err(|e| match e {
+				ureq::Error::Status(status, response) => Error::Other(format!(
+					"signer answered {}: {}",
+					status,
+					response.into_string().unwrap_or_default()
+				)),
+				ureq::Error::Transport(e) => Error::Other(format!("signer unreachable: {}", e)),
+			})?;
+		response
+			.into_json()
+			.map_err(|e| Error::Other(format!("invalid signer response: {}", e)))
+	}
+
+	fn keys_of(&self, id: KeyTypeId) -> Result<Vec<CryptoTypePublicPair>, Error> {
+		let response: KeysResponse =
+			self.post("/keys", &KeysRequest { key_type: id_to_string(id.0) })?;
+		response
+			.keys
+			.into_iter()
+			.map(|key| {
+				let crypto = id_from_str(&key.crypto).map_err(Error::Other)?;
+				Ok(CryptoTypePublicPair(CryptoTypeId(crypto), key.public.0))
+			})
+			.collect()
+	}
+
+	fn public_keys<T: for<'a> TryFrom<&'a [u8]>>(
+		&self,
+		id: KeyTypeId,
+		crypto: CryptoTypeId,
+	) -> Vec<T> {
+		match self.keys_of(id) {
+			Ok(keys) => keys
+				.into_iter()
+				.filter(|key| key.0 == crypto)
+				.filter_map(|key| T::try_from(&key.1[..]).ok())
+				.collect(),
+			Err(e) => {
+				log::warn!(target: "remote-keystore", "Listing {:?} keys failed: {}", id, e);
+				Vec::new()
+			},
+		}
+	}
+
+	fn generate(
+		&self,
+		id: KeyTypeId,
+		crypto: CryptoTypeId,
+		seed: Option<&str>,
+	) -> Result<Vec<u8>, Error> {
+		let response: GenerateResponse = self.post(
+			"/generate",
+			&GenerateRequest {
+				key_type: id_to_string(id.0),
+				crypto: id_to_string(crypto.0),
+				seed: seed.map(Into::into),
+			},
+		)?;
+		Ok(response.public.0)
+	}
+
+	fn has_keys(&self, public_keys: &[(Vec<u8>, KeyTypeId)]) -> bool {
+		let keys = public_keys
+			.iter()
+			.map(|(public, id)| PublicKey {
+				key_type: id_to_string(id.0),
+				public: public.clone().into(),
+			})
+			.collect();
+		match self.post::<_, HasKeysResponse>("/has_keys", &HasKeysRequest { keys }) {
+			Ok(response) => response.has_keys,
+			Err(e) => {
+				log::warn!(target: "remote-keystore", "Checking for keys failed: {}", e);
+				false
+			},
+		}
+	}
+
+	fn sign_with(
+		&self,
+		id: KeyTypeId,
+		key: &CryptoTypePublicPair,
+		msg: &[u8],
+	) -> Result<Option<Vec<u8>>, Error> {
+		let response: SignResponse = self.post(
+			"/sign",
+			&SignRequest {
+				key_type: id_to_string(id.0),
+				crypto: id_to_string(key.0 .0),
+				public: key.1.clone().into(),
+				message: msg.to_vec().into(),
+			},
+		)?;
+		Ok(response.signature.map(|signature| signature.0))
+	}
+}
+
+fn public<T: for<'a> TryFrom<&'a [u8]>>(bytes: Vec<u8>) -> Result<T, Error> {
+	T::try_from(&bytes[..])
+		.map_err(|_| Error::Other("signer returned an invalid public key".into()))
+}
+
+impl SyncCryptoStore for RemoteKeystore {
+	fn keys(&self, id: KeyTypeId) -> Result<Vec<CryptoTypePublicPair>, Error> {
+		self.block(|client| client.keys_of(id))
+	}
+
+	fn sr25519_public_keys(&self, id: KeyTypeId) -> Vec<sr25519::Public> {
+		self.block(|client| client.public_keys(id, sr25519::CRYPTO_ID))
+	}
+
+	fn sr25519_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<sr25519::Public, Error> {
+		public(self.block(|client| client.generate(id, sr25519::CRYPTO_ID, seed))?)
+	}
+
+	fn ed25519_public_keys(&self, id: KeyTypeId) -> Vec<ed25519::Public> {
+		self.block(|client| client.public_keys(id, ed25519::CRYPTO_ID))
+	}
+
+	fn ed25519_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<ed25519::Public, Error> {
+		public(self.block(|client| client.generate(id, ed25519::CRYPTO_ID, seed))?)
+	}
+
+	fn ecdsa_public_keys(&self, id: KeyTypeId) -> Vec<ecdsa::Public> {
+		self.block(|client| client.public_keys(id, ecdsa::CRYPTO_ID))
+	}
+
+	fn ecdsa_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<ecdsa::Public, Error> {
+		public(self.block(|client| client.generate(id, ecdsa::CRYPTO_ID, seed))?)
+	}
+
+	/// Secret keys never leave the signer, so they cannot be inserted through the node.
+	fn insert_unknown(&self, _id: KeyTypeId, _suri: &str, _public: &[u8]) -> Result<(), ()> {
+		Err(())
+	}
+
+	fn has_keys(&self, public_keys: &[(Vec<u8>, KeyTypeId)]) -> bool {
+		self.block(|client| client.has_keys(public_keys))
+	}
+
+	fn sign_with(
+		&self,
+		id: KeyTypeId,
+		key: &CryptoTypePublicPair,
+		msg: &[u8],
+	) -> Result<Option<Vec<u8>>, Error> {
+		self.block(|client| client.sign_with(id, key, msg))
+	}
+
+	fn sr25519_vrf_sign(
+		&self,
+		_key_type: KeyTypeId,
+		_public: &sr25519::Public,
+		_transcript_data: VRFTranscriptData,
+	) -> Result<Option<VRFSignature>, Error> {
+		Err(Error::Unavailable)
+	}
+
+	fn ecdsa_sign_prehashed(
+		&self,
+		_id: KeyTypeId,
+		_public: &ecdsa::Public,
+		_msg: &[u8; 32],
+	) -> Result<Option<ecdsa::Signature>, Error> {
+		Err(Error::Unavailable)
+	}
+}
+
+#[async_trait]
+impl CryptoStore for RemoteKeystore {
+	async fn keys(&self, id: KeyTypeId) -> Result<Vec<CryptoTypePublicPair>, Error> {
+		self.unblock(move |client| client.keys_of(id)).await?
+	}
+
+	async fn sr25519_public_keys(&self, id: KeyTypeId) -> Vec<sr25519::Public> {
+		self.unblock(move |client| client.public_keys(id, sr25519::CRYPTO_ID))
+			.await
+			.unwrap_or_default()
+	}
+
+	async fn sr25519_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<sr25519::Public, Error> {
+		let seed = seed.map(str::to_owned);
+		let public_key = self
+			.unblock(move |client| client.generate(id, sr25519::CRYPTO_ID, seed.as_deref()))
+			.await??;
+		public(public_key)
+	}
+
+	async fn ed25519_public_keys(&self, id: KeyTypeId) -> Vec<ed25519::Public> {
+		self.unblock(move |client| client.public_keys(id, ed25519::CRYPTO_ID))
+			.await
+			.unwrap_or_default()
+	}
+
+	async fn ed25519_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<ed25519::Public, Error> {
+		let seed = seed.map(str::to_owned);
+		let public_key = self
+			.unblock(move |client| client.generate(id, ed25519::CRYPTO_ID, seed.as_deref()))
+			.await??;
+		public(public_key)
+	}
+
+	async fn ecdsa_public_keys(&self, id: KeyTypeId) -> Vec<ecdsa::Public> {
+		self.unblock(move |client| client.public_keys(id, ecdsa::CRYPTO_ID))
+			.await
+			.unwrap_or_default()
+	}
+
+	async fn ecdsa_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<ecdsa::Public, Error> {
+		let seed = seed.map(str::to_owned);
+		let public_key = self
+			.unblock(move |client| client.generate(id, ecdsa::CRYPTO_ID, seed.as_deref()))
+			.await??;
+		public(public_key)
+	}
+
+	async fn insert_unknown(&self, id: KeyTypeId, suri: &str, public: &[u8]) -> Result<(), ()> {
+		SyncCryptoStore::insert_unknown(self, id, suri, public)
+	}
+
+	async fn supported_keys(
+		&self,
+		id: KeyTypeId,
+		keys: Vec<CryptoTypePublicPair>,
+	) -> Result<Vec<CryptoTypePublicPair>, Error> {
+		let all_keys = CryptoStore::keys(self, id).await?;
+		Ok(keys.into_iter().filter(|key| all_keys.contains(key)).collect())
+	}
+
+	async fn has_keys(&self, public_keys: &[(Vec<u8>, KeyTypeId)]) -> bool {
+		let public_keys = public_keys.to_vec();
+		self.unblock(move |client| client.has_keys(&public_keys)).await.unwrap_or(false)
+	}
+
+	async fn sign_with(
+		&self,
+		id: KeyTypeId,
+		key: &CryptoTypePublicPair,
+		msg: &[u8],
+	) -> Result<Option<Vec<u8>>, Error> {
+		let (key, msg) = (key.clone(), msg.to_vec());
+		self.unblock(move |client| client.sign_with(id, &key, &msg)).await?
+	}
+
+	async fn sr25519_vrf_sign(
+		&self,
+		key_type: KeyTypeId,
+		public: &sr25519::Public,
+		transcript_data: VRFTranscriptData,
+	) -> Result<Option<VRFSignature>, Error> {
+		SyncCryptoStore::sr25519_vrf_sign(self, key_type, public, transcript_data)
+	}
+
+	async fn ecdsa_sign_prehashed(
+		&self,
+		id: KeyTypeId,
+		public: &ecdsa::Public,
+		msg: &[u8; 32],
+	) -> Result<Option<ecdsa::Signature>, Error> {
+		SyncCryptoStore::ecdsa_sign_prehashed(self, id, public, msg)
+	}
+}
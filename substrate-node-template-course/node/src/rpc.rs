@@ -7,8 +7,11 @@
 
 use std::sync::Arc;
 
+use crate::{event_index::EventIndex, provenance::Provenance};
+use futures::channel::mpsc::Sender;
 use jsonrpsee::RpcModule;
-use node_template_runtime::{opaque::Block, AccountId, Balance, Index};
+use node_template_runtime::{opaque::Block, AccountId, Balance, BlockNumber, Hash, Index};
+use sc_consensus_manual_seal::EngineCommand;
 use sc_transaction_pool_api::TransactionPool;
 use sp_api::ProvideRuntimeApi;
 use sp_block_builder::BlockBuilder;
@@ -24,6 +27,12 @@ pub struct FullDeps<C, P> {
 	pub pool: Arc<P>,
 	/// Whether to deny unsafe calls
 	pub deny_unsafe: DenyUnsafe,
+	/// Commands for manual seal, if the node runs with `--sealing`.
+	pub command_sink: Option<Sender<EngineCommand<Hash>>>,
+	/// The supply-chain event index, if the node runs with `--event-index`.
+	pub event_index: Option<Arc<EventIndex>>,
+	/// Provenance proofs of products.
+	pub provenance: Provenance,
 }
 
 /// Instantiate all full RPC extensions.
@@ -36,17 +45,35 @@ where
 	C: Send + Sync + 'static,
 	C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Index>,
 	C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
+	C::Api: pallet_contracts_rpc::ContractsRuntimeApi<Block, AccountId, Balance, BlockNumber, Hash>,
 	C::Api: BlockBuilder<Block>,
 	P: TransactionPool + 'static,
 {
+	use crate::{
+		event_index::{SupplyChain, SupplyChainApiServer},
+		provenance::ProvenanceApiServer,
+	};
+	use pallet_contracts_rpc::{Contracts, ContractsApiServer};
 	use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApiServer};
+	use sc_consensus_manual_seal::rpc::{ManualSeal, ManualSealApiServer};
 	use substrate_frame_rpc_system::{System, SystemApiServer};
 
 	let mut module = RpcModule::new(());
-	let FullDeps { client, pool, deny_unsafe } = deps;
+	let FullDeps { client, pool, deny_unsafe, command_sink, event_index, provenance } = deps;
 
 	module.merge(System::new(client.clone(), pool.clone(), deny_unsafe).into_rpc())?;
-	module.merge(TransactionPayment::new(client).into_rpc())?;
+	module.merge(TransactionPayment::new(client.clone()).into_rpc())?;
+	module.merge(Contracts::new(client).into_rpc())?;
+	module.merge(provenance.into_rpc())?;
+
+	if let Some(command_sink) = command_sink {
+		// `engine_createBlock` and `engine_finalizeBlock`.
+		module.merge(ManualSeal::new(command_sink).into_rpc())?;
+	}
+
+	if let Some(event_index) = event_index {
+		module.merge(SupplyChain::new(event_index).into_rpc())?;
+	}
 
 	// Extend this RPC with a custom API by using the following syntax.
 	// `YourRpcStruct` should have a reference to a client, which is needed
@@ -1,15 +1,30 @@
 //! Service and ServiceFactory implementation. Specialized wrapper over substrate service.
 
-use node_template_runtime::{self, opaque::Block, RuntimeApi};
-use sc_client_api::BlockBackend;
+use crate::{event_index::EventIndex, provenance::Provenance, remote_keystore::RemoteKeystore};
+use frame_support::storage::storage_prefix;
+use futures::{
+	channel::mpsc,
+	stream::{self, BoxStream, StreamExt},
+};
+use futures_timer::Delay;
+use node_template_runtime::{self, opaque::Block, Hash, RuntimeApi};
+use sc_client_api::{BlockBackend, StorageProvider, UsageProvider};
 use sc_consensus_aura::{ImportQueueParams, SlotProportion, StartAuraParams};
+use sc_consensus_manual_seal::{
+	consensus::aura::AuraConsensusDataProvider, EngineCommand, ManualSealParams,
+};
 pub use sc_executor::NativeElseWasmExecutor;
 use sc_finality_grandpa::SharedVoterState;
-use sc_keystore::LocalKeystore;
 use sc_service::{error::Error as ServiceError, Configuration, TaskManager};
 use sc_telemetry::{Telemetry, TelemetryWorker};
 use sp_consensus_aura::sr25519::AuthorityPair as AuraPair;
-use std::{sync::Arc, time::Duration};
+use sp_core::{storage::StorageKey, Decode};
+use sp_runtime::generic::BlockId;
+use std::{
+	str::FromStr,
+	sync::{Arc, Mutex},
+	time::Duration,
+};
 
 // Our native executor instance.
 pub struct ExecutorDispatch;
@@ -33,35 +48,62 @@ impl sc_executor::NativeExecutionDispatch for ExecutorDispatch {
 
 pub(crate) type FullClient =
 	sc_service::TFullClient<Block, RuntimeApi, NativeElseWasmExecutor<ExecutorDispatch>>;
-type FullBackend = sc_service::TFullBackend<Block>;
+pub(crate) type FullBackend = sc_service::TFullBackend<Block>;
 type FullSelectChain = sc_consensus::LongestChain<FullBackend, Block>;
 
-pub fn new_partial(
-	config: &Configuration,
-) -> Result<
-	sc_service::PartialComponents<
-		FullClient,
-		FullBackend,
-		FullSelectChain,
-		sc_consensus::DefaultImportQueue<Block, FullClient>,
-		sc_transaction_pool::FullPool<Block, FullClient>,
-		(
-			sc_finality_grandpa::GrandpaBlockImport<
-				FullBackend,
-				Block,
-				FullClient,
-				FullSelectChain,
-			>,
-			sc_finality_grandpa::LinkHalf<Block, FullClient, FullSelectChain>,
-			Option<Telemetry>,
-		),
-	>,
-	ServiceError,
-> {
-	if config.keystore_remote.is_some() {
-		return Err(ServiceError::Other("Remote Keystores are not supported.".into()))
+type PartialComponents = sc_service::PartialComponents<
+	FullClient,
+	FullBackend,
+	FullSelectChain,
+	sc_consensus::DefaultImportQueue<Block, FullClient>,
+	sc_transaction_pool::FullPool<Block, FullClient>,
+	(
+		sc_finality_grandpa::GrandpaBlockImport<FullBackend, Block, FullClient, FullSelectChain>,
+		sc_finality_grandpa::LinkHalf<Block, FullClient, FullSelectChain>,
+		Option<Telemetry>,
+	),
+>;
+
+/// How blocks are built when the node runs without Aura and Grandpa.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sealing {
+	/// Build a block as soon as a transaction enters the pool.
+	Instant,
+	/// Build blocks only when asked to with `engine_createBlock`.
+	Manual,
+	/// Build a block every given number of milliseconds.
+	Interval(u64),
+}
+
+impl FromStr for Sealing {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"instant" => Ok(Sealing::Instant),
+			"manual" => Ok(Sealing::Manual),
+			_ => s
+				.strip_prefix("interval=")
+				.and_then(|millis| millis.parse().ok())
+				.filter(|millis| *millis > 0)
+				.map(Sealing::Interval)
+				.ok_or_else(|| {
+					format!("expected `instant`, `manual` or `interval=<ms>`, got `{}`", s)
+				}),
+		}
 	}
+}
 
+pub fn new_partial(config: &Configuration) -> Result<PartialComponents, ServiceError> {
+	partial_components(config, false)
+}
+
+/// The components of `new_partial`, with an import queue for manually sealed blocks if
+/// `manual_seal` is set.
+fn partial_components(
+	config: &Configuration,
+	manual_seal: bool,
+) -> Result<PartialComponents, ServiceError> {
 	let telemetry = config
 		.telemetry_endpoints
 		.clone()
@@ -110,9 +152,15 @@ pub fn new_partial(
 		telemetry.as_ref().map(|x| x.handle()),
 	)?;
 
-	let slot_duration = sc_consensus_aura::slot_duration(&*client)?;
+	let import_queue = if manual_seal {
+		sc_consensus_manual_seal::import_queue(
+			Box::new(client.clone()),
+			&task_manager.spawn_essential_handle(),
+			config.prometheus_registry(),
+		)
+	} else {
+		let slot_duration = sc_consensus_aura::slot_duration(&*client)?;
 
-	let import_queue =
 		sc_consensus_aura::import_queue::<AuraPair, _, _, _, _, _>(ImportQueueParams {
 			block_import: grandpa_block_import.clone(),
 			justification_import: Some(Box::new(grandpa_block_import.clone())),
@@ -132,7 +180,8 @@ pub fn new_partial(
 			registry: config.prometheus_registry(),
 			check_for_equivocation: Default::default(),
 			telemetry: telemetry.as_ref().map(|x| x.handle()),
-		})?;
+		})?
+	};
 
 	Ok(sc_service::PartialComponents {
 		client,
@@ -146,15 +195,55 @@ pub fn new_partial(
 	})
 }
 
-fn remote_keystore(_url: &String) -> Result<Arc<LocalKeystore>, &'static str> {
-	// FIXME: here would the concrete keystore be built,
-	//        must return a concrete type (NOT `LocalKeystore`) that
-	//        implements `CryptoStore` and `SyncCryptoStore`
-	Err("Remote Keystore not supported.")
+fn remote_keystore(url: &str, token: Option<String>) -> Result<Arc<RemoteKeystore>, String> {
+	let token = token.ok_or("the signer's shared secret is missing, see `--keystore-token-file`")?;
+	RemoteKeystore::open(url, token).map(Arc::new)
+}
+
+/// Timestamps of manually sealed blocks.
+///
+/// Aura requires every block to be in a later slot than its parent, so a block is stamped with
+/// the current time but at least one slot after the previous block. Sealing blocks faster than
+/// one per slot therefore moves the chain's time ahead of the wall clock, which is why
+/// `--sealing` is only allowed for `--dev` chains.
+struct SealTimestamps {
+	slot_duration: u64,
+	last: Mutex<u64>,
+}
+
+impl SealTimestamps {
+	/// Continue after the timestamp of the best block of `client`.
+	fn new(client: &FullClient, slot_duration: u64) -> Result<Self, ServiceError> {
+		let best = BlockId::Hash(client.usage_info().chain.best_hash);
+		let key = StorageKey(storage_prefix(b"Timestamp", b"Now").to_vec());
+		let last = client
+			.storage(&best, &key)?
+			.map(|now| u64::decode(&mut &now.0[..]))
+			.transpose()
+			.map_err(|e| ServiceError::Other(format!("Invalid timestamp: {}", e)))?
+			.unwrap_or_default();
+		Ok(Self { slot_duration, last: Mutex::new(last) })
+	}
+
+	fn next(&self) -> sp_timestamp::InherentDataProvider {
+		let mut last = self.last.lock().expect("lock is never poisoned; qed");
+		*last = (*last + self.slot_duration).max(sp_timestamp::Timestamp::current().as_millis());
+		sp_timestamp::InherentDataProvider::new((*last).into())
+	}
 }
 
 /// Builds a new service for a full client.
-pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError> {
+///
+/// With `sealing` set, blocks are built with manual seal instead of Aura and Grandpa, which is
+/// meant for development chains only. With `event_index` set, supply-chain events are indexed
+/// for the `supplychain_*` RPCs. `keystore_token` is the shared secret of the `--keystore-uri`
+/// signer.
+pub fn new_full(
+	mut config: Configuration,
+	sealing: Option<Sealing>,
+	event_index: bool,
+	keystore_token: Option<String>,
+) -> Result<TaskManager, ServiceError> {
 	let sc_service::PartialComponents {
 		client,
 		backend,
@@ -164,10 +253,17 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 		select_chain,
 		transaction_pool,
 		other: (block_import, grandpa_link, mut telemetry),
-	} = new_partial(&config)?;
-
+	} = partial_components(&config, sealing.is_some())?;
+
+	if sealing.is_some() && config.keystore_remote.is_some() {
+		return Err(ServiceError::Other(
+			"Remote keystores are not supported with `--sealing`, manually sealed blocks are not \
+			 signed."
+				.into(),
+		))
+	}
 	if let Some(url) = &config.keystore_remote {
-		match remote_keystore(url) {
+		match remote_keystore(url, keystore_token) {
 			Ok(k) => keystore_container.set_remote_keystore(k),
 			Err(e) =>
 				return Err(ServiceError::Other(format!(
@@ -218,13 +314,42 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 	let enable_grandpa = !config.disable_grandpa;
 	let prometheus_registry = config.prometheus_registry().cloned();
 
+	let event_index = if event_index {
+		let base_path = config.base_path.as_ref().ok_or("The event index needs a base path")?;
+		let path = base_path.config_dir(config.chain_spec.id()).join("event-index");
+		Some(Arc::new(EventIndex::open(&path).map_err(ServiceError::Other)?))
+	} else {
+		None
+	};
+
+	// Commands of the `engine_*` RPCs for manual seal.
+	let (command_sink, commands_stream) = match sealing {
+		Some(_) => {
+			let (sink, stream) = mpsc::channel(1024);
+			(Some(sink), Some(stream))
+		},
+		None => (None, None),
+	};
+
+	let finality_proof_provider = sc_finality_grandpa::FinalityProofProvider::new_for_service(
+		backend.clone(),
+		Some(grandpa_link.shared_authority_set().clone()),
+	);
+
 	let rpc_extensions_builder = {
 		let client = client.clone();
 		let pool = transaction_pool.clone();
+		let event_index = event_index.clone();
 
 		Box::new(move |deny_unsafe, _| {
-			let deps =
-				crate::rpc::FullDeps { client: client.clone(), pool: pool.clone(), deny_unsafe };
+			let deps = crate::rpc::FullDeps {
+				client: client.clone(),
+				pool: pool.clone(),
+				deny_unsafe,
+				command_sink: command_sink.clone(),
+				event_index: event_index.clone(),
+				provenance: Provenance::new(client.clone(), finality_proof_provider.clone()),
+			};
 			crate::rpc::create_full(deps).map_err(Into::into)
 		})
 	};
@@ -243,6 +368,89 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 		telemetry: telemetry.as_mut(),
 	})?;
 
+	if let Some(registry) = prometheus_registry.as_ref() {
+		let metrics = crate::metrics::SupplyChainMetrics::register(registry)?;
+		task_manager.spawn_handle().spawn(
+			"supply-chain-metrics",
+			None,
+			crate::metrics::run(client.clone(), metrics, network.clone()),
+		);
+	}
+
+	if let Some(event_index) = event_index {
+		task_manager.spawn_handle().spawn_blocking(
+			"event-index",
+			None,
+			crate::event_index::run(client.clone(), event_index),
+		);
+	}
+
+	if let (Some(sealing), Some(commands_stream)) = (sealing, commands_stream) {
+		let proposer_factory = sc_basic_authorship::ProposerFactory::new(
+			task_manager.spawn_handle(),
+			client.clone(),
+			transaction_pool.clone(),
+			prometheus_registry.as_ref(),
+			telemetry.as_ref().map(|x| x.handle()),
+		);
+
+		let commands_stream: BoxStream<'static, EngineCommand<Hash>> = match sealing {
+			Sealing::Manual => Box::pin(commands_stream),
+			Sealing::Instant => Box::pin(stream::select(
+				commands_stream,
+				transaction_pool.pool().validated_pool().import_notification_stream().map(|_| {
+					EngineCommand::SealNewBlock {
+						create_empty: false,
+						finalize: true,
+						parent_hash: None,
+						sender: None,
+					}
+				}),
+			)),
+			Sealing::Interval(millis) => Box::pin(stream::select(
+				commands_stream,
+				stream::unfold((), move |()| async move {
+					Delay::new(Duration::from_millis(millis)).await;
+					let command = EngineCommand::SealNewBlock {
+						create_empty: true,
+						finalize: true,
+						parent_hash: None,
+						sender: None,
+					};
+					Some((command, ()))
+				}),
+			)),
+		};
+
+		let slot_duration = sc_consensus_aura::slot_duration(&*client)?;
+		let timestamps =
+			Arc::new(SealTimestamps::new(&client, slot_duration.as_duration().as_millis() as u64)?);
+
+		let manual_seal = sc_consensus_manual_seal::run_manual_seal(ManualSealParams {
+			block_import: client.clone(),
+			env: proposer_factory,
+			client: client.clone(),
+			pool: transaction_pool,
+			commands_stream,
+			select_chain,
+			consensus_data_provider: Some(Box::new(AuraConsensusDataProvider::new(client.clone()))),
+			create_inherent_data_providers: move |_, ()| {
+				let timestamps = timestamps.clone();
+				async move { Ok(timestamps.next()) }
+			},
+		});
+
+		// Without Grandpa, blocks are only finalized by manual seal.
+		task_manager.spawn_essential_handle().spawn_blocking(
+			"manual-seal",
+			Some("block-authoring"),
+			manual_seal,
+		);
+
+		network_starter.start_network();
+		return Ok(task_manager)
+	}
+
 	if role.is_authority() {
 		let proposer_factory = sc_basic_authorship::ProposerFactory::new(
 			task_manager.spawn_handle(),
@@ -0,0 +1,404 @@
+//! The `sign-transaction` subcommand: builds and signs a supply-chain transaction without a
+//! node or network connection, so that keys kept on an offline machine can sign. The printed
+//! extrinsic is broadcast later from any machine with `author_submitExtrinsic`.
+
+use node_template_runtime::{
+	AccountId, Balance, BlockNumber, ChargeSponsoredTransactionPayment, Hash, Index, Runtime,
+	RuntimeCall, SignedExtra, SignedPayload, TemplateModuleCall, UncheckedExtrinsic, VERSION,
+};
+use sc_cli::KeystoreParams;
+use sc_keystore::LocalKeystore;
+use sp_core::{
+	crypto::{ByteArray, CryptoTypePublicPair, ExposeSecret, KeyTypeId, Ss58Codec},
+	hexdisplay::HexDisplay,
+	sr25519, Encode, Pair,
+};
+use sp_keystore::SyncCryptoStore;
+use sp_runtime::{generic::Era, MultiAddress, MultiSignature};
+use std::{
+	fs,
+	io::{self, Read},
+	path::{Path, PathBuf},
+};
+
+/// A call of the supply-chain pallet dispatched with the signer's origin.
+#[derive(Debug, Clone, clap::Subcommand)]
+pub enum SupplyChainCall {
+	/// Register the signer's company.
+	RegisterCompany {
+		#[clap(long)]
+		name: String,
+		#[clap(long)]
+		id: u64,
+		#[clap(long, default_value = "")]
+		about_me: String,
+	},
+
+	/// Create a product held by the signer.
+	CreateProduct {
+		#[clap(long)]
+		id: u64,
+		#[clap(long)]
+		name: String,
+		#[clap(long, default_value = "")]
+		description: String,
+		/// Company id of the owner.
+		#[clap(long)]
+		owner: u64,
+		/// Comma separated company ids of the previous owners.
+		#[clap(long, value_delimiter = ',')]
+		previous_owners: Vec<u64>,
+	},
+
+	/// Create a supply contract held by the signer.
+	CreateSupplyContract {
+		#[clap(long)]
+		id: u64,
+		#[clap(long)]
+		seller_id: u64,
+		#[clap(long)]
+		buyer_id: u64,
+		/// Comma separated product ids.
+		#[clap(long, value_delimiter = ',')]
+		products: Vec<u64>,
+		#[clap(long)]
+		delivered: bool,
+		#[clap(long, default_value = "0")]
+		iou: u64,
+		#[clap(long)]
+		contract_value: u64,
+		#[clap(long)]
+		contract_fulfilled: bool,
+		#[clap(long, default_value = "0")]
+		asset_id: u32,
+	},
+
+	/// Create an IOU held by the signer.
+	CreateIou {
+		#[clap(long)]
+		id: u64,
+		/// Company id of the debtor.
+		#[clap(long)]
+		debtor: u64,
+		/// Company id of the creditor.
+		#[clap(long)]
+		creditor: u64,
+		#[clap(long)]
+		amount: u64,
+		#[clap(long, default_value = "0")]
+		asset_id: u32,
+	},
+
+	/// Confirm an IOU as its debtor or creditor.
+	ConfirmIou {
+		/// Account holding the IOU.
+		#[clap(long)]
+		iou: AccountId,
+	},
+
+	/// Mark the signer's supply contract as delivered or fulfilled.
+	UpdateSupplyContractStatus {
+		#[clap(long)]
+		delivered: bool,
+		#[clap(long)]
+		contract_fulfilled: bool,
+	},
+
+	/// Pay the value of a supply contract into escrow.
+	FundSupplyContract {
+		/// Account holding the contract.
+		#[clap(long)]
+		contract: AccountId,
+	},
+
+	/// Confirm delivery of a supply contract as its buyer, releasing its escrow to the seller.
+	ConfirmDelivery {
+		/// Account holding the contract.
+		#[clap(long)]
+		contract: AccountId,
+	},
+
+	/// Repay part of an IOU.
+	RepayIou {
+		/// Account holding the IOU.
+		#[clap(long)]
+		iou: AccountId,
+		#[clap(long)]
+		amount: u64,
+	},
+
+	/// Repay an IOU in installments.
+	CreateInstallmentPlan {
+		/// Account holding the IOU.
+		#[clap(long)]
+		iou: AccountId,
+		#[clap(long)]
+		installments: u32,
+		/// Blocks between two installments.
+		#[clap(long)]
+		period: BlockNumber,
+	},
+
+	/// Net the IOUs two companies hold against each other.
+	NetIous {
+		#[clap(long)]
+		company_a: u64,
+		#[clap(long)]
+		company_b: u64,
+		#[clap(long, default_value = "0")]
+		asset_id: u32,
+	},
+
+	/// Pay the supply-chain transaction fees of a staff account.
+	SponsorStaff {
+		#[clap(long)]
+		staff: AccountId,
+	},
+
+	/// Stop paying the transaction fees of a staff account.
+	UnsponsorStaff {
+		#[clap(long)]
+		staff: AccountId,
+	},
+
+	/// Register the multisig of the signer's company.
+	SetCompanyMultisig {
+		/// Comma separated accounts of the officers.
+		#[clap(long, value_delimiter = ',')]
+		officers: Vec<AccountId>,
+		#[clap(long)]
+		threshold: u16,
+	},
+}
+
+impl From<SupplyChainCall> for RuntimeCall {
+	fn from(call: SupplyChainCall) -> Self {
+		match call {
+			SupplyChainCall::RegisterCompany { name, id, about_me } =>
+				TemplateModuleCall::register_company {
+					name: name.into_bytes(),
+					id,
+					about_me: about_me.into_bytes(),
+				},
+			SupplyChainCall::CreateProduct { id, name, description, owner, previous_owners } =>
+				TemplateModuleCall::create_product {
+					id,
+					name: name.into_bytes(),
+					description: description.into_bytes(),
+					owner,
+					previous_owners,
+				},
+			SupplyChainCall::CreateSupplyContract {
+				id,
+				seller_id,
+				buyer_id,
+				products,
+				delivered,
+				iou,
+				contract_value,
+				contract_fulfilled,
+				asset_id,
+			} => TemplateModuleCall::create_supply_contract {
+				id,
+				seller_id,
+				buyer_id,
+				products,
+				delivered,
+				iou,
+				contract_value,
+				contract_fulfilled,
+				asset_id,
+			},
+			SupplyChainCall::CreateIou { id, debtor, creditor, amount, asset_id } =>
+				TemplateModuleCall::create_iou { id, debtor, creditor, amount, asset_id },
+			SupplyChainCall::ConfirmIou { iou } => TemplateModuleCall::confirm_iou { iou },
+			SupplyChainCall::UpdateSupplyContractStatus { delivered, contract_fulfilled } =>
+				TemplateModuleCall::update_supply_contract_status { delivered, contract_fulfilled },
+			SupplyChainCall::FundSupplyContract { contract } =>
+				TemplateModuleCall::fund_supply_contract { contract },
+			SupplyChainCall::ConfirmDelivery { contract } =>
+				TemplateModuleCall::confirm_delivery { contract },
+			SupplyChainCall::RepayIou { iou, amount } =>
+				TemplateModuleCall::repay_iou { iou, amount },
+			SupplyChainCall::CreateInstallmentPlan { iou, installments, period } =>
+				TemplateModuleCall::create_installment_plan { iou, installments, period },
+			SupplyChainCall::NetIous { company_a, company_b, asset_id } =>
+				TemplateModuleCall::net_ious { company_a, company_b, asset_id },
+			SupplyChainCall::SponsorStaff { staff } => TemplateModuleCall::sponsor_staff { staff },
+			SupplyChainCall::UnsponsorStaff { staff } =>
+				TemplateModuleCall::unsponsor_staff { staff },
+			SupplyChainCall::SetCompanyMultisig { officers, threshold } =>
+				TemplateModuleCall::set_company_multisig { officers, threshold },
+		}
+		.into()
+	}
+}
+
+/// Build a supply-chain transaction, sign it offline and print it as a hex encoded extrinsic.
+#[derive(Debug, Clone, clap::Parser)]
+pub struct SignTransactionCmd {
+	#[clap(subcommand)]
+	pub call: SupplyChainCall,
+
+	/// Nonce of the signing account.
+	#[clap(long)]
+	pub nonce: Index,
+
+	/// Hash of the genesis block of the chain the transaction is for.
+	#[clap(long)]
+	pub genesis_hash: Hash,
+
+	/// Number of a recent block from which the transaction is valid for `--era-period` blocks.
+	#[clap(long, requires = "era-block-hash", required_unless_present = "immortal")]
+	pub era_block: Option<BlockNumber>,
+
+	/// Hash of `--era-block`.
+	#[clap(long, requires = "era-block")]
+	pub era_block_hash: Option<Hash>,
+
+	/// Number of blocks a mortal transaction is valid for, rounded to a power of two.
+	#[clap(long, default_value = "64")]
+	pub era_period: u64,
+
+	/// Sign a transaction that never expires instead of giving `--era-block`. It stays valid
+	/// until its nonce is used, even if it was meant to be dropped.
+	#[clap(long, conflicts_with = "era-block")]
+	pub immortal: bool,
+
+	/// Tip paid to the block author on top of the fees.
+	#[clap(long, default_value = "0")]
+	pub tip: Balance,
+
+	/// Spec version of the chain's runtime. Defaults to the runtime built into this node.
+	#[clap(long)]
+	pub spec_version: Option<u32>,
+
+	/// Transaction version of the chain's runtime. Defaults to the runtime built into this node.
+	#[clap(long)]
+	pub transaction_version: Option<u32>,
+
+	/// File holding the secret URI of the sr25519 signing key, e.g. a mnemonic or `//Alice`.
+	/// `-` reads it from standard input.
+	#[clap(
+		long,
+		value_name = "PATH",
+		conflicts_with = "keystore-path",
+		required_unless_present = "keystore-path"
+	)]
+	pub suri_file: Option<PathBuf>,
+
+	/// SS58 address of the signing key in `--keystore-path`.
+	#[clap(long, requires = "keystore-path")]
+	pub public: Option<String>,
+
+	/// Key type of the signing key in `--keystore-path`.
+	#[clap(long, default_value = "acco")]
+	pub key_type: String,
+
+	// `--keystore-path` holds the signing key, as filled by `key insert`. The password options
+	// unlock the keystore or the secret URI.
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub keystore_params: KeystoreParams,
+}
+
+impl SignTransactionCmd {
+	pub fn run(&self) -> sc_cli::Result<()> {
+		let extrinsic = self.build()?;
+		println!("0x{}", HexDisplay::from(&extrinsic.encode()));
+		Ok(())
+	}
+
+	/// Build and sign the extrinsic.
+	pub fn build(&self) -> sc_cli::Result<UncheckedExtrinsic> {
+		let call: RuntimeCall = self.call.clone().into();
+		let (era, era_hash) = match (self.era_block, self.era_block_hash, self.immortal) {
+			(Some(number), Some(hash), false) =>
+				(Era::mortal(self.era_period, number.into()), hash),
+			(None, None, true) => (Era::Immortal, self.genesis_hash),
+			_ => return Err("Give --era-block with --era-block-hash, or --immortal".into()),
+		};
+		let extra: SignedExtra = (
+			frame_system::CheckNonZeroSender::<Runtime>::new(),
+			frame_system::CheckSpecVersion::<Runtime>::new(),
+			frame_system::CheckTxVersion::<Runtime>::new(),
+			frame_system::CheckGenesis::<Runtime>::new(),
+			frame_system::CheckEra::<Runtime>::from(era),
+			frame_system::CheckNonce::<Runtime>::from(self.nonce),
+			frame_system::CheckWeight::<Runtime>::new(),
+			ChargeSponsoredTransactionPayment::from(self.tip),
+		);
+		let payload = SignedPayload::from_raw(
+			call.clone(),
+			extra.clone(),
+			(
+				(),
+				self.spec_version.unwrap_or(VERSION.spec_version),
+				self.transaction_version.unwrap_or(VERSION.transaction_version),
+				self.genesis_hash,
+				era_hash,
+				(),
+				(),
+				(),
+			),
+		);
+
+		let (signer, signature) = self.sign(&payload)?;
+		Ok(UncheckedExtrinsic::new_signed(
+			call,
+			MultiAddress::Id(signer.into()),
+			MultiSignature::Sr25519(signature),
+			extra,
+		))
+	}
+
+	fn sign(
+		&self,
+		payload: &SignedPayload,
+	) -> sc_cli::Result<(sr25519::Public, sr25519::Signature)> {
+		if self.keystore_params.keystore_uri.is_some() {
+			return Err("Remote keystores cannot sign offline, use --keystore-path".into())
+		}
+		let password = self.keystore_params.read_password()?;
+		if let Some(suri_file) = &self.suri_file {
+			let suri = read_suri(suri_file)?;
+			let password = password.as_ref().map(|password| password.expose_secret().as_str());
+			let pair = sr25519::Pair::from_string(&suri, password)
+				.map_err(|e| format!("Invalid secret URI: {:?}", e))?;
+			return Ok((pair.public(), payload.using_encoded(|message| pair.sign(message))))
+		}
+
+		let path = self
+			.keystore_params
+			.keystore_path
+			.as_ref()
+			.ok_or("Either --suri-file or --keystore-path is needed")?;
+		let address = self.public.as_ref().ok_or("--keystore-path needs --public")?;
+		let public = sr25519::Public::from_ss58check(address)
+			.map_err(|e| format!("Invalid public key `{}`: {:?}", address, e))?;
+		let key_type = KeyTypeId::try_from(self.key_type.as_str())
+			.map_err(|_| "The key type must be four characters long")?;
+		let keystore = LocalKeystore::open(path, password)
+			.map_err(|e| format!("Error opening the keystore: {}", e))?;
+		let key = CryptoTypePublicPair(sr25519::CRYPTO_ID, public.to_raw_vec());
+		let signature = payload
+			.using_encoded(|message| SyncCryptoStore::sign_with(&keystore, key_type, &key, message))
+			.map_err(|e| format!("Error signing: {}", e))?
+			.ok_or_else(|| format!("Key {} not found in the keystore", address))?;
+		let signature = sr25519::Signature::try_from(&signature[..])
+			.map_err(|_| "The keystore returned an invalid signature")?;
+		Ok((public, signature))
+	}
+}
+
+/// Read the secret URI from the file at `path`, or from standard input if it is `-`.
+fn read_suri(path: &Path) -> sc_cli::Result<String> {
+	let suri = if path.as_os_str() == "-" {
+		let mut suri = String::new();
+		io::stdin().read_to_string(&mut suri).map(|_| suri)
+	} else {
+		fs::read_to_string(path)
+	}
+	.map_err(|e| format!("Error reading the secret URI from `{}`: {}", path.display(), e))?;
+	Ok(suri.trim().to_string())
+}
@@ -0,0 +1,190 @@
+//! Helpers for driving the `node-template` binary from integration tests.
+
+// Every test binary compiles this module but uses only some of the helpers.
+#![allow(dead_code)]
+
+use local_testnet::RpcClient;
+use nix::{
+	sys::signal::{kill, Signal::SIGINT},
+	unistd::Pid,
+};
+use node_template_runtime::{
+	ChargeSponsoredTransactionPayment, Hash, Index, Runtime, RuntimeCall, SignedExtra,
+	SignedPayload, UncheckedExtrinsic, VERSION,
+};
+use sp_core::{crypto::Ss58Codec, Encode};
+use sp_keyring::Sr25519Keyring;
+use sp_runtime::{generic::Era, MultiAddress};
+use std::{
+	io::{BufRead, BufReader},
+	net::TcpListener,
+	path::Path,
+	process::{Child, Command, Stdio},
+	thread,
+	time::{Duration, Instant},
+};
+
+/// How long a dev node may take to start or to seal a block.
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Path of the node binary built for the current test run.
+pub fn node_binary() -> &'static str {
+	env!("CARGO_BIN_EXE_node-template")
+}
+
+/// Run a dev chain stored in `base_path` until it has imported `blocks` blocks, then stop it.
+pub fn run_dev_node_for_blocks(base_path: &Path, blocks: u32) {
+	run_dev_node_for_blocks_with_args(base_path, blocks, &[])
+}
+
+/// Like `run_dev_node_for_blocks`, passing `args` to the node as well.
+pub fn run_dev_node_for_blocks_with_args(base_path: &Path, blocks: u32, args: &[&str]) {
+	let mut node = Command::new(node_binary())
+		.args(["--dev", "--port", "0", "--rpc-port", "0", "--ws-port", "0", "--no-prometheus"])
+		.arg("--base-path")
+		.arg(base_path)
+		.args(args)
+		.stderr(Stdio::piped())
+		.spawn()
+		.expect("failed to start the node");
+
+	let imported = format!("Imported #{}", blocks);
+	let stderr = BufReader::new(node.stderr.take().expect("stderr is piped"));
+	let reached = stderr.lines().map_while(Result::ok).any(|line| line.contains(&imported));
+
+	kill(Pid::from_raw(node.id() as i32), SIGINT).expect("failed to stop the node");
+	let status = node.wait().expect("node was not running");
+	assert!(reached, "node exited before importing block #{}", blocks);
+	assert!(status.success(), "node exited with {}", status);
+}
+
+/// Export the latest state of the dev chain stored in `base_path` as a raw chain spec.
+pub fn export_state(base_path: &Path) -> Vec<u8> {
+	let output = Command::new(node_binary())
+		.args(["export-state", "--dev"])
+		.arg("--base-path")
+		.arg(base_path)
+		.output()
+		.expect("failed to run export-state");
+	assert!(
+		output.status.success(),
+		"export-state failed: {}",
+		String::from_utf8_lossy(&output.stderr)
+	);
+	output.stdout
+}
+
+/// A dev node sealing a block for every transaction, stopped when dropped.
+pub struct DevNode {
+	process: Child,
+	rpc: RpcClient,
+}
+
+impl DevNode {
+	/// Start a dev chain stored in `base_path` with instant sealing, passing `args` to the node
+	/// as well, and wait until its RPC server answers.
+	pub fn start(base_path: &Path, args: &[&str]) -> Self {
+		Self::start_with_sealing(base_path, "instant", args)
+	}
+
+	/// Like `start`, sealing blocks as `sealing` says.
+	pub fn start_with_sealing(base_path: &Path, sealing: &str, args: &[&str]) -> Self {
+		let rpc_port = TcpListener::bind("127.0.0.1:0")
+			.and_then(|listener| listener.local_addr())
+			.expect("no free port")
+			.port();
+		let process = Command::new(node_binary())
+			.args(["--dev", "--sealing", sealing, "--port", "0", "--ws-port", "0"])
+			.args(["--no-prometheus", "--rpc-port", &rpc_port.to_string()])
+			.arg("--base-path")
+			.arg(base_path)
+			.args(args)
+			.stdout(Stdio::null())
+			.stderr(Stdio::null())
+			.spawn()
+			.expect("failed to start the node");
+		let rpc = RpcClient::new(format!("http://127.0.0.1:{}", rpc_port));
+		let node = DevNode { process, rpc };
+		wait_until("the RPC server to start", || node.rpc.best_number().is_ok());
+		node
+	}
+
+	pub fn rpc(&self) -> &RpcClient {
+		&self.rpc
+	}
+
+	/// Sign `call` by `signer`, submit it and wait until it is sealed. Returns the number of the
+	/// block that includes it.
+	pub fn submit(&self, signer: Sr25519Keyring, call: RuntimeCall) -> u32 {
+		let account = signer.to_account_id().to_ss58check();
+		let nonce: Index = self
+			.rpc
+			.request("system_accountNextIndex", serde_json::json!([account]))
+			.expect("failed to read the nonce");
+		let genesis_hash = self.rpc.block_hash(0).unwrap().expect("the genesis block exists");
+		let best = self.rpc.best_number().unwrap();
+		let extrinsic = signed_extrinsic(signer, nonce, call, genesis_hash);
+		self.rpc.submit_extrinsic(extrinsic.encode().into()).expect("the extrinsic is valid");
+		wait_until("the extrinsic to be sealed", || {
+			self.rpc.best_number().map_or(false, |number| number > best)
+		});
+		best + 1
+	}
+}
+
+impl Drop for DevNode {
+	fn drop(&mut self) {
+		let _ = kill(Pid::from_raw(self.process.id() as i32), SIGINT);
+		let _ = self.process.wait();
+	}
+}
+
+/// An immortal extrinsic calling `call`, signed by `signer` with `nonce` for the chain with
+/// `genesis_hash`.
+pub fn signed_extrinsic(
+	signer: Sr25519Keyring,
+	nonce: Index,
+	call: RuntimeCall,
+	genesis_hash: Hash,
+) -> UncheckedExtrinsic {
+	let extra: SignedExtra = (
+		frame_system::CheckNonZeroSender::<Runtime>::new(),
+		frame_system::CheckSpecVersion::<Runtime>::new(),
+		frame_system::CheckTxVersion::<Runtime>::new(),
+		frame_system::CheckGenesis::<Runtime>::new(),
+		frame_system::CheckEra::<Runtime>::from(Era::Immortal),
+		frame_system::CheckNonce::<Runtime>::from(nonce),
+		frame_system::CheckWeight::<Runtime>::new(),
+		ChargeSponsoredTransactionPayment::from(0),
+	);
+	let payload = SignedPayload::from_raw(
+		call.clone(),
+		extra.clone(),
+		(
+			(),
+			VERSION.spec_version,
+			VERSION.transaction_version,
+			genesis_hash,
+			genesis_hash,
+			(),
+			(),
+			(),
+		),
+	);
+	let signature = payload.using_encoded(|message| signer.sign(message));
+	UncheckedExtrinsic::new_signed(
+		call,
+		MultiAddress::Id(signer.to_account_id()),
+		signature.into(),
+		extra,
+	)
+}
+
+/// Poll `reached` until it holds, for at most `TIMEOUT`.
+pub fn wait_until(what: &str, reached: impl Fn() -> bool) {
+	let deadline = Instant::now() + TIMEOUT;
+	while !reached() {
+		assert!(Instant::now() < deadline, "timed out waiting for {}", what);
+		thread::sleep(Duration::from_millis(100));
+	}
+}
@@ -0,0 +1,140 @@
+//! Pagination of the supply-chain event index, and the events a dev chain indexes.
+
+mod common;
+
+use node_template::event_index::{EventId, EventIndex, EventsPage, IndexedEvent};
+use node_template_runtime::{AccountId, BlockNumber, TemplateModuleCall};
+use sp_core::crypto::Ss58Codec;
+use sp_keyring::Sr25519Keyring;
+
+fn event(block: BlockNumber, index: u32, companies: Vec<AccountId>) -> IndexedEvent {
+	IndexedEvent {
+		id: EventId { block, index },
+		block_hash: Default::default(),
+		name: "ProductCreated".into(),
+		data: vec![2].into(),
+		companies,
+	}
+}
+
+#[test]
+fn events_by_company_are_paginated_in_order() {
+	let dir = tempfile::tempdir().expect("failed to create a directory");
+	let index = EventIndex::open(dir.path()).unwrap();
+	let alice = Sr25519Keyring::Alice.to_account_id();
+	let bob = Sr25519Keyring::Bob.to_account_id();
+	assert_eq!(index.last_indexed().unwrap(), None);
+
+	index
+		.insert_block(1, &[event(1, 2, vec![alice.clone()]), event(1, 3, vec![bob.clone()])])
+		.unwrap();
+	index.insert_block(2, &[]).unwrap();
+	index
+		.insert_block(
+			3,
+			&[event(3, 0, vec![alice.clone(), bob.clone()]), event(3, 1, vec![alice.clone()])],
+		)
+		.unwrap();
+	assert_eq!(index.last_indexed().unwrap(), Some(3));
+
+	let first = index.events_by_company(&alice, None, 2).unwrap();
+	let ids: Vec<_> = first.events.iter().map(|e| e.id).collect();
+	assert_eq!(ids, vec![EventId { block: 1, index: 2 }, EventId { block: 3, index: 0 }]);
+	assert_eq!(first.next, Some(EventId { block: 3, index: 0 }));
+
+	let second = index.events_by_company(&alice, first.next, 2).unwrap();
+	assert_eq!(second.events, vec![event(3, 1, vec![alice.clone()])]);
+	assert_eq!(second.next, None);
+
+	let bobs = index.events_by_company(&bob, None, 10).unwrap();
+	assert_eq!(bobs.events.len(), 2);
+	assert_eq!(bobs.next, None);
+
+	let charlie = Sr25519Keyring::Charlie.to_account_id();
+	assert!(index.events_by_company(&charlie, None, 10).unwrap().events.is_empty());
+}
+
+#[test]
+fn reindexing_a_block_replaces_its_events() {
+	let dir = tempfile::tempdir().expect("failed to create a directory");
+	let index = EventIndex::open(dir.path()).unwrap();
+	let alice = Sr25519Keyring::Alice.to_account_id();
+	let bob = Sr25519Keyring::Bob.to_account_id();
+
+	index
+		.insert_block(1, &[event(1, 0, vec![alice.clone()]), event(1, 1, vec![bob.clone()])])
+		.unwrap();
+	index.insert_block(1, &[event(1, 0, vec![bob.clone()])]).unwrap();
+
+	assert!(index.events_by_company(&alice, None, 10).unwrap().events.is_empty());
+	assert_eq!(
+		index.events_by_company(&bob, None, 10).unwrap().events,
+		vec![event(1, 0, vec![bob.clone()])]
+	);
+}
+
+#[test]
+fn undecodable_blocks_are_skipped_and_recorded() {
+	let dir = tempfile::tempdir().expect("failed to create a directory");
+	let index = EventIndex::open(dir.path()).unwrap();
+	let alice = Sr25519Keyring::Alice.to_account_id();
+
+	index.insert_block(1, &[event(1, 0, vec![alice.clone()])]).unwrap();
+	index.skip_block(2).unwrap();
+	index.skip_block(3).unwrap();
+	assert_eq!(index.last_indexed().unwrap(), Some(3));
+	assert_eq!(index.skipped_blocks().unwrap(), vec![2, 3]);
+
+	// A block indexed again after all is no longer skipped.
+	index.insert_block(3, &[event(3, 0, vec![alice.clone()])]).unwrap();
+	assert_eq!(index.skipped_blocks().unwrap(), vec![2]);
+	assert_eq!(index.events_by_company(&alice, None, 10).unwrap().events.len(), 2);
+}
+
+#[test]
+fn ious_are_indexed_under_both_parties() {
+	let base_path = tempfile::tempdir().expect("failed to create a base path");
+	let node = common::DevNode::start(base_path.path(), &["--event-index"]);
+	let register = |id| TemplateModuleCall::register_company {
+		name: b"company".to_vec(),
+		id,
+		about_me: vec![],
+	};
+	node.submit(Sr25519Keyring::Alice, register(1).into());
+	node.submit(Sr25519Keyring::Bob, register(2).into());
+	// Alice holds an IOU that Bob's company owes hers, and Bob confirms it.
+	let create_iou =
+		TemplateModuleCall::create_iou { id: 1, debtor: 2, creditor: 1, amount: 100, asset_id: 0 };
+	node.submit(Sr25519Keyring::Alice, create_iou.into());
+	let alice = Sr25519Keyring::Alice.to_account_id();
+	let confirm_iou = TemplateModuleCall::confirm_iou { iou: alice.clone() };
+	let number = node.submit(Sr25519Keyring::Bob, confirm_iou.into());
+
+	common::wait_until("the blocks to be indexed", || {
+		node.rpc()
+			.request::<Option<BlockNumber>>("supplychain_lastIndexedBlock", serde_json::json!([]))
+			.map_or(false, |last| last >= Some(number))
+	});
+	let events_of = |keyring: Sr25519Keyring| -> Vec<String> {
+		let page: EventsPage = node
+			.rpc()
+			.request(
+				"supplychain_eventsByCompany",
+				serde_json::json!([keyring.to_account_id().to_ss58check(), null, 10]),
+			)
+			.expect("failed to read the events");
+		page.events.into_iter().map(|event| event.name).collect()
+	};
+	assert_eq!(events_of(Sr25519Keyring::Alice), ["CompanyCreated", "IOUCreated", "IOUConfirmed"]);
+	assert_eq!(events_of(Sr25519Keyring::Bob), ["CompanyCreated", "IOUCreated", "IOUConfirmed"]);
+
+	let error = node
+		.rpc()
+		.request::<EventsPage>(
+			"supplychain_eventsByCompany",
+			serde_json::json!([alice.to_ss58check(), null, 0]),
+		)
+		.err()
+		.expect("a limit of 0 was accepted");
+	assert!(format!("{:?}", error).contains("limit must be at least 1"), "{:?}", error);
+}
@@ -0,0 +1,32 @@
+//! Runs `export-checkpoint` against a dev chain that finalized a few blocks.
+
+mod common;
+
+use std::process::Command;
+
+#[test]
+fn exports_finalized_checkpoint() {
+	let base_path = tempfile::tempdir().expect("failed to create a base path");
+	common::run_dev_node_for_blocks(base_path.path(), 5);
+
+	let output = Command::new(common::node_binary())
+		.args(["export-checkpoint", "--dev"])
+		.arg("--base-path")
+		.arg(base_path.path())
+		.output()
+		.expect("failed to run export-checkpoint");
+	assert!(
+		output.status.success(),
+		"export-checkpoint failed: {}",
+		String::from_utf8_lossy(&output.stderr)
+	);
+
+	let checkpoint: serde_json::Value =
+		serde_json::from_slice(&output.stdout).expect("export-checkpoint writes JSON");
+	assert_eq!(checkpoint["chain"], "dev");
+	assert!(checkpoint["header"]["number"].is_string());
+	assert!(checkpoint["justification"].as_str().unwrap().starts_with("0x"));
+	assert_eq!(checkpoint["signers"]["setId"], 0);
+	assert_eq!(checkpoint["signers"]["authorities"].as_array().unwrap().len(), 1);
+	assert_eq!(checkpoint["authorities"], checkpoint["signers"]);
+}
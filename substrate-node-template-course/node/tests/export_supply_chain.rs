@@ -0,0 +1,77 @@
+//! Runs `export-supply-chain` against a dev chain with a registered company.
+
+mod common;
+
+use node_template_runtime::TemplateModuleCall;
+use sp_core::crypto::Ss58Codec;
+use sp_keyring::Sr25519Keyring;
+use std::process::Command;
+
+fn export(base_path: &std::path::Path, args: &[&str]) -> Vec<u8> {
+	let output = Command::new(common::node_binary())
+		.args(["export-supply-chain", "--dev"])
+		.arg("--base-path")
+		.arg(base_path)
+		.args(args)
+		.output()
+		.expect("failed to run export-supply-chain");
+	assert!(
+		output.status.success(),
+		"export-supply-chain failed: {}",
+		String::from_utf8_lossy(&output.stderr)
+	);
+	output.stdout
+}
+
+#[test]
+fn exports_json_and_csv() {
+	let base_path = tempfile::tempdir().expect("failed to create a base path");
+	let number = {
+		let node = common::DevNode::start(base_path.path(), &[]);
+		let call = TemplateModuleCall::register_company {
+			name: b"Acme, \"Widgets\" Ltd".to_vec(),
+			id: 42,
+			about_me: b"Makes widgets\nand gadgets".to_vec(),
+		};
+		node.submit(Sr25519Keyring::Alice, call.into())
+	};
+	let alice = Sr25519Keyring::Alice.to_account_id().to_ss58check();
+
+	let json: serde_json::Value =
+		serde_json::from_slice(&export(base_path.path(), &[&number.to_string()]))
+			.expect("export-supply-chain writes JSON");
+	assert_eq!(json["block_number"], number);
+	assert_eq!(
+		json["companies"],
+		serde_json::json!([{
+			"account": alice,
+			"id": 42,
+			"name": "Acme, \"Widgets\" Ltd",
+			"about_me": "Makes widgets\nand gadgets",
+			"verified": false,
+		}])
+	);
+	for table in ["products", "contracts", "ious"] {
+		assert_eq!(json[table], serde_json::json!([]), "unexpected {}", table);
+	}
+
+	// The block before the registration has no companies yet.
+	let json: serde_json::Value =
+		serde_json::from_slice(&export(base_path.path(), &[&(number - 1).to_string()]))
+			.expect("export-supply-chain writes JSON");
+	assert_eq!(json["companies"], serde_json::json!([]));
+
+	let csv_dir = base_path.path().join("csv");
+	export(base_path.path(), &["--format", "csv", "--output", csv_dir.to_str().unwrap()]);
+	let companies = std::fs::read_to_string(csv_dir.join("companies.csv")).unwrap();
+	assert_eq!(
+		companies,
+		format!(
+			"account,id,name,about_me,verified\n\
+			 {},42,\"Acme, \"\"Widgets\"\" Ltd\",\"Makes widgets\nand gadgets\",false\n",
+			alice
+		)
+	);
+	let products = std::fs::read_to_string(csv_dir.join("products.csv")).unwrap();
+	assert_eq!(products, "account,id,name,description,owner,previous_owners\n");
+}
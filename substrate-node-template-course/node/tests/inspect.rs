@@ -0,0 +1,85 @@
+//! Runs `inspect` against a dev chain that produced a few blocks.
+
+mod common;
+
+use node_template_runtime::TemplateModuleCall;
+use sp_core::crypto::Ss58Codec;
+use sp_keyring::Sr25519Keyring;
+use std::process::Command;
+
+fn inspect(base_path: &std::path::Path, args: &[&str]) -> String {
+	let output = Command::new(common::node_binary())
+		.args(["inspect", "--dev"])
+		.arg("--base-path")
+		.arg(base_path)
+		.args(args)
+		.output()
+		.expect("failed to run inspect");
+	assert!(output.status.success(), "inspect failed: {}", String::from_utf8_lossy(&output.stderr));
+	String::from_utf8(output.stdout).expect("inspect writes UTF-8")
+}
+
+#[test]
+fn decodes_blocks_and_extrinsics() {
+	let base_path = tempfile::tempdir().expect("failed to create a base path");
+	common::run_dev_node_for_blocks(base_path.path(), 2);
+
+	let block: serde_json::Value =
+		serde_json::from_str(&inspect(base_path.path(), &["1", "--json"]))
+			.expect("inspect --json writes JSON");
+	assert_eq!(block["number"], 1);
+	let timestamp = &block["extrinsics"][0];
+	assert_eq!(timestamp["pallet"], "Timestamp");
+	assert_eq!(timestamp["call"], "set");
+	assert!(timestamp["args"]["now"].is_u64());
+	assert_eq!(timestamp["events"][0]["pallet"], "System");
+	assert_eq!(timestamp["events"][0]["name"], "ExtrinsicSuccess");
+	assert!(block["storageChanges"].is_array());
+
+	let text = inspect(base_path.path(), &["1:0"]);
+	assert!(text.contains("Extrinsic 0: Timestamp.set"), "unexpected output: {}", text);
+	assert!(!text.contains("Storage changes"), "unexpected output: {}", text);
+}
+
+#[test]
+fn decodes_the_storage_changes_of_register_company() {
+	let base_path = tempfile::tempdir().expect("failed to create a base path");
+	let number = {
+		let node = common::DevNode::start(base_path.path(), &[]);
+		let call = TemplateModuleCall::register_company {
+			name: b"Acme".to_vec(),
+			id: 42,
+			about_me: b"Widgets".to_vec(),
+		};
+		node.submit(Sr25519Keyring::Alice, call.into())
+	};
+
+	let block: serde_json::Value =
+		serde_json::from_str(&inspect(base_path.path(), &[&number.to_string(), "--json"]))
+			.expect("inspect --json writes JSON");
+	let alice = Sr25519Keyring::Alice.to_account_id().to_ss58check();
+	let extrinsic = &block["extrinsics"][1];
+	assert_eq!(extrinsic["pallet"], "TemplateModule");
+	assert_eq!(extrinsic["call"], "register_company");
+	assert_eq!(extrinsic["signer"]["Id"], alice.as_str());
+	assert_eq!(extrinsic["args"]["name"], "Acme");
+
+	let changes = block["storageChanges"].as_array().expect("storage changes are listed");
+	let change = |item: &str| {
+		changes
+			.iter()
+			.find(|change| change["item"] == item)
+			.unwrap_or_else(|| panic!("no change of {} in {:?}", item, changes))
+	};
+	let company = change("AccountToCompany");
+	assert_eq!(company["key"], alice.as_str());
+	assert!(company["before"].is_null());
+	assert_eq!(
+		company["after"],
+		serde_json::json!({ "id": 42, "name": "Acme", "about_me": "Widgets", "verified": false })
+	);
+	let id = change("CompanyIdToAccount");
+	assert_eq!(id["key"], 42);
+	assert!(id["before"].is_null());
+	assert_eq!(id["after"], alice.as_str());
+}
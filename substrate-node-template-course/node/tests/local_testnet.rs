@@ -0,0 +1,66 @@
+//! Runs a local network of Alice, Bob and Charlie and checks that they finalize the same chain
+//! and share the transactions submitted to one of them.
+
+mod common;
+
+use local_testnet::Network;
+use node_template_runtime::{
+	pallet_template::{AccountToCompany, Company},
+	Runtime, TemplateModuleCall,
+};
+use serde_json::json;
+use sp_core::{hexdisplay::HexDisplay, Bytes, Decode, Encode, H256};
+use sp_keyring::Sr25519Keyring;
+use std::{
+	thread,
+	time::{Duration, Instant},
+};
+
+const TIMEOUT: Duration = Duration::from_secs(120);
+
+#[test]
+fn nodes_finalize_the_same_blocks() {
+	let network =
+		Network::start(env!("CARGO_BIN_EXE_node-template"), 3).expect("failed to start network");
+	network.wait_for_peers(TIMEOUT).unwrap();
+	network.wait_for_finalized(3, TIMEOUT).unwrap();
+
+	let hashes: Vec<_> =
+		network.nodes().iter().map(|node| node.rpc().block_hash(3).unwrap()).collect();
+	assert!(hashes[0].is_some());
+	assert!(hashes.iter().all(|hash| *hash == hashes[0]), "nodes disagree on block #3");
+}
+
+#[test]
+fn extrinsics_submitted_to_one_node_reach_the_others() {
+	let network =
+		Network::start(env!("CARGO_BIN_EXE_node-template"), 3).expect("failed to start network");
+	network.wait_for_peers(TIMEOUT).unwrap();
+	let (alice, charlie) = (network.nodes()[0].rpc(), network.nodes()[2].rpc());
+
+	let genesis_hash = alice.block_hash(0).unwrap().expect("the genesis block exists");
+	let call = TemplateModuleCall::register_company {
+		name: b"Acme".to_vec(),
+		id: 42,
+		about_me: Vec::new(),
+	};
+	let extrinsic = common::signed_extrinsic(Sr25519Keyring::Alice, 0, call.into(), genesis_hash);
+	alice.submit_extrinsic(extrinsic.encode().into()).expect("the extrinsic is valid");
+
+	// Charlie does not author blocks, so it only learns of the company from the others' blocks.
+	let key = AccountToCompany::<Runtime>::hashed_key_for(Sr25519Keyring::Alice.to_account_id());
+	let key = format!("0x{}", HexDisplay::from(&key));
+	let deadline = Instant::now() + TIMEOUT;
+	let company = loop {
+		let finalized: H256 = charlie.finalized_head().unwrap();
+		let stored: Option<Bytes> =
+			charlie.request("state_getStorage", json!([key, finalized])).unwrap();
+		if let Some(stored) = stored {
+			break Company::decode(&mut &stored[..]).expect("a company")
+		}
+		assert!(Instant::now() < deadline, "Charlie never finalized the company");
+		thread::sleep(Duration::from_millis(500));
+	};
+	assert_eq!(company.id, 42);
+	assert_eq!(company.name, b"Acme".to_vec());
+}
@@ -0,0 +1,76 @@
+//! Runs a dev chain that builds its blocks with manual seal instead of Aura.
+
+mod common;
+
+use sp_core::H256;
+use std::{thread, time::Duration};
+
+#[test]
+fn dev_chain_produces_blocks_with_interval_sealing() {
+	let base_path = tempfile::tempdir().expect("failed to create a base path");
+	common::run_dev_node_for_blocks_with_args(base_path.path(), 3, &["--sealing", "interval=500"]);
+
+	// A restarted node continues the chain, with timestamps after those already stored.
+	common::run_dev_node_for_blocks_with_args(base_path.path(), 5, &["--sealing", "interval=500"]);
+}
+
+#[test]
+fn dev_chain_seals_and_finalizes_blocks_on_request_with_manual_sealing() {
+	let base_path = tempfile::tempdir().expect("failed to create a base path");
+	let node = common::DevNode::start_with_sealing(base_path.path(), "manual", &[]);
+	let rpc = node.rpc();
+	let genesis = rpc.block_hash(0).unwrap().expect("the genesis block exists");
+
+	// Nothing is sealed until it is asked for.
+	thread::sleep(Duration::from_secs(1));
+	assert_eq!(rpc.best_number().unwrap(), 0);
+
+	let created: serde_json::Value = rpc
+		.request("engine_createBlock", serde_json::json!([true, false, null]))
+		.expect("failed to create a block");
+	let hash: H256 =
+		serde_json::from_value(created["hash"].clone()).expect("the created block has a hash");
+	assert_eq!(rpc.best_number().unwrap(), 1);
+	assert_eq!(rpc.block_hash(1).unwrap(), Some(hash));
+	assert_eq!(rpc.finalized_head().unwrap(), genesis);
+
+	let finalized: bool = rpc
+		.request("engine_finalizeBlock", serde_json::json!([hash, null]))
+		.expect("failed to finalize the block");
+	assert!(finalized);
+	assert_eq!(rpc.finalized_head().unwrap(), hash);
+}
+
+#[test]
+fn sealing_requires_a_dev_chain_without_a_remote_keystore() {
+	let base_path = tempfile::tempdir().expect("failed to create a base path");
+	let run = |args: &[&str]| {
+		let output = std::process::Command::new(common::node_binary())
+			.arg("--base-path")
+			.arg(base_path.path())
+			.args(["--sealing", "instant", "--port", "0", "--rpc-port", "0", "--ws-port", "0"])
+			.args(args)
+			.output()
+			.expect("failed to run the node");
+		assert!(!output.status.success(), "node accepted {:?}", args);
+		String::from_utf8_lossy(&output.stderr).into_owned()
+	};
+
+	let stderr = run(&["--chain", "local"]);
+	assert!(stderr.contains("--dev"), "unexpected error: {}", stderr);
+
+	let token_file = base_path.path().join("token");
+	std::fs::write(&token_file, "0123456789abcdef0123456789abcdef").unwrap();
+	let stderr = run(&[
+		"--dev",
+		"--keystore-uri",
+		"http://127.0.0.1:1",
+		"--keystore-token-file",
+		token_file.to_str().expect("temporary paths are UTF-8"),
+	]);
+	assert!(
+		stderr.contains("Remote keystores are not supported with `--sealing`"),
+		"unexpected error: {}",
+		stderr
+	);
+}
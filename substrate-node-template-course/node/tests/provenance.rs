@@ -0,0 +1,104 @@
+//! The provenance verifier computes the same storage keys as the runtime, and accepts the proofs
+//! and Grandpa justifications of a local network only for the block and authorities they are for.
+
+mod common;
+
+use local_testnet::Network;
+use node_template_runtime::{pallet_template, Runtime, TemplateModuleCall};
+use provenance_verifier::{
+	company_account_key, product_key, verify, verify_finality, Error, Header, ProvenanceProof,
+};
+use sc_finality_grandpa::FinalityProof;
+use sp_core::{crypto::Ss58Codec, Decode, Encode, H256};
+use sp_finality_grandpa::AuthorityId;
+use sp_keyring::{Ed25519Keyring, Sr25519Keyring};
+use std::{
+	thread,
+	time::{Duration, Instant},
+};
+
+const TIMEOUT: Duration = Duration::from_secs(120);
+
+#[test]
+fn verifier_keys_match_the_runtime() {
+	let account = Sr25519Keyring::Alice.to_account_id();
+	assert_eq!(
+		product_key(&account),
+		pallet_template::AccountToProduct::<Runtime>::hashed_key_for(&account)
+	);
+	assert_eq!(
+		company_account_key(42),
+		pallet_template::CompanyIdToAccount::<Runtime>::hashed_key_for(42)
+	);
+}
+
+#[test]
+fn verifier_checks_grandpa_justifications_of_a_local_network() {
+	let network =
+		Network::start(env!("CARGO_BIN_EXE_node-template"), 2).expect("failed to start network");
+	network.wait_for_peers(TIMEOUT).unwrap();
+	let rpc = network.nodes()[0].rpc();
+
+	let genesis_hash = rpc.block_hash(0).unwrap().expect("the genesis block exists");
+	let call = TemplateModuleCall::create_product {
+		id: 7,
+		name: b"Steel coil".to_vec(),
+		description: Vec::new(),
+		owner: 1,
+		previous_owners: Vec::new(),
+	};
+	let extrinsic = common::signed_extrinsic(Sr25519Keyring::Alice, 0, call.into(), genesis_hash);
+	rpc.submit_extrinsic(extrinsic.encode().into()).expect("the extrinsic is valid");
+
+	// The node only proves finalized blocks, and has a justification once a round completed.
+	let alice = Sr25519Keyring::Alice.to_account_id().to_ss58check();
+	let deadline = Instant::now() + TIMEOUT;
+	let proof = loop {
+		let proof: Option<ProvenanceProof> = rpc
+			.request("supplychain_productProvenance", serde_json::json!([alice, null]))
+			.ok();
+		if let Some(proof) = proof.filter(|proof| proof.finality_proof.is_some()) {
+			break proof
+		}
+		assert!(Instant::now() < deadline, "the product was never finalized with a proof");
+		thread::sleep(Duration::from_millis(500));
+	};
+	let header: Header = rpc
+		.request("chain_getHeader", serde_json::json!([proof.block_hash]))
+		.expect("the proven block exists");
+	assert_eq!(header.hash(), proof.block_hash);
+	assert_eq!(verify(&proof, &header).expect("valid storage proof").product.id, 7);
+
+	// The genesis authority set of the local chain.
+	let authorities: Vec<AuthorityId> = [Ed25519Keyring::Alice, Ed25519Keyring::Bob]
+		.iter()
+		.map(|key| key.public().into())
+		.collect();
+	verify_finality(&proof, &header, 0, &authorities).expect("Alice and Bob finalized the block");
+
+	let rejected = |proof: &ProvenanceProof, header: &Header, set_id, authorities: &[AuthorityId]| {
+		matches!(
+			verify_finality(proof, header, set_id, authorities),
+			Err(Error::InvalidFinalityProof(_))
+		)
+	};
+
+	let mut tampered_header = header.clone();
+	tampered_header.state_root = H256::repeat_byte(1);
+	assert!(rejected(&proof, &tampered_header, 0, &authorities));
+
+	// The justification starts with its round, which every precommit signature covers.
+	let encoded = proof.finality_proof.as_ref().expect("checked above");
+	let mut finality = FinalityProof::<Header>::decode(&mut &encoded[..]).unwrap();
+	finality.justification[0] ^= 1;
+	let tampered_proof =
+		ProvenanceProof { finality_proof: Some(finality.encode().into()), ..proof.clone() };
+	assert!(rejected(&tampered_proof, &header, 0, &authorities));
+
+	assert!(rejected(&proof, &header, 1, &authorities));
+	let others: Vec<AuthorityId> = [Ed25519Keyring::Charlie, Ed25519Keyring::Dave]
+		.iter()
+		.map(|key| key.public().into())
+		.collect();
+	assert!(rejected(&proof, &header, 0, &others));
+}
@@ -0,0 +1,107 @@
+//! Runs a dev chain whose Aura and Grandpa keys are held by the `remote-signer` binary.
+
+mod common;
+
+use node_template::remote_keystore::{protocol::MAX_REQUEST_SIZE, RemoteKeystore};
+use sp_core::{
+	crypto::{ByteArray, CryptoTypePublicPair},
+	sr25519, Pair,
+};
+use sp_keyring::{Ed25519Keyring, Sr25519Keyring};
+use sp_keystore::SyncCryptoStore;
+use sp_runtime::{
+	key_types::{AURA, GRANDPA},
+	KeyTypeId,
+};
+use std::{
+	io::{BufRead, BufReader},
+	path::PathBuf,
+	process::{Child, Command, Stdio},
+};
+use tempfile::TempDir;
+
+const TOKEN: &str = "correct horse battery staple";
+
+/// A running `remote-signer`, stopped when dropped.
+struct Signer {
+	process: Child,
+	url: String,
+	token_file: PathBuf,
+	_dir: TempDir,
+}
+
+impl Signer {
+	fn start() -> Self {
+		let dir = tempfile::tempdir().expect("failed to create a token directory");
+		let token_file = dir.path().join("token");
+		std::fs::write(&token_file, format!("{}\n", TOKEN)).expect("failed to write the token");
+		let mut process = Command::new(env!("CARGO_BIN_EXE_remote-signer"))
+			.args(["--listen", "127.0.0.1:0", "--token-file"])
+			.arg(&token_file)
+			.stdout(Stdio::piped())
+			.spawn()
+			.expect("failed to start the signer");
+		let mut line = String::new();
+		BufReader::new(process.stdout.take().expect("stdout is piped"))
+			.read_line(&mut line)
+			.expect("signer prints its address");
+		let address = line.trim().strip_prefix("Listening on ").expect("unexpected signer output");
+		Signer { url: format!("http://{}", address), process, token_file, _dir: dir }
+	}
+}
+
+impl Drop for Signer {
+	fn drop(&mut self) {
+		let _ = self.process.kill();
+	}
+}
+
+#[test]
+fn dev_chain_produces_blocks_with_remote_keys() {
+	let signer = Signer::start();
+	let wrong_token = RemoteKeystore::open(&signer.url, "not the shared secret".into());
+	assert!(wrong_token.err().expect("the token is checked").contains("401"));
+	let keystore = RemoteKeystore::open(&signer.url, TOKEN.into()).expect("signer is reachable");
+
+	let aura = SyncCryptoStore::sr25519_generate_new(&keystore, AURA, Some("//Alice")).unwrap();
+	let grandpa =
+		SyncCryptoStore::ed25519_generate_new(&keystore, GRANDPA, Some("//Alice")).unwrap();
+	assert_eq!(aura, Sr25519Keyring::Alice.public());
+	assert_eq!(grandpa, Ed25519Keyring::Alice.public());
+	assert!(SyncCryptoStore::has_keys(
+		&keystore,
+		&[(aura.to_raw_vec(), AURA), (grandpa.to_raw_vec(), GRANDPA)]
+	));
+	let bob = Sr25519Keyring::Bob.public().to_raw_vec();
+	assert!(!SyncCryptoStore::has_keys(&keystore, &[(aura.to_raw_vec(), AURA), (bob, AURA)]));
+
+	let key = CryptoTypePublicPair(sr25519::CRYPTO_ID, aura.to_raw_vec());
+	let signature = SyncCryptoStore::sign_with(&keystore, AURA, &key, b"supply chain")
+		.unwrap()
+		.expect("signer holds the aura key");
+	let signature = sr25519::Signature::from_slice(&signature).expect("64 byte signature");
+	assert!(sr25519::Pair::verify(&signature, b"supply chain", &aura));
+
+	// The signer only serves the consensus key types.
+	let account = KeyTypeId(*b"acco");
+	assert!(SyncCryptoStore::sr25519_generate_new(&keystore, account, Some("//Bob")).is_err());
+	assert!(SyncCryptoStore::sign_with(&keystore, account, &key, b"supply chain").is_err());
+
+	// Oversized requests are refused before they are read.
+	let oversized = ureq::post(&format!("{}/sign", signer.url))
+		.set("Authorization", &format!("Bearer {}", TOKEN))
+		.send_bytes(&vec![b' '; MAX_REQUEST_SIZE + 1]);
+	assert!(matches!(oversized, Err(ureq::Error::Status(413, _))));
+
+	let base_path = tempfile::tempdir().expect("failed to create a base path");
+	common::run_dev_node_for_blocks_with_args(
+		base_path.path(),
+		3,
+		&[
+			"--keystore-uri",
+			&signer.url,
+			"--keystore-token-file",
+			signer.token_file.to_str().expect("temporary paths are UTF-8"),
+		],
+	);
+}
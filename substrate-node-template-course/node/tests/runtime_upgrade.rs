@@ -0,0 +1,80 @@
+//! Runs the runtime upgrade, including the `try-runtime` pre- and post-upgrade checks of every
+//! pallet, against state exported from a dev chain with `export-state` and rolled back to the
+//! storage layout of the supply-chain pallet before its migration.
+//!
+//! `cargo test -p node-template --features try-runtime --test runtime_upgrade`
+
+#![cfg(feature = "try-runtime")]
+
+mod common;
+
+use frame_support::traits::{Get, StorageVersion};
+use node_template_runtime::{
+	pallet_template, Executive, GenesisConfig, LegacyContractAsset, Runtime, TemplateModule,
+};
+use sc_service::GenericChainSpec;
+use sp_keyring::AccountKeyring;
+use sp_runtime::BuildStorage;
+
+/// State of a dev chain that produced a few blocks.
+fn exported_dev_state() -> sp_io::TestExternalities {
+	let base_path = tempfile::tempdir().expect("failed to create a base path");
+	common::run_dev_node_for_blocks(base_path.path(), 3);
+	let spec = common::export_state(base_path.path());
+
+	let spec = GenericChainSpec::<GenesisConfig>::from_json_bytes(spec)
+		.expect("export-state writes a valid chain spec");
+	sp_io::TestExternalities::new(spec.build_storage().expect("chain spec has raw storage"))
+}
+
+#[test]
+fn runtime_upgrade_migrates_legacy_supply_chain_state() {
+	exported_dev_state().execute_with(|| {
+		let alice = AccountKeyring::Alice.to_account_id();
+		let bob = AccountKeyring::Bob.to_account_id();
+		// The migration only runs on version 0 storage; dev genesis is already at version 1.
+		StorageVersion::new(0).put::<TemplateModule>();
+		// Companies as stored before companies could be verified, both with the same id.
+		for account in [&alice, &bob] {
+			frame_support::storage::unhashed::put(
+				&pallet_template::AccountToCompany::<Runtime>::hashed_key_for(account),
+				&(42u64, b"Legacy Logistics".to_vec(), Vec::<u8>::new()),
+			);
+		}
+		// A supply contract and an IOU in the version 0 layout, without asset or status flags:
+		// (id, seller_id, buyer_id, products, delivered, iou, contract_value, contract_fulfilled)
+		frame_support::storage::unhashed::put(
+			&pallet_template::AccountToSupplyContract::<Runtime>::hashed_key_for(&alice),
+			&(5u64, 42u64, 43u64, vec![1u64, 2], true, 9u64, 300u64, false),
+		);
+		// (id, debtor, creditor, amount)
+		frame_support::storage::unhashed::put(
+			&pallet_template::AccountToIOU::<Runtime>::hashed_key_for(&bob),
+			&(9u64, 43u64, 42u64, 120u64),
+		);
+
+		Executive::try_runtime_upgrade().expect("runtime upgrade checks failed");
+
+		let (owner, displaced) =
+			if alice < bob { (alice.clone(), bob.clone()) } else { (bob.clone(), alice.clone()) };
+		assert_eq!(TemplateModule::company_info(&owner).name, b"Legacy Logistics".to_vec());
+		assert_eq!(TemplateModule::company_account(42), Some(owner));
+		assert_eq!(TemplateModule::displaced_company(&displaced).map(|c| c.id), Some(42));
+
+		let contract = TemplateModule::supply_contract_info(&alice);
+		assert_eq!(
+			(contract.id, contract.seller_id, contract.buyer_id, contract.products),
+			(5, 42, 43, vec![1, 2])
+		);
+		assert_eq!((contract.delivered, contract.contract_fulfilled), (true, false));
+		assert_eq!((contract.iou, contract.contract_value), (9, 300));
+		// Legacy values are denominated in asset 0 and were never paid into escrow.
+		assert_eq!(LegacyContractAsset::get(), 0);
+		assert_eq!((contract.asset_id, contract.escrowed), (LegacyContractAsset::get(), false));
+
+		let iou = TemplateModule::iou_info(&bob);
+		assert_eq!((iou.id, iou.debtor, iou.creditor, iou.amount), (9, 43, 42, 120));
+		assert_eq!((iou.asset_id, iou.delinquent), (LegacyContractAsset::get(), false));
+		assert_eq!(StorageVersion::get::<TemplateModule>(), 1);
+	});
+}
@@ -0,0 +1,172 @@
+//! Signs a supply-chain transaction with `sign-transaction` and checks the extrinsic it prints.
+
+mod common;
+
+use node_template_runtime::{
+	ChargeSponsoredTransactionPayment, Hash, Runtime, RuntimeCall, SignedExtra, SignedPayload,
+	TemplateModuleCall, UncheckedExtrinsic, VERSION,
+};
+use sp_core::{bytes::from_hex, Decode, Encode};
+use sp_keyring::Sr25519Keyring;
+use sp_runtime::{generic::Era, traits::Verify, MultiAddress};
+use std::{
+	io::Write,
+	process::{Command, Output, Stdio},
+};
+
+/// Run `sign-transaction` with `args`, writing `stdin` to its standard input.
+fn sign_transaction(args: &[&str], stdin: &str) -> Output {
+	let mut process = Command::new(common::node_binary())
+		.arg("sign-transaction")
+		.args(args)
+		.stdin(Stdio::piped())
+		.stdout(Stdio::piped())
+		.stderr(Stdio::piped())
+		.spawn()
+		.expect("failed to run sign-transaction");
+	// The command may exit before reading, e.g. on invalid arguments.
+	let _ = process.stdin.take().expect("stdin is piped").write_all(stdin.as_bytes());
+	process.wait_with_output().expect("failed to run sign-transaction")
+}
+
+fn decode(output: Output) -> UncheckedExtrinsic {
+	assert!(
+		output.status.success(),
+		"sign-transaction failed: {}",
+		String::from_utf8_lossy(&output.stderr)
+	);
+	let hex = String::from_utf8(output.stdout).unwrap();
+	UncheckedExtrinsic::decode(&mut &from_hex(hex.trim()).unwrap()[..]).expect("an extrinsic")
+}
+
+#[test]
+fn signs_register_company() {
+	let genesis_hash = Hash::repeat_byte(7);
+	let dir = tempfile::tempdir().expect("failed to create a directory");
+	let suri_file = dir.path().join("suri");
+	std::fs::write(&suri_file, "//Alice\n").expect("failed to write the secret URI");
+	let output = sign_transaction(
+		&[
+			"--nonce",
+			"3",
+			"--immortal",
+			"--suri-file",
+			suri_file.to_str().expect("temporary paths are UTF-8"),
+			"--genesis-hash",
+			&format!("{:?}", genesis_hash),
+			"register-company",
+			"--name",
+			"Acme",
+			"--id",
+			"42",
+		],
+		"",
+	);
+	let extrinsic = decode(output);
+	let call = RuntimeCall::from(TemplateModuleCall::register_company {
+		name: b"Acme".to_vec(),
+		id: 42,
+		about_me: Vec::new(),
+	});
+	assert_eq!(extrinsic.function, call);
+
+	let (address, signature, extra) = extrinsic.signature.expect("the extrinsic is signed");
+	let alice = Sr25519Keyring::Alice.to_account_id();
+	assert_eq!(address, MultiAddress::Id(alice.clone()));
+	let expected: SignedExtra = (
+		frame_system::CheckNonZeroSender::<Runtime>::new(),
+		frame_system::CheckSpecVersion::<Runtime>::new(),
+		frame_system::CheckTxVersion::<Runtime>::new(),
+		frame_system::CheckGenesis::<Runtime>::new(),
+		frame_system::CheckEra::<Runtime>::from(Era::Immortal),
+		frame_system::CheckNonce::<Runtime>::from(3),
+		frame_system::CheckWeight::<Runtime>::new(),
+		ChargeSponsoredTransactionPayment::from(0),
+	);
+	assert_eq!(extra.encode(), expected.encode());
+
+	let payload = SignedPayload::from_raw(
+		call,
+		extra,
+		(
+			(),
+			VERSION.spec_version,
+			VERSION.transaction_version,
+			genesis_hash,
+			genesis_hash,
+			(),
+			(),
+			(),
+		),
+	);
+	assert!(payload.using_encoded(|message| signature.verify(message, &alice)));
+}
+
+#[test]
+fn signs_mortal_transactions_with_a_secret_uri_from_stdin() {
+	let genesis_hash = Hash::repeat_byte(7);
+	let era_hash = Hash::repeat_byte(9);
+	let output = sign_transaction(
+		&[
+			"--nonce",
+			"0",
+			"--suri-file",
+			"-",
+			"--genesis-hash",
+			&format!("{:?}", genesis_hash),
+			"--era-block",
+			"100",
+			"--era-block-hash",
+			&format!("{:?}", era_hash),
+			"register-company",
+			"--name",
+			"Acme",
+			"--id",
+			"42",
+		],
+		"//Bob",
+	);
+	let extrinsic = decode(output);
+
+	let (address, signature, extra) = extrinsic.signature.expect("the extrinsic is signed");
+	let bob = Sr25519Keyring::Bob.to_account_id();
+	assert_eq!(address, MultiAddress::Id(bob.clone()));
+	assert_eq!(extra.4.encode(), Era::mortal(64, 100).encode());
+	let payload = SignedPayload::from_raw(
+		extrinsic.function,
+		extra,
+		(
+			(),
+			VERSION.spec_version,
+			VERSION.transaction_version,
+			genesis_hash,
+			era_hash,
+			(),
+			(),
+			(),
+		),
+	);
+	assert!(payload.using_encoded(|message| signature.verify(message, &bob)));
+}
+
+#[test]
+fn requires_an_era_or_immortal() {
+	let output = sign_transaction(
+		&[
+			"--nonce",
+			"0",
+			"--suri-file",
+			"-",
+			"--genesis-hash",
+			&format!("{:?}", Hash::repeat_byte(7)),
+			"register-company",
+			"--name",
+			"Acme",
+			"--id",
+			"42",
+		],
+		"//Alice",
+	);
+	assert!(!output.status.success());
+	assert!(String::from_utf8_lossy(&output.stderr).contains("--era-block"));
+}
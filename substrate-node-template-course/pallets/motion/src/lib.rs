@@ -0,0 +1,143 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+/// Lets a collective dispatch calls with `Root` origin once a motion reaches the configured
+/// threshold. This replaces `pallet_sudo` as the way privileged calls such as runtime upgrades
+/// are made on the consortium chain.
+///
+/// Each threshold may only dispatch the calls the runtime allows for it: a simple majority
+/// those in `SimpleMajorityCalls`, a super majority those and the ones in `SuperMajorityCalls`,
+/// and only a unanimous collective any call.
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use frame_support::{
+		dispatch::{GetDispatchInfo, UnfilteredDispatchable},
+		pallet_prelude::*,
+		traits::Contains,
+	};
+	use frame_system::pallet_prelude::*;
+	use sp_std::prelude::*;
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	/// Configure the pallet by specifying the parameters and types on which it depends.
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// Because this pallet emits events, it depends on the runtime's definition of an event.
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+		/// A call that can be dispatched with `Root` origin by a passed motion.
+		type RuntimeCall: Parameter
+			+ UnfilteredDispatchable<RuntimeOrigin = Self::RuntimeOrigin>
+			+ GetDispatchInfo;
+
+		/// Origin of a motion backed by more than half of the collective.
+		type SimpleMajorityOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// Origin of a motion backed by at least two thirds of the collective.
+		type SuperMajorityOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// Origin of a motion backed by the whole collective.
+		type UnanimousOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// Calls a simple majority may dispatch.
+		type SimpleMajorityCalls: Contains<<Self as Config>::RuntimeCall>;
+
+		/// Calls a super majority may dispatch on top of `SimpleMajorityCalls`.
+		type SuperMajorityCalls: Contains<<Self as Config>::RuntimeCall>;
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The call needs a motion with a higher threshold.
+		CallNotAllowed,
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// A simple majority motion was dispatched with the given result.
+		DispatchSimpleMajority { motion_result: DispatchResult },
+		/// A super majority motion was dispatched with the given result.
+		DispatchSuperMajority { motion_result: DispatchResult },
+		/// A unanimous motion was dispatched with the given result.
+		DispatchUnanimous { motion_result: DispatchResult },
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Dispatch `call` with `Root` origin, given a simple majority of the collective.
+		///
+		/// `call` must be one of `SimpleMajorityCalls`.
+		#[pallet::weight({
+			let dispatch_info = call.get_dispatch_info();
+			(dispatch_info.weight.saturating_add(Weight::from_ref_time(10_000)), dispatch_info.class)
+		})]
+		pub fn simple_majority(
+			origin: OriginFor<T>,
+			call: Box<<T as Config>::RuntimeCall>,
+		) -> DispatchResultWithPostInfo {
+			T::SimpleMajorityOrigin::ensure_origin(origin)?;
+			ensure!(T::SimpleMajorityCalls::contains(&call), Error::<T>::CallNotAllowed);
+
+			let motion_result = Self::dispatch_as_root(*call);
+			Self::deposit_event(Event::DispatchSimpleMajority { motion_result });
+			Ok(Pays::No.into())
+		}
+
+		/// Dispatch `call` with `Root` origin, given a super majority of the collective.
+		///
+		/// `call` must be one of `SimpleMajorityCalls` or `SuperMajorityCalls`.
+		#[pallet::weight({
+			let dispatch_info = call.get_dispatch_info();
+			(dispatch_info.weight.saturating_add(Weight::from_ref_time(10_000)), dispatch_info.class)
+		})]
+		pub fn super_majority(
+			origin: OriginFor<T>,
+			call: Box<<T as Config>::RuntimeCall>,
+		) -> DispatchResultWithPostInfo {
+			T::SuperMajorityOrigin::ensure_origin(origin)?;
+			ensure!(
+				T::SimpleMajorityCalls::contains(&call) || T::SuperMajorityCalls::contains(&call),
+				Error::<T>::CallNotAllowed
+			);
+
+			let motion_result = Self::dispatch_as_root(*call);
+			Self::deposit_event(Event::DispatchSuperMajority { motion_result });
+			Ok(Pays::No.into())
+		}
+
+		/// Dispatch any `call` with `Root` origin, given the whole collective agrees.
+		#[pallet::weight({
+			let dispatch_info = call.get_dispatch_info();
+			(dispatch_info.weight.saturating_add(Weight::from_ref_time(10_000)), dispatch_info.class)
+		})]
+		pub fn unanimous(
+			origin: OriginFor<T>,
+			call: Box<<T as Config>::RuntimeCall>,
+		) -> DispatchResultWithPostInfo {
+			T::UnanimousOrigin::ensure_origin(origin)?;
+
+			let motion_result = Self::dispatch_as_root(*call);
+			Self::deposit_event(Event::DispatchUnanimous { motion_result });
+			Ok(Pays::No.into())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		fn dispatch_as_root(call: <T as Config>::RuntimeCall) -> DispatchResult {
+			call.dispatch_bypass_filter(frame_system::RawOrigin::Root.into())
+				.map(|_| ())
+				.map_err(|e| e.error)
+		}
+	}
+}
@@ -0,0 +1,95 @@
+use crate as pallet_motion;
+use frame_support::{
+	ord_parameter_types,
+	traits::{ConstU16, ConstU64, Contains},
+};
+use frame_system::{self as system, EnsureSignedBy};
+use sp_core::H256;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system,
+		Motion: pallet_motion,
+	}
+);
+
+impl system::Config for Test {
+	type BaseCallFilter = frame_support::traits::Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type RuntimeOrigin = RuntimeOrigin;
+	type RuntimeCall = RuntimeCall;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type RuntimeEvent = RuntimeEvent;
+	type BlockHashCount = ConstU64<250>;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ConstU16<42>;
+	type OnSetCode = ();
+	type MaxConsumers = frame_support::traits::ConstU32<16>;
+}
+
+ord_parameter_types! {
+	pub const SimpleMajority: u64 = 1;
+	pub const SuperMajority: u64 = 2;
+	pub const Unanimous: u64 = 3;
+}
+
+impl pallet_motion::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type RuntimeCall = RuntimeCall;
+	type SimpleMajorityOrigin = EnsureSignedBy<SimpleMajority, u64>;
+	type SuperMajorityOrigin = EnsureSignedBy<SuperMajority, u64>;
+	type UnanimousOrigin = EnsureSignedBy<Unanimous, u64>;
+	type SimpleMajorityCalls = IsSetHeapPages;
+	type SuperMajorityCalls = IsSetStorage;
+}
+
+/// Lets a simple majority change the heap pages.
+pub struct IsSetHeapPages;
+
+impl Contains<RuntimeCall> for IsSetHeapPages {
+	fn contains(call: &RuntimeCall) -> bool {
+		matches!(call, RuntimeCall::System(frame_system::Call::set_heap_pages { .. }))
+	}
+}
+
+/// Lets a super majority write storage.
+pub struct IsSetStorage;
+
+impl Contains<RuntimeCall> for IsSetStorage {
+	fn contains(call: &RuntimeCall) -> bool {
+		matches!(call, RuntimeCall::System(frame_system::Call::set_storage { .. }))
+	}
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut ext: sp_io::TestExternalities =
+		system::GenesisConfig::default().build_storage::<Test>().unwrap().into();
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
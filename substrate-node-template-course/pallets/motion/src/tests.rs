@@ -0,0 +1,83 @@
+use crate::{mock::*, Error, Event};
+use frame_support::{assert_noop, assert_ok};
+use sp_runtime::DispatchError;
+
+fn root_only_call() -> Box<RuntimeCall> {
+	Box::new(RuntimeCall::System(frame_system::Call::set_heap_pages { pages: 64 }))
+}
+
+#[test]
+fn simple_majority_dispatches_as_root() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Motion::simple_majority(RuntimeOrigin::signed(1), root_only_call()));
+		System::assert_last_event(
+			Event::<Test>::DispatchSimpleMajority { motion_result: Ok(()) }.into(),
+		);
+	});
+}
+
+#[test]
+fn super_majority_dispatches_as_root() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Motion::super_majority(RuntimeOrigin::signed(2), root_only_call()));
+		System::assert_last_event(
+			Event::<Test>::DispatchSuperMajority { motion_result: Ok(()) }.into(),
+		);
+	});
+}
+
+#[test]
+fn unanimous_dispatches_as_root() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Motion::unanimous(RuntimeOrigin::signed(3), root_only_call()));
+		System::assert_last_event(
+			Event::<Test>::DispatchUnanimous { motion_result: Ok(()) }.into(),
+		);
+	});
+}
+
+#[test]
+fn motions_reject_other_origins() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Motion::simple_majority(RuntimeOrigin::signed(2), root_only_call()),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			Motion::super_majority(RuntimeOrigin::signed(1), root_only_call()),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			Motion::unanimous(RuntimeOrigin::signed(2), root_only_call()),
+			DispatchError::BadOrigin
+		);
+	});
+}
+
+fn set_storage_call() -> Box<RuntimeCall> {
+	Box::new(RuntimeCall::System(frame_system::Call::set_storage {
+		items: vec![(b"key".to_vec(), b"value".to_vec())],
+	}))
+}
+
+fn kill_storage_call() -> Box<RuntimeCall> {
+	Box::new(RuntimeCall::System(frame_system::Call::kill_storage { keys: vec![b"key".to_vec()] }))
+}
+
+#[test]
+fn stronger_calls_need_higher_thresholds() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Motion::simple_majority(RuntimeOrigin::signed(1), set_storage_call()),
+			Error::<Test>::CallNotAllowed
+		);
+		assert_ok!(Motion::super_majority(RuntimeOrigin::signed(2), set_storage_call()));
+		assert_ok!(Motion::super_majority(RuntimeOrigin::signed(2), root_only_call()));
+
+		assert_noop!(
+			Motion::super_majority(RuntimeOrigin::signed(2), kill_storage_call()),
+			Error::<Test>::CallNotAllowed
+		);
+		assert_ok!(Motion::unanimous(RuntimeOrigin::signed(3), kill_storage_call()));
+	});
+}
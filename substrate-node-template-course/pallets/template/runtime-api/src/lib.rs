@@ -0,0 +1,16 @@
+//! Runtime API for querying the companies registered with the supply chain pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+pub use pallet_template::{Company, CompanyDetails, LegalDetails};
+
+sp_api::decl_runtime_apis! {
+	pub trait SupplyChainApi<AccountId> where
+		AccountId: Codec,
+	{
+		/// The company registered to `account`, with the legal name, website and registration
+		/// number of its judged identity.
+		fn company_details(account: AccountId) -> Option<CompanyDetails>;
+	}
+}
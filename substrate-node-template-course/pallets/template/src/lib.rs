@@ -14,14 +14,32 @@ mod tests;
 #[cfg(feature = "runtime-benchmarks")]
 mod benchmarking;
 
+pub mod migrations;
+
 #[frame_support::pallet]
 pub mod pallet {
-	use frame_support::pallet_prelude::*;
+	use frame_support::{
+		dispatch::GetCallName,
+		pallet_prelude::*,
+		traits::{
+			schedule::{v2::Named as ScheduleNamed, DispatchTime, MaybeHashed, LOWEST_PRIORITY},
+			tokens::{fungibles, Balance},
+		},
+		PalletId,
+	};
 	use frame_system::pallet_prelude::*;
+	use sp_runtime::traits::{AccountIdConversion, Saturating, Zero};
 	use sp_std::prelude::*;
 
+	/// Identifier of the asset a supply contract or IOU is denominated in.
+	pub type AssetId = u32;
+
+	/// The current storage version.
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	#[pallet::without_storage_info]
 	pub struct Pallet<T>(_);
 
@@ -30,6 +48,90 @@ pub mod pallet {
 	pub trait Config: frame_system::Config {
 		/// Because this pallet emits events, it depends on the runtime's definition of an event.
 		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+		/// Origin allowed to mark companies as verified.
+		type VerifyOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// Origin allowed to rule on the status of a disputed supply contract.
+		type ArbiterOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// Origin allowed to pause and unpause individual calls of this pallet.
+		type PauseOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// Balance type of `Assets`. Contract values and IOU amounts are converted into it.
+		type AssetBalance: Balance + From<u64>;
+
+		/// The assets supply contracts are escrowed in and IOUs are repaid in.
+		type Assets: fungibles::Transfer<
+			Self::AccountId,
+			AssetId = AssetId,
+			Balance = Self::AssetBalance,
+		>;
+
+		/// The pallet id, used to derive the account that holds escrowed contract value.
+		#[pallet::constant]
+		type PalletId: Get<PalletId>;
+
+		/// The overarching call type, used to schedule IOU installments.
+		type RuntimeCall: Parameter + From<Call<Self>>;
+
+		/// The caller origin, overarching type of all pallets origins.
+		type PalletsOrigin: From<frame_system::RawOrigin<Self::AccountId>>;
+
+		/// Scheduler that debits IOU installments on behalf of the debtor.
+		type Scheduler: ScheduleNamed<
+			Self::BlockNumber,
+			<Self as Config>::RuntimeCall,
+			Self::PalletsOrigin,
+		>;
+
+		/// Source of the judged legal details of the account registering a company.
+		type Identity: CompanyIdentity<Self::AccountId>;
+
+		/// Whether `register_company` requires the caller to hold a judged identity.
+		#[pallet::constant]
+		type RequireJudgedIdentity: Get<bool>;
+
+		/// Derives the account of a multisig from its signatories and threshold.
+		type MultiAccountId: MultiAccountId<Self::AccountId>;
+
+		/// Contract value above which a supply contract must be created and settled by a
+		/// company's multisig.
+		#[pallet::constant]
+		type MultisigThreshold: Get<u64>;
+
+		/// Maximum number of IOUs per direction that one `net_ious` call offsets.
+		#[pallet::constant]
+		type MaxNettedIOUs: Get<u32>;
+	}
+
+	///Derives the account a multisig of `who` with `threshold` approvals dispatches from
+	pub trait MultiAccountId<AccountId> {
+		///`who` is sorted and free of duplicates
+		fn multi_account_id(who: &[AccountId], threshold: u16) -> AccountId;
+	}
+
+	///Legal details of a company, taken from an identity a registrar has judged
+	#[derive(Encode, Decode, Clone, PartialEq, Default, RuntimeDebug, TypeInfo)]
+	pub struct LegalDetails {
+		///registered legal name
+		pub legal_name: Vec<u8>,
+		///company website
+		pub website: Vec<u8>,
+		///company registration number
+		pub registration_number: Vec<u8>,
+	}
+
+	///Looks up the legal details of an account whose identity has been judged by a registrar
+	pub trait CompanyIdentity<AccountId> {
+		///`None` unless the account has an identity with a positive judgement
+		fn legal_details(who: &AccountId) -> Option<LegalDetails>;
+	}
+
+	impl<AccountId> CompanyIdentity<AccountId> for () {
+		fn legal_details(_: &AccountId) -> Option<LegalDetails> {
+			None
+		}
 	}
 
 	///Company data
@@ -41,6 +143,16 @@ pub mod pallet {
 		pub name: Vec<u8>,
 		///companys about information
 		pub about_me: Vec<u8>,
+		///whether governance has verified the company
+		pub verified: bool,
+	}
+
+	///Company data together with the legal details of its judged identity
+	#[derive(Encode, Decode, Clone, PartialEq, TypeInfo)]
+	pub struct CompanyDetails {
+		pub company: Company,
+		///`None` if the company account has no judged identity
+		pub legal: Option<LegalDetails>,
 	}
 
 	///storage map to interact with the node's storage
@@ -49,6 +161,18 @@ pub mod pallet {
 	pub type AccountToCompany<T: Config> =
 		StorageMap<_, Blake2_128Concat, T::AccountId, Company, ValueQuery>;
 
+	///reverse lookup from a company id to the account it is registered to
+	#[pallet::storage]
+	#[pallet::getter(fn company_account)]
+	pub type CompanyIdToAccount<T: Config> = StorageMap<_, Blake2_128Concat, u64, T::AccountId>;
+
+	///companies whose id the v1 migration gave to another account that had registered the same
+	///id, kept until their account registers again
+	#[pallet::storage]
+	#[pallet::getter(fn displaced_company)]
+	pub type DisplacedCompanies<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, Company>;
+
 	///Contract data
 	#[derive(Encode, Decode, Clone, PartialEq, Default, TypeInfo)]
 	pub struct SupplyContract {
@@ -60,6 +184,10 @@ pub mod pallet {
 		pub iou: u64,
 		pub contract_value: u64,
 		pub contract_fulfilled: bool,
+		///asset the contract value is paid in
+		pub asset_id: AssetId,
+		///whether the buyer has paid the contract value into escrow
+		pub escrowed: bool,
 	}
 
 	///storage map to interact with the node's storage
@@ -68,6 +196,13 @@ pub mod pallet {
 	pub type AccountToSupplyContract<T: Config> =
 		StorageMap<_, Blake2_128Concat, T::AccountId, SupplyContract, ValueQuery>;
 
+	///contract account a supply contract's holder allowed to update its status through the
+	///runtime's chain extension
+	#[pallet::storage]
+	#[pallet::getter(fn supply_contract_delegate)]
+	pub type SupplyContractDelegate<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, T::AccountId>;
+
 	///product data
 	#[derive(Encode, Decode, Clone, PartialEq, Default, TypeInfo)]
 	pub struct Product {
@@ -91,6 +226,10 @@ pub mod pallet {
 		pub debtor: u64,
 		pub creditor: u64,
 		pub amount: u64,
+		///asset the IOU is repaid in
+		pub asset_id: AssetId,
+		///whether a scheduled installment could not be paid
+		pub delinquent: bool,
 	}
 
 	///storage map to interact with the node's storage
@@ -99,6 +238,103 @@ pub mod pallet {
 	pub type AccountToIOU<T: Config> =
 		StorageMap<_, Blake2_128Concat, T::AccountId, IOU, ValueQuery>;
 
+	///whether the debtor and the creditor of the IOU held by an account have confirmed it
+	#[pallet::storage]
+	#[pallet::getter(fn iou_confirmations)]
+	pub type IOUConfirmations<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, (bool, bool), ValueQuery>;
+
+	///accounts holding IOUs confirmed by both parties, keyed by `(debtor, creditor, asset_id)`
+	#[pallet::storage]
+	pub type ConfirmedIOUs<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		(u64, u64, AssetId),
+		Blake2_128Concat,
+		T::AccountId,
+		(),
+		OptionQuery,
+	>;
+
+	///Installment plan debiting an IOU from its debtor every `period` blocks
+	#[derive(Encode, Decode, Clone, PartialEq, RuntimeDebug, TypeInfo)]
+	pub struct InstallmentPlan<BlockNumber> {
+		///amount debited per installment; the last one pays whatever is left
+		pub installment: u64,
+		///installments not yet attempted
+		pub remaining_installments: u32,
+		///blocks between two installments
+		pub period: BlockNumber,
+		///block at which the next installment is due
+		pub next_due: BlockNumber,
+		///installments that could not be paid
+		pub missed: u32,
+	}
+
+	///installment plans keyed by the account holding the IOU
+	#[pallet::storage]
+	#[pallet::getter(fn installment_plan)]
+	pub type IOUInstallmentPlan<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, InstallmentPlan<T::BlockNumber>>;
+
+	///company accounts paying the transaction fees of their staff's supply-chain calls
+	#[pallet::storage]
+	#[pallet::getter(fn sponsor_of)]
+	pub type StaffSponsor<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, T::AccountId>;
+
+	///names of the calls of this pallet that the runtime's call filter currently rejects
+	#[pallet::storage]
+	#[pallet::getter(fn paused_calls)]
+	pub type PausedCalls<T: Config> = StorageMap<_, Blake2_128Concat, Vec<u8>, (), OptionQuery>;
+
+	///multisig account of a company's officers, keyed by the company account
+	#[pallet::storage]
+	#[pallet::getter(fn company_multisig)]
+	pub type CompanyMultisig<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, T::AccountId>;
+
+	///company account a multisig acts for, keyed by the multisig account
+	#[pallet::storage]
+	#[pallet::getter(fn multisig_company)]
+	pub type MultisigCompany<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, T::AccountId>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		/// Founding companies of the consortium as `(account, id, name, about_me)`. They start
+		/// out verified.
+		pub companies: Vec<(T::AccountId, u64, Vec<u8>, Vec<u8>)>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self { companies: Vec::new() }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			for (account, id, name, about_me) in &self.companies {
+				assert!(name.len() <= 64, "company name too long");
+				assert!(about_me.len() <= 2000, "company description too long");
+				assert!(*id > 0 && *id < 10000000000000, "company id out of range");
+				assert!(!<AccountToCompany<T>>::contains_key(account), "duplicate company account");
+				assert!(!<CompanyIdToAccount<T>>::contains_key(id), "duplicate company id");
+
+				let company = Company {
+					id: *id,
+					name: name.clone(),
+					about_me: about_me.clone(),
+					verified: true,
+				};
+				<AccountToCompany<T>>::insert(account, company);
+				<CompanyIdToAccount<T>>::insert(id, account);
+			}
+		}
+	}
+
 	// Pallets use events to inform users when important changes are made.
 	// https://docs.substrate.io/main-docs/build/events-errors/
 	#[pallet::event]
@@ -108,6 +344,45 @@ pub mod pallet {
 		SupplyContractCreated { contract: T::AccountId },
 		ProductCreated { product: T::AccountId },
 		IOUCreated { iou: T::AccountId },
+		/// The debtor or the creditor of an IOU confirmed it.
+		IOUConfirmed { iou: T::AccountId, company: u64 },
+		/// Governance changed the verification status of a company.
+		CompanyVerified { company: T::AccountId, verified: bool },
+		/// An arbiter ruled on the status of a supply contract.
+		ArbiterRuling { contract: T::AccountId, delivered: bool, contract_fulfilled: bool },
+		/// The delivery or fulfilment status of a supply contract changed.
+		SupplyContractStatusChanged {
+			contract: T::AccountId,
+			delivered: bool,
+			contract_fulfilled: bool,
+		},
+		/// The holder of a supply contract allowed a smart contract to update its status, or
+		/// revoked that.
+		SupplyContractDelegateSet { contract: T::AccountId, delegate: Option<T::AccountId> },
+		/// The buyer paid the value of a supply contract into escrow.
+		SupplyContractFunded { contract: T::AccountId, asset_id: AssetId, amount: u64 },
+		/// Escrowed contract value was released to the seller.
+		EscrowReleased { contract: T::AccountId, seller: T::AccountId, amount: u64 },
+		/// The debtor repaid part or all of an IOU.
+		IOURepaid { iou: T::AccountId, amount: u64, remaining: u64 },
+		/// The debtor set up an installment plan for an IOU.
+		InstallmentPlanCreated { iou: T::AccountId, installments: u32, period: T::BlockNumber },
+		/// A scheduled installment was debited from the debtor.
+		InstallmentPaid { iou: T::AccountId, amount: u64, remaining: u64 },
+		/// A scheduled installment could not be paid and the IOU was marked delinquent.
+		InstallmentMissed { iou: T::AccountId, amount: u64 },
+		/// Opposing IOUs between two companies were offset against each other.
+		Netted { company_a: u64, company_b: u64, asset_id: AssetId, amount: u64 },
+		/// A company started paying the fees of a staff account.
+		StaffSponsored { company: T::AccountId, staff: T::AccountId },
+		/// A company stopped paying the fees of a staff account.
+		StaffUnsponsored { company: T::AccountId, staff: T::AccountId },
+		/// A company registered the multisig of its officers.
+		CompanyMultisigSet { company: T::AccountId, multisig: T::AccountId, threshold: u16 },
+		/// Governance paused a call of this pallet.
+		CallPaused { call: Vec<u8> },
+		/// Governance unpaused a call of this pallet.
+		CallUnpaused { call: Vec<u8> },
 	}
 
 	// Errors inform users that something went wrong.
@@ -119,6 +394,75 @@ pub mod pallet {
 		IdTooBig,
 		ProductIdNotFound,
 		NotProductOwner,
+		/// The account has no registered company.
+		CompanyNotFound,
+		/// Another account already registered a company with this id.
+		CompanyIdTaken,
+		/// Registering a company requires an identity judged by a registrar.
+		IdentityNotJudged,
+		/// The caller is not the account registered to the buyer of the supply contract, nor the
+		/// buyer's multisig.
+		NotBuyer,
+		/// The caller is not the account registered to the debtor of the IOU.
+		NotDebtor,
+		/// The supply contract value is already held in escrow.
+		AlreadyEscrowed,
+		/// The caller's supply contract is in escrow and cannot be replaced until it is settled.
+		SupplyContractEscrowed,
+		/// The account does not hold an IOU.
+		IOUNotFound,
+		/// The repayment is zero or larger than the outstanding amount.
+		InvalidRepayment,
+		/// The account does not hold a supply contract.
+		SupplyContractNotFound,
+		/// Supply contracts can only move forward: undelivered, delivered, then fulfilled.
+		InvalidStatusTransition,
+		/// An installment plan needs at least one installment and a non-zero period.
+		InvalidInstallmentPlan,
+		/// The IOU already has an installment plan.
+		InstallmentPlanExists,
+		/// The IOU has no installment plan.
+		NoInstallmentPlan,
+		/// The IOU held by the caller is being repaid in installments and cannot be replaced.
+		InstallmentPlanActive,
+		/// The next installment is not due yet.
+		InstallmentNotDue,
+		/// The installments could not be scheduled.
+		ScheduleFailed,
+		/// A company cannot net IOUs against itself.
+		CannotNetWithSelf,
+		/// The caller is not one of the companies whose IOUs are being netted.
+		NotPartyToIOUs,
+		/// The caller is not the account registered to the debtor or the creditor of the IOU.
+		NotPartyToIOU,
+		/// The two companies do not owe each other in both directions.
+		NothingToNet,
+		/// The staff account is already sponsored by a company.
+		AlreadySponsored,
+		/// The staff account is not sponsored by the caller.
+		NotSponsor,
+		/// A multisig needs at least two approvals and no more than its number of officers.
+		InvalidMultisig,
+		/// The multisig is already registered to another company.
+		MultisigTaken,
+		/// Supply contracts above `MultisigThreshold` must come from the multisig of a party.
+		MultisigRequired,
+		/// This pallet has no call with the given name.
+		UnknownCall,
+		/// `pause_call` and `unpause_call` cannot be paused.
+		UnpausableCall,
+		/// The call is already paused.
+		AlreadyPaused,
+		/// The call is not paused.
+		NotPaused,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		#[cfg(feature = "try-runtime")]
+		fn try_state(_n: BlockNumberFor<T>) -> Result<(), &'static str> {
+			Self::do_try_state()
+		}
 	}
 
 	// Dispatchable functions allows users to interact with the pallet and invoke state changes.
@@ -139,10 +483,23 @@ pub mod pallet {
 			ensure!(about_me.len() <= 2000, Error::<T>::AboutMeTooLong);
 			ensure!(id > 0, Error::<T>::IdTooSmall);
 			ensure!(id < 10000000000000, Error::<T>::IdTooBig);
+			ensure!(
+				<CompanyIdToAccount<T>>::get(id).map_or(true, |account| account == sender),
+				Error::<T>::CompanyIdTaken
+			);
+			ensure!(
+				!T::RequireJudgedIdentity::get() || T::Identity::legal_details(&sender).is_some(),
+				Error::<T>::IdentityNotJudged
+			);
 
-			let new_company = Company { name, id, about_me };
+			if <AccountToCompany<T>>::contains_key(&sender) {
+				<CompanyIdToAccount<T>>::remove(<AccountToCompany<T>>::get(&sender).id);
+			}
+			let new_company = Company { name, id, about_me, verified: false };
 
+			<DisplacedCompanies<T>>::remove(&sender);
 			<AccountToCompany<T>>::insert(&sender, new_company);
+			<CompanyIdToAccount<T>>::insert(id, &sender);
 			Self::deposit_event(Event::CompanyCreated { company: sender });
 			Ok(())
 		}
@@ -167,7 +524,9 @@ pub mod pallet {
 			Ok(())
 		}
 
-		#[pallet::weight(10_000 + T::DbWeight::get().writes(1).ref_time())]
+		/// Create a supply contract held by the caller, replacing any previous one that is not in
+		/// escrow.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 1).ref_time())]
 		pub fn create_supply_contract(
 			origin: OriginFor<T>,
 			id: u64,
@@ -178,36 +537,746 @@ pub mod pallet {
 			iou: u64,
 			contract_value: u64,
 			contract_fulfilled: bool,
+			asset_id: AssetId,
 		)-> DispatchResult{
 			let sender = ensure_signed(origin)?;
 			ensure!(id > 0, Error::<T>::IdTooSmall);
 			ensure!(id < 10000000000000, Error::<T>::IdTooBig);
+			Self::ensure_multisig_if_high_value(&sender, contract_value, &[seller_id, buyer_id])?;
+			ensure!(
+				!<AccountToSupplyContract<T>>::get(&sender).escrowed,
+				Error::<T>::SupplyContractEscrowed
+			);
 
-			let new_supply_contract = SupplyContract { id, seller_id, buyer_id, products, delivered, iou, contract_value, contract_fulfilled };
+			let new_supply_contract = SupplyContract {
+				id,
+				seller_id,
+				buyer_id,
+				products,
+				delivered,
+				iou,
+				contract_value,
+				contract_fulfilled,
+				asset_id,
+				escrowed: false,
+			};
 
 			<AccountToSupplyContract<T>>::insert(&sender, new_supply_contract);
 			Self::deposit_event(Event::SupplyContractCreated { contract: sender });
 			Ok(())
 		}
 
-		#[pallet::weight(10_000 + T::DbWeight::get().writes(1).ref_time())]
+		/// Create an IOU held by the caller.
+		///
+		/// The IOU counts as confirmed by the debtor or the creditor if the caller is the account
+		/// registered to it. The other party confirms it with `confirm_iou`. An IOU the debtor is
+		/// repaying in installments cannot be replaced until its plan ends.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(5, 4).ref_time())]
 		pub fn create_iou(
 			origin: OriginFor<T>,
 			id: u64,
 			debtor: u64,
 			creditor: u64,
 			amount: u64,
+			asset_id: AssetId,
 		)-> DispatchResult{
 			let sender = ensure_signed(origin)?;
 			ensure!(id > 0, Error::<T>::IdTooSmall);
 			ensure!(id < 10000000000000, Error::<T>::IdTooBig);
+			ensure!(
+				!<IOUInstallmentPlan<T>>::contains_key(&sender),
+				Error::<T>::InstallmentPlanActive
+			);
 
-			let new_iou = IOU { id, debtor, creditor, amount };
+			let new_iou = IOU { id, debtor, creditor, amount, asset_id, delinquent: false };
+			let confirmations = (
+				Self::company_account(debtor).as_ref() == Some(&sender),
+				Self::company_account(creditor).as_ref() == Some(&sender),
+			);
 
+			Self::forget_iou(&sender);
+			Self::record_confirmations(&sender, &new_iou, confirmations);
 			<AccountToIOU<T>>::insert(&sender, new_iou);
 			Self::deposit_event(Event::IOUCreated { iou: sender });
 			Ok(())
 		}
 
+		/// Confirm the IOU held by `iou` as its debtor or creditor.
+		///
+		/// Must be called by the account registered to the debtor or the creditor. `net_ious`
+		/// only offsets IOUs that both have confirmed, so that a company cannot net what it owes
+		/// against a claim the other company never agreed to.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(4, 2).ref_time())]
+		pub fn confirm_iou(origin: OriginFor<T>, iou: T::AccountId) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(<AccountToIOU<T>>::contains_key(&iou), Error::<T>::IOUNotFound);
+			let owed = <AccountToIOU<T>>::get(&iou);
+			let is_debtor = Self::company_account(owed.debtor).as_ref() == Some(&sender);
+			let is_creditor = Self::company_account(owed.creditor).as_ref() == Some(&sender);
+			ensure!(is_debtor || is_creditor, Error::<T>::NotPartyToIOU);
+
+			let (debtor, creditor) = <IOUConfirmations<T>>::get(&iou);
+			Self::record_confirmations(&iou, &owed, (debtor || is_debtor, creditor || is_creditor));
+			let company = if is_debtor { owed.debtor } else { owed.creditor };
+			Self::deposit_event(Event::IOUConfirmed { iou, company });
+			Ok(())
+		}
+
+		/// Mark the company registered to `company` as verified or unverified.
+		///
+		/// The origin must be `VerifyOrigin`.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1).ref_time())]
+		pub fn verify_company(
+			origin: OriginFor<T>,
+			company: T::AccountId,
+			verified: bool,
+		) -> DispatchResult {
+			T::VerifyOrigin::ensure_origin(origin)?;
+			ensure!(<AccountToCompany<T>>::contains_key(&company), Error::<T>::CompanyNotFound);
+
+			<AccountToCompany<T>>::mutate(&company, |c| c.verified = verified);
+			Self::deposit_event(Event::CompanyVerified { company, verified });
+			Ok(())
+		}
+
+		/// Settle a dispute by setting the status of the supply contract held by `contract`.
+		///
+		/// The origin must be `ArbiterOrigin`. Unlike `update_supply_contract_status`, a ruling may
+		/// move a contract back to an earlier status.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1).ref_time())]
+		pub fn arbitrate_supply_contract(
+			origin: OriginFor<T>,
+			contract: T::AccountId,
+			delivered: bool,
+			contract_fulfilled: bool,
+		) -> DispatchResult {
+			T::ArbiterOrigin::ensure_origin(origin)?;
+			ensure!(
+				<AccountToSupplyContract<T>>::contains_key(&contract),
+				Error::<T>::SupplyContractNotFound
+			);
+			ensure!(delivered || !contract_fulfilled, Error::<T>::InvalidStatusTransition);
+
+			let mut supply_contract = <AccountToSupplyContract<T>>::get(&contract);
+			supply_contract.delivered = delivered;
+			supply_contract.contract_fulfilled = contract_fulfilled;
+			Self::release_escrow_if_fulfilled(&contract, &mut supply_contract)?;
+			<AccountToSupplyContract<T>>::insert(&contract, supply_contract);
+
+			Self::deposit_event(Event::ArbiterRuling { contract, delivered, contract_fulfilled });
+			Ok(())
+		}
+
+		/// Move the caller's supply contract to a new delivery/fulfilment status.
+		///
+		/// A contract in escrow can only be fulfilled here if the caller acts for its buyer;
+		/// otherwise the buyer confirms it with `confirm_delivery`.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1).ref_time())]
+		pub fn update_supply_contract_status(
+			origin: OriginFor<T>,
+			delivered: bool,
+			contract_fulfilled: bool,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			Self::set_supply_contract_status(&sender, delivered, contract_fulfilled)
+		}
+
+		/// Confirm as the buyer that the supply contract held by `contract` was delivered, which
+		/// fulfils it and releases its escrow to the seller.
+		///
+		/// Must be called by the account registered to the buyer or by the buyer's multisig, which
+		/// is required above `MultisigThreshold`.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(4, 3).ref_time())]
+		pub fn confirm_delivery(origin: OriginFor<T>, contract: T::AccountId) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(
+				<AccountToSupplyContract<T>>::contains_key(&contract),
+				Error::<T>::SupplyContractNotFound
+			);
+			let buyer_id = <AccountToSupplyContract<T>>::get(&contract).buyer_id;
+			ensure!(Self::acts_for(&sender, buyer_id), Error::<T>::NotBuyer);
+			Self::change_supply_contract_status(&contract, &sender, true, true)
+		}
+
+		/// Allow the smart contract `delegate` to update the status of the caller's supply contract
+		/// through the runtime's chain extension, or revoke that with `None`.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1).ref_time())]
+		pub fn set_supply_contract_delegate(
+			origin: OriginFor<T>,
+			delegate: Option<T::AccountId>,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(
+				<AccountToSupplyContract<T>>::contains_key(&sender),
+				Error::<T>::SupplyContractNotFound
+			);
+
+			<SupplyContractDelegate<T>>::set(&sender, delegate.clone());
+			Self::deposit_event(Event::SupplyContractDelegateSet { contract: sender, delegate });
+			Ok(())
+		}
+
+		/// Pay the value of the supply contract held by `contract` into escrow.
+		///
+		/// Must be called by the account registered to the contract's buyer or by the buyer's
+		/// multisig, which is required above `MultisigThreshold`. The value is released to the
+		/// seller once the contract is fulfilled.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 3).ref_time())]
+		pub fn fund_supply_contract(
+			origin: OriginFor<T>,
+			contract: T::AccountId,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(
+				<AccountToSupplyContract<T>>::contains_key(&contract),
+				Error::<T>::SupplyContractNotFound
+			);
+			let mut supply_contract = <AccountToSupplyContract<T>>::get(&contract);
+			ensure!(Self::acts_for(&sender, supply_contract.buyer_id), Error::<T>::NotBuyer);
+			Self::ensure_multisig_if_high_value(
+				&sender,
+				supply_contract.contract_value,
+				&[supply_contract.buyer_id],
+			)?;
+			ensure!(!supply_contract.escrowed, Error::<T>::AlreadyEscrowed);
+
+			T::Assets::transfer(
+				supply_contract.asset_id,
+				&sender,
+				&Self::escrow_account(),
+				supply_contract.contract_value.into(),
+				false,
+			)?;
+			supply_contract.escrowed = true;
+			let (asset_id, amount) = (supply_contract.asset_id, supply_contract.contract_value);
+			Self::deposit_event(Event::SupplyContractFunded {
+				contract: contract.clone(),
+				asset_id,
+				amount,
+			});
+
+			Self::release_escrow_if_fulfilled(&contract, &mut supply_contract)?;
+			<AccountToSupplyContract<T>>::insert(&contract, supply_contract);
+			Ok(())
+		}
+
+		/// Repay `amount` of the IOU held by `iou` to its creditor.
+		///
+		/// Must be called by the account registered to the IOU's debtor. The IOU is closed once
+		/// nothing is outstanding, and the installments of its plan are spread over what is left.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(4, 4).ref_time())]
+		pub fn repay_iou(origin: OriginFor<T>, iou: T::AccountId, amount: u64) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(<AccountToIOU<T>>::contains_key(&iou), Error::<T>::IOUNotFound);
+			let owed = <AccountToIOU<T>>::get(&iou);
+			ensure!(
+				Self::company_account(owed.debtor).as_ref() == Some(&sender),
+				Error::<T>::NotDebtor
+			);
+
+			let remaining = Self::do_repay_iou(&iou, &sender, amount)?;
+			Self::reschedule_installments(&iou, remaining);
+			Self::deposit_event(Event::IOURepaid { iou, amount, remaining });
+			Ok(())
+		}
+
+		/// Repay the IOU held by `iou` in `installments` payments, one every `period` blocks.
+		///
+		/// Must be called by the account registered to the IOU's debtor. Each installment is
+		/// debited automatically through the scheduler; a missed payment marks the IOU delinquent.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 2).ref_time())]
+		pub fn create_installment_plan(
+			origin: OriginFor<T>,
+			iou: T::AccountId,
+			installments: u32,
+			period: T::BlockNumber,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(<AccountToIOU<T>>::contains_key(&iou), Error::<T>::IOUNotFound);
+			let owed = <AccountToIOU<T>>::get(&iou);
+			ensure!(
+				Self::company_account(owed.debtor).as_ref() == Some(&sender),
+				Error::<T>::NotDebtor
+			);
+			ensure!(installments > 0 && !period.is_zero(), Error::<T>::InvalidInstallmentPlan);
+			ensure!(
+				!<IOUInstallmentPlan<T>>::contains_key(&iou),
+				Error::<T>::InstallmentPlanExists
+			);
+
+			let next_due = frame_system::Pallet::<T>::block_number().saturating_add(period);
+			T::Scheduler::schedule_named(
+				Self::installment_task_id(&iou),
+				DispatchTime::At(next_due),
+				Some((period, installments)),
+				LOWEST_PRIORITY,
+				frame_system::RawOrigin::Signed(sender).into(),
+				MaybeHashed::Value(Call::<T>::pay_installment { iou: iou.clone() }.into()),
+			)
+			.map_err(|_| Error::<T>::ScheduleFailed)?;
+
+			<IOUInstallmentPlan<T>>::insert(
+				&iou,
+				InstallmentPlan {
+					installment: Self::installment_of(owed.amount, installments),
+					remaining_installments: installments,
+					period,
+					next_due,
+					missed: 0,
+				},
+			);
+			Self::deposit_event(Event::InstallmentPlanCreated { iou, installments, period });
+			Ok(())
+		}
+
+		/// Pay the next due installment of the IOU held by `iou`.
+		///
+		/// Dispatched by the scheduler with the debtor's origin. A payment that fails does not
+		/// fail the call: the IOU is marked delinquent and the plan moves on.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(4, 3).ref_time())]
+		pub fn pay_installment(origin: OriginFor<T>, iou: T::AccountId) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			let mut plan =
+				<IOUInstallmentPlan<T>>::get(&iou).ok_or(Error::<T>::NoInstallmentPlan)?;
+			ensure!(
+				plan.next_due <= frame_system::Pallet::<T>::block_number(),
+				Error::<T>::InstallmentNotDue
+			);
+			if !<AccountToIOU<T>>::contains_key(&iou) {
+				// Repaid or netted outside of the plan.
+				Self::end_installment_plan(&iou);
+				return Ok(())
+			}
+			let owed = <AccountToIOU<T>>::get(&iou);
+			ensure!(
+				Self::company_account(owed.debtor).as_ref() == Some(&sender),
+				Error::<T>::NotDebtor
+			);
+
+			let amount = if plan.remaining_installments <= 1 {
+				owed.amount
+			} else {
+				plan.installment.min(owed.amount)
+			};
+			match Self::do_repay_iou(&iou, &sender, amount) {
+				Ok(remaining) => Self::deposit_event(Event::InstallmentPaid {
+					iou: iou.clone(),
+					amount,
+					remaining,
+				}),
+				Err(_) => {
+					<AccountToIOU<T>>::mutate(&iou, |owed| owed.delinquent = true);
+					plan.missed = plan.missed.saturating_add(1);
+					Self::deposit_event(Event::InstallmentMissed { iou: iou.clone(), amount });
+				},
+			}
+
+			plan.remaining_installments = plan.remaining_installments.saturating_sub(1);
+			plan.next_due = plan.next_due.saturating_add(plan.period);
+			if plan.remaining_installments == 0 || !<AccountToIOU<T>>::contains_key(&iou) {
+				Self::end_installment_plan(&iou);
+			} else {
+				<IOUInstallmentPlan<T>>::insert(&iou, plan);
+			}
+			Ok(())
+		}
+
+		/// Offset the outstanding IOUs that two companies hold against each other.
+		///
+		/// Only IOUs denominated in `asset_id` and confirmed by both companies are netted, at most
+		/// `MaxNettedIOUs` in each direction. The direction with the smaller total is closed and
+		/// the larger one is reduced by the same amount, and installment plans are adjusted to
+		/// what is left. Must be called by an account registered to one of the two companies.
+		#[pallet::weight({
+			let ious = u64::from(T::MaxNettedIOUs::get());
+			10_000 + T::DbWeight::get().reads_writes(1 + 8 * ious, 10 * ious).ref_time()
+		})]
+		pub fn net_ious(
+			origin: OriginFor<T>,
+			company_a: u64,
+			company_b: u64,
+			asset_id: AssetId,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(company_a != company_b, Error::<T>::CannotNetWithSelf);
+			ensure!(<AccountToCompany<T>>::contains_key(&sender), Error::<T>::CompanyNotFound);
+			let caller_id = <AccountToCompany<T>>::get(&sender).id;
+			ensure!(caller_id == company_a || caller_id == company_b, Error::<T>::NotPartyToIOUs);
+
+			let a_owes_b = Self::confirmed_ious(company_a, company_b, asset_id);
+			let b_owes_a = Self::confirmed_ious(company_b, company_a, asset_id);
+
+			let netted = Self::total_owed(&a_owes_b).min(Self::total_owed(&b_owes_a));
+			ensure!(netted > 0, Error::<T>::NothingToNet);
+
+			Self::offset_ious(a_owes_b, netted);
+			Self::offset_ious(b_owes_a, netted);
+
+			Self::deposit_event(Event::Netted { company_a, company_b, asset_id, amount: netted });
+			Ok(())
+		}
+
+		/// Pay the transaction fees of `staff` for calls into this pallet.
+		///
+		/// The caller must have a registered company. Fees are charged to the company by the
+		/// runtime's sponsored fee payment signed extension.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 1).ref_time())]
+		pub fn sponsor_staff(origin: OriginFor<T>, staff: T::AccountId) -> DispatchResult {
+			let company = ensure_signed(origin)?;
+			ensure!(<AccountToCompany<T>>::contains_key(&company), Error::<T>::CompanyNotFound);
+			ensure!(!<StaffSponsor<T>>::contains_key(&staff), Error::<T>::AlreadySponsored);
+
+			<StaffSponsor<T>>::insert(&staff, &company);
+			Self::deposit_event(Event::StaffSponsored { company, staff });
+			Ok(())
+		}
+
+		/// Stop paying the transaction fees of `staff`.
+		///
+		/// May be called by the sponsoring company or by the staff account itself.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1).ref_time())]
+		pub fn unsponsor_staff(origin: OriginFor<T>, staff: T::AccountId) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			let company = <StaffSponsor<T>>::get(&staff).ok_or(Error::<T>::NotSponsor)?;
+			ensure!(sender == company || sender == staff, Error::<T>::NotSponsor);
+
+			<StaffSponsor<T>>::remove(&staff);
+			Self::deposit_event(Event::StaffUnsponsored { company, staff });
+			Ok(())
+		}
+
+		/// Register the multisig of `officers` with `threshold` approvals as the caller company's
+		/// multisig, replacing any previous one. A multisig can only act for one company.
+		///
+		/// Supply contracts worth more than `MultisigThreshold` can only be created, funded and
+		/// fulfilled by this multisig.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 3).ref_time())]
+		pub fn set_company_multisig(
+			origin: OriginFor<T>,
+			mut officers: Vec<T::AccountId>,
+			threshold: u16,
+		) -> DispatchResult {
+			let company = ensure_signed(origin)?;
+			ensure!(<AccountToCompany<T>>::contains_key(&company), Error::<T>::CompanyNotFound);
+			officers.sort();
+			officers.dedup();
+			ensure!(
+				threshold >= 2 && usize::from(threshold) <= officers.len(),
+				Error::<T>::InvalidMultisig
+			);
+
+			let multisig = T::MultiAccountId::multi_account_id(&officers, threshold);
+			ensure!(
+				<MultisigCompany<T>>::get(&multisig).map_or(true, |owner| owner == company),
+				Error::<T>::MultisigTaken
+			);
+			if let Some(previous) = <CompanyMultisig<T>>::get(&company) {
+				<MultisigCompany<T>>::remove(previous);
+			}
+			<CompanyMultisig<T>>::insert(&company, &multisig);
+			<MultisigCompany<T>>::insert(&multisig, &company);
+			Self::deposit_event(Event::CompanyMultisigSet { company, multisig, threshold });
+			Ok(())
+		}
+
+		/// Reject every dispatch of the call named `call`, e.g. `create_supply_contract`, until it
+		/// is unpaused.
+		///
+		/// The origin must be `PauseOrigin`. Enforcement is left to the runtime's call filter,
+		/// which should consult [`Pallet::is_paused`].
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1).ref_time())]
+		pub fn pause_call(origin: OriginFor<T>, call: Vec<u8>) -> DispatchResult {
+			T::PauseOrigin::ensure_origin(origin)?;
+			ensure!(
+				Call::<T>::get_call_names().iter().any(|name| name.as_bytes() == &call[..]),
+				Error::<T>::UnknownCall
+			);
+			ensure!(
+				&call[..] != b"pause_call" && &call[..] != b"unpause_call",
+				Error::<T>::UnpausableCall
+			);
+			ensure!(!<PausedCalls<T>>::contains_key(&call), Error::<T>::AlreadyPaused);
+
+			<PausedCalls<T>>::insert(&call, ());
+			Self::deposit_event(Event::CallPaused { call });
+			Ok(())
+		}
+
+		/// Allow the call named `call` to be dispatched again.
+		///
+		/// The origin must be `PauseOrigin`.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1).ref_time())]
+		pub fn unpause_call(origin: OriginFor<T>, call: Vec<u8>) -> DispatchResult {
+			T::PauseOrigin::ensure_origin(origin)?;
+			ensure!(<PausedCalls<T>>::contains_key(&call), Error::<T>::NotPaused);
+
+			<PausedCalls<T>>::remove(&call);
+			Self::deposit_event(Event::CallUnpaused { call });
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Update the status flags of the supply contract held by `owner`.
+		///
+		/// Shared by the `update_supply_contract_status` call and the runtime's contracts chain
+		/// extension. A contract cannot be fulfilled before it is delivered and neither flag can
+		/// be reset once set. Contracts above `MultisigThreshold` can only be fulfilled by the
+		/// multisig of a party, and contracts in escrow only by an `owner` acting for the buyer.
+		pub fn set_supply_contract_status(
+			owner: &T::AccountId,
+			delivered: bool,
+			contract_fulfilled: bool,
+		) -> DispatchResult {
+			Self::change_supply_contract_status(owner, owner, delivered, contract_fulfilled)
+		}
+
+		/// Update the status flags of the supply contract held by `owner` on behalf of `who`.
+		fn change_supply_contract_status(
+			owner: &T::AccountId,
+			who: &T::AccountId,
+			delivered: bool,
+			contract_fulfilled: bool,
+		) -> DispatchResult {
+			ensure!(
+				<AccountToSupplyContract<T>>::contains_key(owner),
+				Error::<T>::SupplyContractNotFound
+			);
+			let mut contract = <AccountToSupplyContract<T>>::get(owner);
+			ensure!(
+				(delivered || !contract.delivered) &&
+					(contract_fulfilled || !contract.contract_fulfilled) &&
+					(delivered || !contract_fulfilled),
+				Error::<T>::InvalidStatusTransition
+			);
+			if contract_fulfilled && !contract.contract_fulfilled {
+				Self::ensure_multisig_if_high_value(
+					who,
+					contract.contract_value,
+					&[contract.seller_id, contract.buyer_id],
+				)?;
+				// Releasing the escrow must not be up to the seller.
+				ensure!(
+					!contract.escrowed || Self::acts_for(who, contract.buyer_id),
+					Error::<T>::NotBuyer
+				);
+			}
+
+			contract.delivered = delivered;
+			contract.contract_fulfilled = contract_fulfilled;
+			Self::release_escrow_if_fulfilled(owner, &mut contract)?;
+			<AccountToSupplyContract<T>>::insert(owner, contract);
+			Self::deposit_event(Event::SupplyContractStatusChanged {
+				contract: owner.clone(),
+				delivered,
+				contract_fulfilled,
+			});
+			Ok(())
+		}
+
+		/// Whether `who` is the account registered to `company_id` or that company's multisig.
+		fn acts_for(who: &T::AccountId, company_id: u64) -> bool {
+			let company = Self::multisig_company(who).unwrap_or_else(|| who.clone());
+			Self::company_account(company_id).as_ref() == Some(&company)
+		}
+
+		/// Ensure `who` is the multisig of one of `company_ids` if `contract_value` is above
+		/// `MultisigThreshold`.
+		fn ensure_multisig_if_high_value(
+			who: &T::AccountId,
+			contract_value: u64,
+			company_ids: &[u64],
+		) -> DispatchResult {
+			if contract_value <= T::MultisigThreshold::get() {
+				return Ok(())
+			}
+			let company = Self::multisig_company(who).ok_or(Error::<T>::MultisigRequired)?;
+			ensure!(
+				company_ids.contains(&<AccountToCompany<T>>::get(&company).id),
+				Error::<T>::MultisigRequired
+			);
+			Ok(())
+		}
+
+		/// Check that `CompanyIdToAccount` is exactly the reverse of `AccountToCompany`.
+		#[cfg(feature = "try-runtime")]
+		pub fn do_try_state() -> Result<(), &'static str> {
+			for (account, company) in <AccountToCompany<T>>::iter() {
+				ensure!(
+					<CompanyIdToAccount<T>>::get(company.id).as_ref() == Some(&account),
+					"company missing from the id index"
+				);
+			}
+			for (id, account) in <CompanyIdToAccount<T>>::iter() {
+				ensure!(
+					<AccountToCompany<T>>::contains_key(&account) &&
+						<AccountToCompany<T>>::get(&account).id == id,
+					"id index points at the wrong company"
+				);
+			}
+			Ok(())
+		}
+
+		/// Whether governance has paused `call`.
+		pub fn is_paused(call: &Call<T>) -> bool {
+			<PausedCalls<T>>::contains_key(call.get_call_name().as_bytes())
+		}
+
+		/// The company registered to `who` along with its judged legal details, if any.
+		pub fn company_details(who: &T::AccountId) -> Option<CompanyDetails> {
+			if !<AccountToCompany<T>>::contains_key(who) {
+				return None
+			}
+			Some(CompanyDetails {
+				company: <AccountToCompany<T>>::get(who),
+				legal: T::Identity::legal_details(who),
+			})
+		}
+
+		/// The account holding the value of every funded, unfulfilled supply contract.
+		pub fn escrow_account() -> T::AccountId {
+			T::PalletId::get().into_account_truncating()
+		}
+
+		/// Pay the escrowed value of `contract` out to its seller once it is fulfilled.
+		fn release_escrow_if_fulfilled(
+			owner: &T::AccountId,
+			contract: &mut SupplyContract,
+		) -> DispatchResult {
+			if !contract.escrowed || !contract.contract_fulfilled {
+				return Ok(())
+			}
+			let seller =
+				Self::company_account(contract.seller_id).ok_or(Error::<T>::CompanyNotFound)?;
+			T::Assets::transfer(
+				contract.asset_id,
+				&Self::escrow_account(),
+				&seller,
+				contract.contract_value.into(),
+				false,
+			)?;
+			contract.escrowed = false;
+			Self::deposit_event(Event::EscrowReleased {
+				contract: owner.clone(),
+				seller,
+				amount: contract.contract_value,
+			});
+			Ok(())
+		}
+
+		/// Move `amount` of the IOU held by `iou` from `payer` to the creditor and return what is
+		/// still outstanding.
+		pub(crate) fn do_repay_iou(
+			iou: &T::AccountId,
+			payer: &T::AccountId,
+			amount: u64,
+		) -> Result<u64, DispatchError> {
+			let mut owed = <AccountToIOU<T>>::get(iou);
+			ensure!(amount > 0 && amount <= owed.amount, Error::<T>::InvalidRepayment);
+			let creditor =
+				Self::company_account(owed.creditor).ok_or(Error::<T>::CompanyNotFound)?;
+
+			T::Assets::transfer(owed.asset_id, payer, &creditor, amount.into(), false)?;
+			owed.amount -= amount;
+			let remaining = owed.amount;
+			if remaining == 0 {
+				Self::forget_iou(iou);
+				<AccountToIOU<T>>::remove(iou);
+			} else {
+				<AccountToIOU<T>>::insert(iou, owed);
+			}
+			Ok(remaining)
+		}
+
+		fn installment_task_id(iou: &T::AccountId) -> Vec<u8> {
+			(b"supchain/installment", iou).encode()
+		}
+
+		/// Drop the installment plan of `iou` and any installments still scheduled for it.
+		fn end_installment_plan(iou: &T::AccountId) {
+			<IOUInstallmentPlan<T>>::remove(iou);
+			// The scheduler has already dropped the task after its last run.
+			let _ = T::Scheduler::cancel_named(Self::installment_task_id(iou));
+		}
+
+		/// The installment that pays `amount` off in `installments` payments, rounded up so that
+		/// the plan never leaves a remainder after the last installment.
+		fn installment_of(amount: u64, installments: u32) -> u64 {
+			let count = u64::from(installments.max(1));
+			amount / count + u64::from(amount % count != 0)
+		}
+
+		/// Spread the installments still to come of the plan of `iou` over the `remaining` amount
+		/// after it was repaid or netted outside of the plan, ending the plan if nothing is left.
+		fn reschedule_installments(iou: &T::AccountId, remaining: u64) {
+			if remaining == 0 {
+				if <IOUInstallmentPlan<T>>::contains_key(iou) {
+					Self::end_installment_plan(iou);
+				}
+				return
+			}
+			<IOUInstallmentPlan<T>>::mutate(iou, |plan| {
+				if let Some(plan) = plan {
+					plan.installment = Self::installment_of(remaining, plan.remaining_installments);
+				}
+			});
+		}
+
+		/// Record which parties have confirmed the IOU `owed` held by `holder`, and make it
+		/// available to `net_ious` once both have.
+		fn record_confirmations(holder: &T::AccountId, owed: &IOU, confirmations: (bool, bool)) {
+			<IOUConfirmations<T>>::insert(holder, confirmations);
+			if confirmations == (true, true) {
+				<ConfirmedIOUs<T>>::insert((owed.debtor, owed.creditor, owed.asset_id), holder, ());
+			}
+		}
+
+		/// Drop the confirmations of the IOU held by `holder`, before it is replaced or removed.
+		fn forget_iou(holder: &T::AccountId) {
+			if let Ok(owed) = <AccountToIOU<T>>::try_get(holder) {
+				<ConfirmedIOUs<T>>::remove((owed.debtor, owed.creditor, owed.asset_id), holder);
+			}
+			<IOUConfirmations<T>>::remove(holder);
+		}
+
+		/// The confirmed IOUs of `debtor` to `creditor` in `asset_id`, at most `MaxNettedIOUs`.
+		fn confirmed_ious(
+			debtor: u64,
+			creditor: u64,
+			asset_id: AssetId,
+		) -> Vec<(T::AccountId, IOU)> {
+			<ConfirmedIOUs<T>>::iter_key_prefix((debtor, creditor, asset_id))
+				.take(T::MaxNettedIOUs::get() as usize)
+				.map(|holder| {
+					let iou = <AccountToIOU<T>>::get(&holder);
+					(holder, iou)
+				})
+				.filter(|(_, iou)| iou.amount > 0)
+				.collect()
+		}
+
+		fn total_owed(ious: &[(T::AccountId, IOU)]) -> u64 {
+			ious.iter().fold(0, |total, (_, iou)| total.saturating_add(iou.amount))
+		}
+
+		/// Reduce `ious` by `amount` in order, removing every IOU that is paid off completely.
+		fn offset_ious(ious: Vec<(T::AccountId, IOU)>, mut amount: u64) {
+			for (holder, mut iou) in ious {
+				if amount == 0 {
+					break
+				}
+				let offset = amount.min(iou.amount);
+				amount -= offset;
+				iou.amount -= offset;
+				let remaining = iou.amount;
+				if remaining == 0 {
+					Self::forget_iou(&holder);
+					<AccountToIOU<T>>::remove(&holder);
+				} else {
+					<AccountToIOU<T>>::insert(&holder, iou);
+				}
+				Self::reschedule_installments(&holder, remaining);
+			}
+		}
 	}
 }
@@ -0,0 +1,164 @@
+//! Storage migrations for the supply-chain pallet.
+
+use super::*;
+use frame_support::{
+	pallet_prelude::*,
+	traits::{OnRuntimeUpgrade, StorageVersion},
+};
+use sp_std::{collections::btree_map::BTreeMap, marker::PhantomData, vec::Vec};
+
+/// Migrates storage written before companies could be verified and contracts and IOUs were
+/// denominated in an asset.
+pub mod v1 {
+	use super::*;
+
+	/// `Company` as stored by storage version 0.
+	#[derive(Encode, Decode)]
+	struct OldCompany {
+		id: u64,
+		name: Vec<u8>,
+		about_me: Vec<u8>,
+	}
+
+	/// `SupplyContract` as stored by storage version 0.
+	#[derive(Encode, Decode)]
+	struct OldSupplyContract {
+		id: u64,
+		seller_id: u64,
+		buyer_id: u64,
+		products: Vec<u64>,
+		delivered: bool,
+		iou: u64,
+		contract_value: u64,
+		contract_fulfilled: bool,
+	}
+
+	/// `IOU` as stored by storage version 0.
+	#[derive(Encode, Decode)]
+	struct OldIOU {
+		id: u64,
+		debtor: u64,
+		creditor: u64,
+		amount: u64,
+	}
+
+	/// Adds the new fields with their defaults, denominates existing supply contracts and IOUs
+	/// in `LegacyAsset` and builds the `CompanyIdToAccount` index.
+	///
+	/// Version 0 let several accounts register the same company id. The smallest of those
+	/// accounts keeps the id; the companies of the others are moved to `DisplacedCompanies` and
+	/// logged, and their accounts have to register again with a free id.
+	///
+	/// Supply contracts are migrated as not escrowed: their value was never paid into the
+	/// pallet account.
+	pub struct MigrateToV1<T, LegacyAsset>(PhantomData<(T, LegacyAsset)>);
+
+	impl<T: Config, LegacyAsset: Get<AssetId>> OnRuntimeUpgrade for MigrateToV1<T, LegacyAsset> {
+		fn on_runtime_upgrade() -> Weight {
+			if StorageVersion::get::<Pallet<T>>() >= 1 {
+				log::info!(target: "runtime::template", "v1 migration already applied, skipping");
+				return T::DbWeight::get().reads(1)
+			}
+
+			let asset_id = LegacyAsset::get();
+			let mut translated = 0u64;
+			let mut owners = BTreeMap::<u64, T::AccountId>::new();
+			<AccountToCompany<T>>::translate::<OldCompany, _>(|account, old| {
+				translated += 1;
+				let owner = owners.entry(old.id).or_insert_with(|| account.clone());
+				if account < *owner {
+					*owner = account;
+				}
+				Some(Company {
+					id: old.id,
+					name: old.name,
+					about_me: old.about_me,
+					verified: false,
+				})
+			});
+			<AccountToSupplyContract<T>>::translate::<OldSupplyContract, _>(|_, old| {
+				translated += 1;
+				Some(SupplyContract {
+					id: old.id,
+					seller_id: old.seller_id,
+					buyer_id: old.buyer_id,
+					products: old.products,
+					delivered: old.delivered,
+					iou: old.iou,
+					contract_value: old.contract_value,
+					contract_fulfilled: old.contract_fulfilled,
+					asset_id,
+					escrowed: false,
+				})
+			});
+			<AccountToIOU<T>>::translate::<OldIOU, _>(|_, old| {
+				translated += 1;
+				Some(IOU {
+					id: old.id,
+					debtor: old.debtor,
+					creditor: old.creditor,
+					amount: old.amount,
+					asset_id,
+					delinquent: false,
+				})
+			});
+
+			let displaced: Vec<_> = <AccountToCompany<T>>::iter()
+				.filter(|(account, company)| owners.get(&company.id) != Some(account))
+				.collect();
+			for (account, company) in &displaced {
+				log::warn!(
+					target: "runtime::template",
+					"company id {} is registered to another account, moving the company of {:?} \
+					 to DisplacedCompanies",
+					company.id,
+					account,
+				);
+				<AccountToCompany<T>>::remove(account);
+				<DisplacedCompanies<T>>::insert(account, company);
+			}
+			for (id, account) in &owners {
+				<CompanyIdToAccount<T>>::insert(id, account);
+			}
+			StorageVersion::new(1).put::<Pallet<T>>();
+
+			log::info!(target: "runtime::template", "migrated {} values to v1", translated);
+			// Companies are read twice, and written again for the index or when displaced.
+			let companies = owners.len() as u64 + displaced.len() as u64;
+			T::DbWeight::get().reads_writes(
+				translated + companies + 1,
+				translated + owners.len() as u64 + 2 * displaced.len() as u64 + 1,
+			)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			let counts = (
+				<AccountToCompany<T>>::iter_keys().count() as u64,
+				<AccountToSupplyContract<T>>::iter_keys().count() as u64,
+				<AccountToIOU<T>>::iter_keys().count() as u64,
+			);
+			Ok(counts.encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let (companies, contracts, ious): (u64, u64, u64) =
+				Decode::decode(&mut &state[..]).map_err(|_| "invalid pre-upgrade state")?;
+			ensure!(StorageVersion::get::<Pallet<T>>() == 1, "storage version not updated");
+			// `iter` skips values that fail to decode, so a layout mismatch shows up as a count
+			// mismatch.
+			ensure!(
+				(<AccountToCompany<T>>::iter().count() + <DisplacedCompanies<T>>::iter().count())
+					as u64 == companies,
+				"companies lost in migration"
+			);
+			ensure!(
+				<AccountToSupplyContract<T>>::iter().count() as u64 == contracts,
+				"supply contracts lost in migration"
+			);
+			ensure!(<AccountToIOU<T>>::iter().count() as u64 == ious, "IOUs lost in migration");
+			Pallet::<T>::do_try_state()
+		}
+	}
+}
@@ -1,10 +1,16 @@
 use crate as pallet_template;
-use frame_support::traits::{ConstU16, ConstU64};
+use frame_support::{
+	parameter_types,
+	traits::{ConstU16, ConstU32, ConstU64, Contains, EqualPrivilegeOnly, OnInitialize},
+	weights::Weight,
+	PalletId,
+};
 use frame_system as system;
 use sp_core::H256;
 use sp_runtime::{
 	testing::Header,
 	traits::{BlakeTwo256, IdentityLookup},
+	BuildStorage,
 };
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
@@ -18,12 +24,15 @@ frame_support::construct_runtime!(
 		UncheckedExtrinsic = UncheckedExtrinsic,
 	{
 		System: frame_system,
+		Balances: pallet_balances,
+		Assets: pallet_assets,
+		Scheduler: pallet_scheduler,
 		TemplateModule: pallet_template,
 	}
 );
 
 impl system::Config for Test {
-	type BaseCallFilter = frame_support::traits::Everything;
+	type BaseCallFilter = PauseFilter;
 	type BlockWeights = ();
 	type BlockLength = ();
 	type DbWeight = ();
@@ -40,7 +49,7 @@ impl system::Config for Test {
 	type BlockHashCount = ConstU64<250>;
 	type Version = ();
 	type PalletInfo = PalletInfo;
-	type AccountData = ();
+	type AccountData = pallet_balances::AccountData<u64>;
 	type OnNewAccount = ();
 	type OnKilledAccount = ();
 	type SystemWeightInfo = ();
@@ -49,11 +58,147 @@ impl system::Config for Test {
 	type MaxConsumers = frame_support::traits::ConstU32<16>;
 }
 
+/// Rejects the calls of `TemplateModule` that governance has paused.
+pub struct PauseFilter;
+
+impl Contains<RuntimeCall> for PauseFilter {
+	fn contains(call: &RuntimeCall) -> bool {
+		match call {
+			RuntimeCall::TemplateModule(call) => !TemplateModule::is_paused(call),
+			_ => true,
+		}
+	}
+}
+
+impl pallet_balances::Config for Test {
+	type MaxLocks = ();
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+	type Balance = u64;
+	type RuntimeEvent = RuntimeEvent;
+	type DustRemoval = ();
+	type ExistentialDeposit = ConstU64<1>;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+impl pallet_assets::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type Balance = u64;
+	type AssetId = u32;
+	type Currency = Balances;
+	type ForceOrigin = frame_system::EnsureRoot<u64>;
+	type AssetDeposit = ConstU64<1>;
+	type AssetAccountDeposit = ConstU64<1>;
+	type MetadataDepositBase = ConstU64<1>;
+	type MetadataDepositPerByte = ConstU64<1>;
+	type ApprovalDeposit = ConstU64<1>;
+	type StringLimit = ConstU32<50>;
+	type Freezer = ();
+	type Extra = ();
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	pub MaximumSchedulerWeight: Weight = Weight::from_ref_time(1_000_000_000);
+}
+
+impl pallet_scheduler::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type RuntimeOrigin = RuntimeOrigin;
+	type PalletsOrigin = OriginCaller;
+	type RuntimeCall = RuntimeCall;
+	type MaximumWeight = MaximumSchedulerWeight;
+	type ScheduleOrigin = frame_system::EnsureRoot<u64>;
+	type MaxScheduledPerBlock = ConstU32<10>;
+	type WeightInfo = ();
+	type OriginPrivilegeCmp = EqualPrivilegeOnly;
+	type PreimageProvider = ();
+	type NoPreimagePostponement = ();
+}
+
+parameter_types! {
+	pub const SupplyChainPalletId: PalletId = PalletId(*b"supchain");
+	pub static RequireJudgedIdentity: bool = false;
+}
+
+/// Account whose identity a registrar has judged in tests.
+pub const JUDGED: u64 = 1;
+
+/// Identity provider that only knows the legal details of `JUDGED`.
+pub struct MockIdentity;
+
+impl pallet_template::CompanyIdentity<u64> for MockIdentity {
+	fn legal_details(who: &u64) -> Option<pallet_template::LegalDetails> {
+		(*who == JUDGED).then(|| pallet_template::LegalDetails {
+			legal_name: b"Acme Supplies Ltd".to_vec(),
+			website: b"https://acme.example".to_vec(),
+			registration_number: b"01234567".to_vec(),
+		})
+	}
+}
+
 impl pallet_template::Config for Test {
 	type RuntimeEvent = RuntimeEvent;
+	type VerifyOrigin = frame_system::EnsureRoot<u64>;
+	type ArbiterOrigin = frame_system::EnsureRoot<u64>;
+	type PauseOrigin = frame_system::EnsureRoot<u64>;
+	type AssetBalance = u64;
+	type Assets = Assets;
+	type PalletId = SupplyChainPalletId;
+	type RuntimeCall = RuntimeCall;
+	type PalletsOrigin = OriginCaller;
+	type Scheduler = Scheduler;
+	type Identity = MockIdentity;
+	type RequireJudgedIdentity = RequireJudgedIdentity;
+	type MultiAccountId = MockMultiAccountId;
+	type MultisigThreshold = ConstU64<MULTISIG_THRESHOLD>;
+	type MaxNettedIOUs = ConstU32<4>;
+}
+
+/// The asset that supply contracts and IOUs are denominated in during tests.
+pub const STABLECOIN: u32 = 1;
+
+/// Contract value above which supply contracts need a company multisig in tests.
+pub const MULTISIG_THRESHOLD: u64 = 500;
+
+/// Multisig of officers 2 and 3 with both approvals required.
+pub const OFFICERS_MULTISIG: u64 = 1007;
+
+/// Derives multisig accounts as `1000 + sum of signatories + threshold`.
+pub struct MockMultiAccountId;
+
+impl pallet_template::MultiAccountId<u64> for MockMultiAccountId {
+	fn multi_account_id(who: &[u64], threshold: u16) -> u64 {
+		1000 + who.iter().sum::<u64>() + u64::from(threshold)
+	}
 }
 
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext() -> sp_io::TestExternalities {
-	system::GenesisConfig::default().build_storage::<Test>().unwrap().into()
+	let mut storage = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> { balances: vec![(1, 100), (2, 100), (3, 100)] }
+		.assimilate_storage(&mut storage)
+		.unwrap();
+	pallet_assets::GenesisConfig::<Test> {
+		assets: vec![(STABLECOIN, 1, true, 1)],
+		metadata: vec![],
+		accounts: vec![
+			(STABLECOIN, 1, 1_000),
+			(STABLECOIN, 2, 1_000),
+			(STABLECOIN, 3, 1_000),
+			(STABLECOIN, OFFICERS_MULTISIG, 1_000),
+		],
+	}
+	.assimilate_storage(&mut storage)
+	.unwrap();
+	storage.into()
+}
+
+/// Advance to block `n`, running the scheduler's `on_initialize` for every block on the way.
+pub fn run_to_block(n: u64) {
+	while System::block_number() < n {
+		System::set_block_number(System::block_number() + 1);
+		Scheduler::on_initialize(System::block_number());
+	}
 }
@@ -1,4 +1,754 @@
-use crate::{mock::*, Error};
+use crate::{mock::*, Error, Event};
 use frame_support::{assert_noop, assert_ok};
+use sp_runtime::traits::Dispatchable;
+
+fn register(account: u64, id: u64) {
+	assert_ok!(TemplateModule::register_company(
+		RuntimeOrigin::signed(account),
+		b"company".to_vec(),
+		id,
+		Vec::new(),
+	));
+}
 
 // Unit tests go here.
+
+#[test]
+fn net_ious_closes_smaller_side_and_reduces_larger() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		register(1, 1);
+		register(2, 2);
+		assert_ok!(TemplateModule::create_iou(RuntimeOrigin::signed(1), 1, 1, 2, 100, STABLECOIN));
+		assert_ok!(TemplateModule::create_iou(RuntimeOrigin::signed(2), 2, 2, 1, 40, STABLECOIN));
+		assert_ok!(TemplateModule::confirm_iou(RuntimeOrigin::signed(2), 1));
+		assert_ok!(TemplateModule::confirm_iou(RuntimeOrigin::signed(1), 2));
+
+		assert_ok!(TemplateModule::net_ious(RuntimeOrigin::signed(1), 1, 2, STABLECOIN));
+
+		assert_eq!(TemplateModule::iou_info(1).amount, 60);
+		assert!(!crate::AccountToIOU::<Test>::contains_key(2));
+		assert_eq!(TemplateModule::iou_confirmations(2), (false, false));
+		System::assert_last_event(
+			Event::<Test>::Netted { company_a: 1, company_b: 2, asset_id: STABLECOIN, amount: 40 }
+				.into(),
+		);
+	});
+}
+
+#[test]
+fn net_ious_ignores_ious_the_debtor_never_confirmed() {
+	new_test_ext().execute_with(|| {
+		register(1, 1);
+		register(2, 2);
+		// A real debt of company 1 to company 2, confirmed by both.
+		assert_ok!(TemplateModule::create_iou(RuntimeOrigin::signed(2), 1, 1, 2, 100, STABLECOIN));
+		assert_ok!(TemplateModule::confirm_iou(RuntimeOrigin::signed(1), 2));
+		// A claim company 1 made up against company 2.
+		assert_ok!(TemplateModule::create_iou(RuntimeOrigin::signed(1), 2, 2, 1, 100, STABLECOIN));
+		assert_eq!(TemplateModule::iou_confirmations(1), (false, true));
+
+		assert_noop!(
+			TemplateModule::confirm_iou(RuntimeOrigin::signed(3), 1),
+			Error::<Test>::NotPartyToIOU
+		);
+		assert_noop!(
+			TemplateModule::net_ious(RuntimeOrigin::signed(1), 1, 2, STABLECOIN),
+			Error::<Test>::NothingToNet
+		);
+		assert_eq!(TemplateModule::iou_info(2).amount, 100);
+	});
+}
+
+#[test]
+fn net_ious_requires_opposing_ious() {
+	new_test_ext().execute_with(|| {
+		register(1, 1);
+		register(2, 2);
+		assert_ok!(TemplateModule::create_iou(RuntimeOrigin::signed(1), 1, 1, 2, 100, STABLECOIN));
+
+		assert_noop!(
+			TemplateModule::net_ious(RuntimeOrigin::signed(1), 1, 2, STABLECOIN),
+			Error::<Test>::NothingToNet
+		);
+	});
+}
+
+#[test]
+fn net_ious_must_be_called_by_a_party() {
+	new_test_ext().execute_with(|| {
+		register(1, 1);
+		register(3, 3);
+
+		assert_noop!(
+			TemplateModule::net_ious(RuntimeOrigin::signed(3), 1, 2, STABLECOIN),
+			Error::<Test>::NotPartyToIOUs
+		);
+		assert_noop!(
+			TemplateModule::net_ious(RuntimeOrigin::signed(1), 1, 1, STABLECOIN),
+			Error::<Test>::CannotNetWithSelf
+		);
+		// An unregistered caller must not pass as company 0.
+		assert_noop!(
+			TemplateModule::net_ious(RuntimeOrigin::signed(4), 0, 1, STABLECOIN),
+			Error::<Test>::CompanyNotFound
+		);
+	});
+}
+
+#[test]
+fn supply_contract_status_only_moves_forward() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			TemplateModule::update_supply_contract_status(RuntimeOrigin::signed(1), true, false),
+			Error::<Test>::SupplyContractNotFound
+		);
+		assert_ok!(TemplateModule::create_supply_contract(
+			RuntimeOrigin::signed(1),
+			1,
+			1,
+			2,
+			vec![1],
+			false,
+			0,
+			500,
+			false,
+			STABLECOIN,
+		));
+
+		assert_noop!(
+			TemplateModule::update_supply_contract_status(RuntimeOrigin::signed(1), false, true),
+			Error::<Test>::InvalidStatusTransition
+		);
+		assert_ok!(TemplateModule::update_supply_contract_status(
+			RuntimeOrigin::signed(1),
+			true,
+			false
+		));
+		assert_ok!(TemplateModule::update_supply_contract_status(
+			RuntimeOrigin::signed(1),
+			true,
+			true
+		));
+		assert_noop!(
+			TemplateModule::update_supply_contract_status(RuntimeOrigin::signed(1), false, false),
+			Error::<Test>::InvalidStatusTransition
+		);
+		assert!(TemplateModule::supply_contract_info(1).contract_fulfilled);
+	});
+}
+
+#[test]
+fn holder_names_and_revokes_status_delegate() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			TemplateModule::set_supply_contract_delegate(RuntimeOrigin::signed(1), Some(9)),
+			Error::<Test>::SupplyContractNotFound
+		);
+		create_contract(1, 1, 2, 300);
+
+		assert_ok!(TemplateModule::set_supply_contract_delegate(RuntimeOrigin::signed(1), Some(9)));
+		assert_eq!(TemplateModule::supply_contract_delegate(1), Some(9));
+		assert_ok!(TemplateModule::set_supply_contract_delegate(RuntimeOrigin::signed(1), None));
+		assert_eq!(TemplateModule::supply_contract_delegate(1), None);
+	});
+}
+
+#[test]
+fn verify_company_requires_verify_origin() {
+	new_test_ext().execute_with(|| {
+		register(1, 1);
+
+		assert_noop!(
+			TemplateModule::verify_company(RuntimeOrigin::signed(1), 1, true),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_noop!(
+			TemplateModule::verify_company(RuntimeOrigin::root(), 2, true),
+			Error::<Test>::CompanyNotFound
+		);
+		assert_ok!(TemplateModule::verify_company(RuntimeOrigin::root(), 1, true));
+		assert!(TemplateModule::company_info(1).verified);
+	});
+}
+
+#[test]
+fn arbiter_can_move_contract_back() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(TemplateModule::create_supply_contract(
+			RuntimeOrigin::signed(1),
+			1,
+			1,
+			2,
+			vec![1],
+			true,
+			0,
+			500,
+			true,
+			STABLECOIN,
+		));
+
+		assert_noop!(
+			TemplateModule::arbitrate_supply_contract(RuntimeOrigin::signed(2), 1, false, false),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_ok!(TemplateModule::arbitrate_supply_contract(
+			RuntimeOrigin::root(),
+			1,
+			true,
+			false
+		));
+		assert!(!TemplateModule::supply_contract_info(1).contract_fulfilled);
+	});
+}
+
+fn create_contract(holder: u64, seller_id: u64, buyer_id: u64, value: u64) {
+	assert_ok!(TemplateModule::create_supply_contract(
+		RuntimeOrigin::signed(holder),
+		1,
+		seller_id,
+		buyer_id,
+		vec![1],
+		false,
+		0,
+		value,
+		false,
+		STABLECOIN,
+	));
+}
+
+#[test]
+fn register_company_rejects_taken_id() {
+	new_test_ext().execute_with(|| {
+		register(1, 1);
+
+		assert_noop!(
+			TemplateModule::register_company(RuntimeOrigin::signed(2), b"copy".to_vec(), 1, vec![]),
+			Error::<Test>::CompanyIdTaken
+		);
+		register(1, 5);
+		assert_eq!(TemplateModule::company_account(1), None);
+		assert_eq!(TemplateModule::company_account(5), Some(1));
+	});
+}
+
+#[test]
+fn register_company_can_require_judged_identity() {
+	new_test_ext().execute_with(|| {
+		RequireJudgedIdentity::set(true);
+
+		assert_noop!(
+			TemplateModule::register_company(RuntimeOrigin::signed(2), b"anon".to_vec(), 2, vec![]),
+			Error::<Test>::IdentityNotJudged
+		);
+		register(JUDGED, 1);
+	});
+}
+
+#[test]
+fn company_details_expose_judged_legal_details() {
+	new_test_ext().execute_with(|| {
+		register(JUDGED, 1);
+		register(2, 2);
+
+		let legal = TemplateModule::company_details(&JUDGED).unwrap().legal.unwrap();
+		assert_eq!(legal.legal_name, b"Acme Supplies Ltd".to_vec());
+		assert_eq!(legal.registration_number, b"01234567".to_vec());
+		assert_eq!(TemplateModule::company_details(&2).unwrap().legal, None);
+		assert!(TemplateModule::company_details(&3).is_none());
+	});
+}
+
+#[test]
+fn escrow_is_released_to_seller_on_fulfilment() {
+	new_test_ext().execute_with(|| {
+		register(1, 1);
+		register(2, 2);
+		create_contract(1, 1, 2, 300);
+
+		assert_noop!(
+			TemplateModule::fund_supply_contract(RuntimeOrigin::signed(1), 1),
+			Error::<Test>::NotBuyer
+		);
+		assert_ok!(TemplateModule::fund_supply_contract(RuntimeOrigin::signed(2), 1));
+		assert_eq!(Assets::balance(STABLECOIN, 2), 700);
+		assert_eq!(Assets::balance(STABLECOIN, TemplateModule::escrow_account()), 300);
+		assert_noop!(
+			TemplateModule::fund_supply_contract(RuntimeOrigin::signed(2), 1),
+			Error::<Test>::AlreadyEscrowed
+		);
+
+		assert_ok!(TemplateModule::confirm_delivery(RuntimeOrigin::signed(2), 1));
+		assert_eq!(Assets::balance(STABLECOIN, 1), 1_300);
+		assert_eq!(Assets::balance(STABLECOIN, TemplateModule::escrow_account()), 0);
+		let contract = TemplateModule::supply_contract_info(1);
+		assert!(!contract.escrowed && contract.delivered && contract.contract_fulfilled);
+	});
+}
+
+#[test]
+fn seller_cannot_release_escrow() {
+	new_test_ext().execute_with(|| {
+		register(1, 1);
+		register(2, 2);
+		create_contract(1, 1, 2, 300);
+		assert_ok!(TemplateModule::fund_supply_contract(RuntimeOrigin::signed(2), 1));
+
+		assert_ok!(TemplateModule::update_supply_contract_status(
+			RuntimeOrigin::signed(1),
+			true,
+			false
+		));
+		assert_noop!(
+			TemplateModule::update_supply_contract_status(RuntimeOrigin::signed(1), true, true),
+			Error::<Test>::NotBuyer
+		);
+		assert_noop!(
+			TemplateModule::confirm_delivery(RuntimeOrigin::signed(1), 1),
+			Error::<Test>::NotBuyer
+		);
+		assert_noop!(
+			TemplateModule::confirm_delivery(RuntimeOrigin::signed(3), 1),
+			Error::<Test>::NotBuyer
+		);
+		assert_eq!(Assets::balance(STABLECOIN, TemplateModule::escrow_account()), 300);
+
+		// The arbiter may still rule that the contract was fulfilled.
+		assert_ok!(TemplateModule::arbitrate_supply_contract(RuntimeOrigin::root(), 1, true, true));
+		assert_eq!(Assets::balance(STABLECOIN, 1), 1_300);
+	});
+}
+
+#[test]
+fn escrowed_contract_cannot_be_replaced() {
+	new_test_ext().execute_with(|| {
+		register(1, 1);
+		register(2, 2);
+		create_contract(1, 1, 2, 300);
+		assert_ok!(TemplateModule::fund_supply_contract(RuntimeOrigin::signed(2), 1));
+
+		assert_noop!(
+			TemplateModule::create_supply_contract(
+				RuntimeOrigin::signed(1),
+				2,
+				1,
+				2,
+				vec![1],
+				false,
+				0,
+				10,
+				false,
+				STABLECOIN,
+			),
+			Error::<Test>::SupplyContractEscrowed
+		);
+
+		assert_ok!(TemplateModule::confirm_delivery(RuntimeOrigin::signed(2), 1));
+		create_contract(1, 1, 2, 10);
+	});
+}
+
+#[test]
+fn repay_iou_pays_creditor_and_closes_iou() {
+	new_test_ext().execute_with(|| {
+		register(1, 1);
+		register(2, 2);
+		assert_ok!(TemplateModule::create_iou(RuntimeOrigin::signed(2), 1, 1, 2, 100, STABLECOIN));
+
+		assert_noop!(
+			TemplateModule::repay_iou(RuntimeOrigin::signed(2), 2, 50),
+			Error::<Test>::NotDebtor
+		);
+		assert_noop!(
+			TemplateModule::repay_iou(RuntimeOrigin::signed(1), 2, 101),
+			Error::<Test>::InvalidRepayment
+		);
+		assert_ok!(TemplateModule::repay_iou(RuntimeOrigin::signed(1), 2, 60));
+		assert_eq!(TemplateModule::iou_info(2).amount, 40);
+		assert_ok!(TemplateModule::repay_iou(RuntimeOrigin::signed(1), 2, 40));
+
+		assert!(!crate::AccountToIOU::<Test>::contains_key(2));
+		assert_eq!(Assets::balance(STABLECOIN, 1), 900);
+		assert_eq!(Assets::balance(STABLECOIN, 2), 1_100);
+	});
+}
+
+#[test]
+fn installments_are_debited_on_schedule() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		register(1, 1);
+		register(2, 2);
+		assert_ok!(TemplateModule::create_iou(RuntimeOrigin::signed(2), 1, 1, 2, 100, STABLECOIN));
+
+		assert_noop!(
+			TemplateModule::create_installment_plan(RuntimeOrigin::signed(2), 2, 2, 5),
+			Error::<Test>::NotDebtor
+		);
+		assert_noop!(
+			TemplateModule::create_installment_plan(RuntimeOrigin::signed(1), 2, 0, 5),
+			Error::<Test>::InvalidInstallmentPlan
+		);
+		assert_ok!(TemplateModule::create_installment_plan(RuntimeOrigin::signed(1), 2, 3, 5));
+		assert_noop!(
+			TemplateModule::pay_installment(RuntimeOrigin::signed(1), 2),
+			Error::<Test>::InstallmentNotDue
+		);
+
+		run_to_block(6);
+		assert_eq!(TemplateModule::iou_info(2).amount, 66);
+		run_to_block(11);
+		assert_eq!(TemplateModule::iou_info(2).amount, 32);
+		run_to_block(16);
+		assert!(!crate::AccountToIOU::<Test>::contains_key(2));
+		assert!(TemplateModule::installment_plan(2).is_none());
+		assert_eq!(Assets::balance(STABLECOIN, 2), 1_100);
+	});
+}
+
+#[test]
+fn iou_with_a_plan_cannot_be_replaced() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		register(1, 1);
+		register(2, 2);
+		assert_ok!(TemplateModule::create_iou(RuntimeOrigin::signed(2), 1, 1, 2, 100, STABLECOIN));
+		assert_ok!(TemplateModule::create_installment_plan(RuntimeOrigin::signed(1), 2, 2, 5));
+
+		assert_noop!(
+			TemplateModule::create_iou(RuntimeOrigin::signed(2), 1, 1, 2, 900, STABLECOIN),
+			Error::<Test>::InstallmentPlanActive
+		);
+		run_to_block(11);
+		assert!(!crate::AccountToIOU::<Test>::contains_key(2));
+		assert_eq!(Assets::balance(STABLECOIN, 1), 900);
+		assert_eq!(Assets::balance(STABLECOIN, 2), 1_100);
+
+		// Once the plan has ended, the holder may issue a new IOU.
+		assert_ok!(TemplateModule::create_iou(RuntimeOrigin::signed(2), 1, 1, 2, 900, STABLECOIN));
+	});
+}
+
+#[test]
+fn missed_installment_marks_iou_delinquent() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		register(1, 1);
+		register(2, 2);
+		assert_ok!(TemplateModule::create_iou(
+			RuntimeOrigin::signed(2),
+			1,
+			1,
+			2,
+			4_000,
+			STABLECOIN
+		));
+		assert_ok!(TemplateModule::create_installment_plan(RuntimeOrigin::signed(1), 2, 2, 5));
+
+		run_to_block(6);
+		let iou = TemplateModule::iou_info(2);
+		assert!(iou.delinquent);
+		assert_eq!(iou.amount, 4_000);
+		assert_eq!(TemplateModule::installment_plan(2).unwrap().missed, 1);
+		System::assert_has_event(Event::<Test>::InstallmentMissed { iou: 2, amount: 2_000 }.into());
+	});
+}
+
+#[test]
+fn repaying_outside_the_plan_lowers_the_installments() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		register(1, 1);
+		register(2, 2);
+		assert_ok!(TemplateModule::create_iou(RuntimeOrigin::signed(2), 1, 1, 2, 100, STABLECOIN));
+		assert_ok!(TemplateModule::create_installment_plan(RuntimeOrigin::signed(1), 2, 3, 5));
+		assert_eq!(TemplateModule::installment_plan(2).unwrap().installment, 34);
+
+		assert_ok!(TemplateModule::repay_iou(RuntimeOrigin::signed(1), 2, 70));
+		assert_eq!(TemplateModule::installment_plan(2).unwrap().installment, 10);
+		run_to_block(6);
+		assert_eq!(TemplateModule::iou_info(2).amount, 20);
+		assert!(!TemplateModule::iou_info(2).delinquent);
+	});
+}
+
+#[test]
+fn netting_an_iou_away_ends_its_plan() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		register(1, 1);
+		register(2, 2);
+		assert_ok!(TemplateModule::create_iou(RuntimeOrigin::signed(2), 1, 1, 2, 100, STABLECOIN));
+		assert_ok!(TemplateModule::confirm_iou(RuntimeOrigin::signed(1), 2));
+		assert_ok!(TemplateModule::create_installment_plan(RuntimeOrigin::signed(1), 2, 2, 5));
+		assert_ok!(TemplateModule::create_iou(RuntimeOrigin::signed(1), 2, 2, 1, 100, STABLECOIN));
+		assert_ok!(TemplateModule::confirm_iou(RuntimeOrigin::signed(2), 1));
+
+		assert_ok!(TemplateModule::net_ious(RuntimeOrigin::signed(1), 1, 2, STABLECOIN));
+		assert!(TemplateModule::installment_plan(2).is_none());
+		run_to_block(6);
+		assert_eq!(Assets::balance(STABLECOIN, 1), 1_000);
+		assert_eq!(Assets::balance(STABLECOIN, 2), 1_000);
+	});
+}
+
+#[test]
+fn company_can_sponsor_and_release_staff() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_noop!(
+			TemplateModule::sponsor_staff(RuntimeOrigin::signed(1), 3),
+			Error::<Test>::CompanyNotFound
+		);
+		register(1, 1);
+		register(2, 2);
+
+		assert_ok!(TemplateModule::sponsor_staff(RuntimeOrigin::signed(1), 3));
+		assert_eq!(TemplateModule::sponsor_of(3), Some(1));
+		assert_noop!(
+			TemplateModule::sponsor_staff(RuntimeOrigin::signed(2), 3),
+			Error::<Test>::AlreadySponsored
+		);
+		assert_noop!(
+			TemplateModule::unsponsor_staff(RuntimeOrigin::signed(2), 3),
+			Error::<Test>::NotSponsor
+		);
+
+		assert_ok!(TemplateModule::unsponsor_staff(RuntimeOrigin::signed(3), 3));
+		assert_eq!(TemplateModule::sponsor_of(3), None);
+		System::assert_last_event(Event::<Test>::StaffUnsponsored { company: 1, staff: 3 }.into());
+	});
+}
+
+#[test]
+fn paused_calls_are_filtered() {
+	new_test_ext().execute_with(|| {
+		let call = || {
+			RuntimeCall::TemplateModule(crate::Call::register_company {
+				name: b"company".to_vec(),
+				id: 1,
+				about_me: vec![],
+			})
+		};
+		assert_noop!(
+			TemplateModule::pause_call(RuntimeOrigin::signed(1), b"register_company".to_vec()),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_noop!(
+			TemplateModule::pause_call(RuntimeOrigin::root(), b"no_such_call".to_vec()),
+			Error::<Test>::UnknownCall
+		);
+		assert_noop!(
+			TemplateModule::pause_call(RuntimeOrigin::root(), b"unpause_call".to_vec()),
+			Error::<Test>::UnpausableCall
+		);
+
+		assert_ok!(TemplateModule::pause_call(RuntimeOrigin::root(), b"register_company".to_vec()));
+		assert_noop!(
+			call().dispatch(RuntimeOrigin::signed(1)),
+			frame_system::Error::<Test>::CallFiltered
+		);
+		assert_ok!(RuntimeCall::Balances(pallet_balances::Call::transfer { dest: 2, value: 10 })
+			.dispatch(RuntimeOrigin::signed(1)));
+
+		assert_ok!(TemplateModule::unpause_call(
+			RuntimeOrigin::root(),
+			b"register_company".to_vec()
+		));
+		assert_ok!(call().dispatch(RuntimeOrigin::signed(1)));
+	});
+}
+
+#[test]
+fn high_value_contracts_need_company_multisig() {
+	new_test_ext().execute_with(|| {
+		register(1, 1);
+		register(2, 2);
+		let value = MULTISIG_THRESHOLD + 1;
+		assert_noop!(
+			TemplateModule::set_company_multisig(RuntimeOrigin::signed(2), vec![3], 2),
+			Error::<Test>::InvalidMultisig
+		);
+		assert_ok!(TemplateModule::set_company_multisig(RuntimeOrigin::signed(2), vec![3, 2], 2));
+		assert_eq!(TemplateModule::company_multisig(2), Some(OFFICERS_MULTISIG));
+
+		assert_noop!(
+			TemplateModule::create_supply_contract(
+				RuntimeOrigin::signed(2),
+				1,
+				1,
+				2,
+				vec![1],
+				false,
+				0,
+				value,
+				false,
+				STABLECOIN,
+			),
+			Error::<Test>::MultisigRequired
+		);
+		create_contract(OFFICERS_MULTISIG, 1, 2, value);
+
+		assert_noop!(
+			TemplateModule::fund_supply_contract(RuntimeOrigin::signed(2), OFFICERS_MULTISIG),
+			Error::<Test>::MultisigRequired
+		);
+		assert_ok!(TemplateModule::fund_supply_contract(
+			RuntimeOrigin::signed(OFFICERS_MULTISIG),
+			OFFICERS_MULTISIG
+		));
+		assert_eq!(Assets::balance(STABLECOIN, OFFICERS_MULTISIG), 1_000 - value);
+
+		assert_ok!(TemplateModule::update_supply_contract_status(
+			RuntimeOrigin::signed(OFFICERS_MULTISIG),
+			true,
+			true
+		));
+		assert_eq!(Assets::balance(STABLECOIN, 1), 1_000 + value);
+	});
+}
+
+#[test]
+fn company_cannot_take_over_another_company_multisig() {
+	new_test_ext().execute_with(|| {
+		register(1, 1);
+		register(2, 2);
+		assert_ok!(TemplateModule::set_company_multisig(RuntimeOrigin::signed(2), vec![2, 3], 2));
+
+		assert_noop!(
+			TemplateModule::set_company_multisig(RuntimeOrigin::signed(1), vec![3, 2], 2),
+			Error::<Test>::MultisigTaken
+		);
+		assert_eq!(TemplateModule::multisig_company(OFFICERS_MULTISIG), Some(2));
+		assert_eq!(TemplateModule::company_multisig(1), None);
+
+		// Registering the same multisig again is fine.
+		assert_ok!(TemplateModule::set_company_multisig(RuntimeOrigin::signed(2), vec![3, 2], 2));
+		assert_eq!(TemplateModule::company_multisig(2), Some(OFFICERS_MULTISIG));
+	});
+}
+
+#[test]
+fn v1_migration_translates_legacy_values() {
+	use frame_support::traits::{ConstU32, OnRuntimeUpgrade, StorageVersion};
+
+	new_test_ext().execute_with(|| {
+		StorageVersion::new(0).put::<TemplateModule>();
+		let legacy_company = (7u64, b"old".to_vec(), b"about".to_vec());
+		frame_support::storage::unhashed::put(
+			&crate::AccountToCompany::<Test>::hashed_key_for(1),
+			&legacy_company,
+		);
+		let legacy_iou = (3u64, 7u64, 8u64, 250u64);
+		frame_support::storage::unhashed::put(
+			&crate::AccountToIOU::<Test>::hashed_key_for(1),
+			&legacy_iou,
+		);
+
+		crate::migrations::v1::MigrateToV1::<Test, ConstU32<STABLECOIN>>::on_runtime_upgrade();
+
+		let company = TemplateModule::company_info(1);
+		assert_eq!((company.id, company.name, company.verified), (7, b"old".to_vec(), false));
+		assert_eq!(TemplateModule::company_account(7), Some(1));
+		let iou = TemplateModule::iou_info(1);
+		assert_eq!((iou.amount, iou.asset_id, iou.delinquent), (250, STABLECOIN, false));
+		assert_eq!(StorageVersion::get::<TemplateModule>(), 1);
+	});
+}
+
+#[test]
+fn v1_migration_keeps_one_owner_per_company_id() {
+	use frame_support::traits::{ConstU32, OnRuntimeUpgrade, StorageVersion};
+
+	new_test_ext().execute_with(|| {
+		StorageVersion::new(0).put::<TemplateModule>();
+		for account in [3u64, 1, 2] {
+			frame_support::storage::unhashed::put(
+				&crate::AccountToCompany::<Test>::hashed_key_for(account),
+				&(7u64, b"same id".to_vec(), Vec::<u8>::new()),
+			);
+		}
+
+		crate::migrations::v1::MigrateToV1::<Test, ConstU32<STABLECOIN>>::on_runtime_upgrade();
+
+		assert_eq!(TemplateModule::company_account(7), Some(1));
+		assert_eq!(TemplateModule::company_info(1).id, 7);
+		for displaced in [2, 3] {
+			assert!(!crate::AccountToCompany::<Test>::contains_key(displaced));
+			assert_eq!(TemplateModule::displaced_company(displaced).unwrap().id, 7);
+		}
+
+		// A displaced account registers again with a free id.
+		register(2, 8);
+		assert!(TemplateModule::displaced_company(2).is_none());
+	});
+}
+
+#[cfg(feature = "try-runtime")]
+#[test]
+fn v1_migration_passes_its_try_runtime_checks() {
+	use frame_support::traits::{ConstU32, OnRuntimeUpgrade, StorageVersion};
+	type Migration = crate::migrations::v1::MigrateToV1<Test, ConstU32<STABLECOIN>>;
+
+	new_test_ext().execute_with(|| {
+		StorageVersion::new(0).put::<TemplateModule>();
+		for account in [1u64, 2] {
+			frame_support::storage::unhashed::put(
+				&crate::AccountToCompany::<Test>::hashed_key_for(account),
+				&(7u64, b"same id".to_vec(), Vec::<u8>::new()),
+			);
+		}
+		frame_support::storage::unhashed::put(
+			&crate::AccountToSupplyContract::<Test>::hashed_key_for(1),
+			&(4u64, 7u64, 8u64, vec![1u64], false, 3u64, 250u64, false),
+		);
+		frame_support::storage::unhashed::put(
+			&crate::AccountToIOU::<Test>::hashed_key_for(1),
+			&(3u64, 7u64, 8u64, 250u64),
+		);
+
+		let state = Migration::pre_upgrade().unwrap();
+		Migration::on_runtime_upgrade();
+		assert_ok!(Migration::post_upgrade(state));
+
+		let contract = TemplateModule::supply_contract_info(1);
+		assert_eq!(
+			(contract.contract_value, contract.asset_id, contract.escrowed),
+			(250, STABLECOIN, false)
+		);
+	});
+}
+
+#[cfg(feature = "try-runtime")]
+#[test]
+fn v1_migration_try_runtime_checks_catch_lost_values() {
+	use frame_support::traits::{ConstU32, OnRuntimeUpgrade, StorageVersion};
+	type Migration = crate::migrations::v1::MigrateToV1<Test, ConstU32<STABLECOIN>>;
+
+	new_test_ext().execute_with(|| {
+		StorageVersion::new(0).put::<TemplateModule>();
+		// Not a version 0 supply contract, so the migration drops it.
+		frame_support::storage::unhashed::put(
+			&crate::AccountToSupplyContract::<Test>::hashed_key_for(1),
+			&(4u64, 7u64),
+		);
+
+		let state = Migration::pre_upgrade().unwrap();
+		Migration::on_runtime_upgrade();
+		assert_eq!(Migration::post_upgrade(state), Err("supply contracts lost in migration"));
+	});
+}
+
+#[test]
+fn genesis_companies_start_verified() {
+	use frame_support::traits::GenesisBuild;
+
+	let mut storage = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	crate::GenesisConfig::<Test> { companies: vec![(1, 7, b"founder".to_vec(), Vec::new())] }
+		.assimilate_storage(&mut storage)
+		.unwrap();
+	sp_io::TestExternalities::from(storage).execute_with(|| {
+		assert!(TemplateModule::company_info(1).verified);
+		assert_eq!(TemplateModule::company_account(7), Some(1));
+	});
+}
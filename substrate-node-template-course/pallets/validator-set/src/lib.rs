@@ -0,0 +1,209 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+/// Lets governance add and remove the validators of the consortium's proof-of-authority network.
+/// The pallet is the `SessionManager` of `pallet_session`, which hands the validator set to Aura
+/// and Grandpa at the next session change. It also holds the block time and the SS58 prefix chosen
+/// at genesis.
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+pub mod migrations;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+	use sp_runtime::SaturatedConversion;
+	use sp_std::{marker::PhantomData, prelude::*};
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::without_storage_info]
+	pub struct Pallet<T>(_);
+
+	/// Configure the pallet by specifying the parameters and types on which it depends.
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// Because this pallet emits events, it depends on the runtime's definition of an event.
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+		/// Origin allowed to add and remove validators.
+		type AddRemoveOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// Validators that must remain after a removal.
+		#[pallet::constant]
+		type MinAuthorities: Get<u32>;
+
+		/// Block time in milliseconds used when the genesis config does not set one.
+		#[pallet::constant]
+		type DefaultBlockTime: Get<u64>;
+
+		/// SS58 prefix used when the genesis config does not set one.
+		#[pallet::constant]
+		type DefaultSs58Prefix: Get<u16>;
+	}
+
+	/// The validators of the next session that `pallet_session` starts.
+	#[pallet::storage]
+	#[pallet::getter(fn validators)]
+	pub type Validators<T: Config> = StorageValue<_, Vec<T::AccountId>, ValueQuery>;
+
+	/// Whether `Validators` changed since it was last handed to `pallet_session`.
+	#[pallet::storage]
+	pub type ValidatorsChanged<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+	/// Target time between two blocks, in milliseconds. Fixed at genesis: changing the slot
+	/// duration of a running chain would stop block production.
+	#[pallet::storage]
+	#[pallet::getter(fn block_time)]
+	pub type BlockTime<T: Config> = StorageValue<_, u64, ValueQuery, T::DefaultBlockTime>;
+
+	/// Prefix of the SS58 addresses of the chain's accounts, fixed at genesis.
+	#[pallet::storage]
+	#[pallet::getter(fn ss58_prefix)]
+	pub type Ss58Prefix<T: Config> = StorageValue<_, u16, ValueQuery, T::DefaultSs58Prefix>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		pub initial_validators: Vec<T::AccountId>,
+		pub block_time: u64,
+		pub ss58_prefix: u16,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self {
+				initial_validators: Vec::new(),
+				block_time: T::DefaultBlockTime::get(),
+				ss58_prefix: T::DefaultSs58Prefix::get(),
+			}
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			assert!(self.block_time > 0, "block time must be positive");
+			let mut validators = self.initial_validators.clone();
+			validators.sort();
+			validators.dedup();
+			assert!(
+				validators.len() == self.initial_validators.len(),
+				"duplicate initial validators"
+			);
+
+			<Validators<T>>::put(&self.initial_validators);
+			<BlockTime<T>>::put(self.block_time);
+			<Ss58Prefix<T>>::put(self.ss58_prefix);
+		}
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// A validator was added and takes part from the next session on.
+		ValidatorAdded { validator: T::AccountId },
+		/// A validator was removed and stops taking part from the next session on.
+		ValidatorRemoved { validator: T::AccountId },
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The account is already a validator.
+		Duplicate,
+		/// The account is not a validator.
+		NotValidator,
+		/// Removing the validator would leave fewer than `MinAuthorities`.
+		TooFewValidators,
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Add `validator` to the validator set.
+		///
+		/// The origin must be `AddRemoveOrigin`. The validator starts producing blocks two
+		/// sessions later, once it has set its session keys with `Session::set_keys`.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 2).ref_time())]
+		pub fn add_validator(origin: OriginFor<T>, validator: T::AccountId) -> DispatchResult {
+			T::AddRemoveOrigin::ensure_origin(origin)?;
+			let mut validators = <Validators<T>>::get();
+			ensure!(!validators.contains(&validator), Error::<T>::Duplicate);
+
+			validators.push(validator.clone());
+			<Validators<T>>::put(validators);
+			<ValidatorsChanged<T>>::put(true);
+			Self::deposit_event(Event::ValidatorAdded { validator });
+			Ok(())
+		}
+
+		/// Remove `validator` from the validator set.
+		///
+		/// The origin must be `AddRemoveOrigin`.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 2).ref_time())]
+		pub fn remove_validator(origin: OriginFor<T>, validator: T::AccountId) -> DispatchResult {
+			T::AddRemoveOrigin::ensure_origin(origin)?;
+			let mut validators = <Validators<T>>::get();
+			let index =
+				validators.iter().position(|v| v == &validator).ok_or(Error::<T>::NotValidator)?;
+			ensure!(
+				validators.len() > T::MinAuthorities::get() as usize,
+				Error::<T>::TooFewValidators
+			);
+
+			validators.remove(index);
+			<Validators<T>>::put(validators);
+			<ValidatorsChanged<T>>::put(true);
+			Self::deposit_event(Event::ValidatorRemoved { validator });
+			Ok(())
+		}
+	}
+
+	impl<T: Config> pallet_session::SessionManager<T::AccountId> for Pallet<T> {
+		fn new_session(_new_index: u32) -> Option<Vec<T::AccountId>> {
+			if !<ValidatorsChanged<T>>::take() {
+				return None
+			}
+			Some(<Validators<T>>::get())
+		}
+
+		fn end_session(_end_index: u32) {}
+
+		fn start_session(_start_index: u32) {}
+	}
+
+	/// The genesis SS58 prefix, for `frame_system::Config::SS58Prefix`.
+	pub struct GenesisSs58Prefix<T>(PhantomData<T>);
+
+	impl<T: Config> Get<u16> for GenesisSs58Prefix<T> {
+		fn get() -> u16 {
+			<Ss58Prefix<T>>::get()
+		}
+	}
+
+	/// Half the genesis block time, for `pallet_timestamp::Config::MinimumPeriod`.
+	pub struct MinimumPeriod<T>(PhantomData<T>);
+
+	impl<T: Config> Get<u64> for MinimumPeriod<T> {
+		fn get() -> u64 {
+			<BlockTime<T>>::get() / 2
+		}
+	}
+
+	/// The number of blocks in `Millis` milliseconds at the genesis block time, at least one.
+	///
+	/// For durations such as session lengths, which would otherwise be wrong on chains whose
+	/// block time differs from `DefaultBlockTime`.
+	pub struct BlocksIn<T, Millis>(PhantomData<(T, Millis)>);
+
+	impl<T: Config, Millis: Get<u64>> Get<T::BlockNumber> for BlocksIn<T, Millis> {
+		fn get() -> T::BlockNumber {
+			(Millis::get() / <BlockTime<T>>::get().max(1)).max(1).saturated_into()
+		}
+	}
+}
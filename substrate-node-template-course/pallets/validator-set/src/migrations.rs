@@ -0,0 +1,63 @@
+//! Storage migrations for the validator-set pallet.
+
+use super::*;
+use frame_support::{pallet_prelude::*, traits::OnRuntimeUpgrade};
+use sp_runtime::traits::OpaqueKeys;
+use sp_std::{marker::PhantomData, vec::Vec};
+
+/// Seeds the validator set and the session keys of `pallet_session` when the two pallets are
+/// added to a chain that already runs Aura and Grandpa.
+///
+/// Their genesis config never runs on such a chain, so the first session change would otherwise
+/// hand Aura and Grandpa an empty authority set and stall the chain. `Authorities` returns the
+/// current authorities with their session keys. The migration does nothing once
+/// `pallet_session` has validators, as on chains that started with both pallets.
+pub struct SeedFromAuthorities<T, Authorities>(PhantomData<(T, Authorities)>);
+
+impl<T, Authorities> OnRuntimeUpgrade for SeedFromAuthorities<T, Authorities>
+where
+	T: Config + pallet_session::Config<ValidatorId = <T as frame_system::Config>::AccountId>,
+	Authorities: Get<Vec<(T::AccountId, T::Keys)>>,
+{
+	fn on_runtime_upgrade() -> Weight {
+		if !pallet_session::Validators::<T>::get().is_empty() {
+			log::info!(target: "runtime::validator-set", "session validators exist, skipping");
+			return T::DbWeight::get().reads(1)
+		}
+		let authorities = Authorities::get();
+		if authorities.is_empty() {
+			log::warn!(target: "runtime::validator-set", "no authorities to seed validators from");
+			return T::DbWeight::get().reads(3)
+		}
+
+		let validators: Vec<_> = authorities.iter().map(|(who, _)| who.clone()).collect();
+		for (who, keys) in &authorities {
+			for id in T::Keys::key_ids() {
+				pallet_session::KeyOwner::<T>::insert((*id, keys.get_raw(*id).to_vec()), who);
+			}
+			pallet_session::NextKeys::<T>::insert(who, keys);
+			// `pallet_session` releases this reference when the keys are purged.
+			if frame_system::Pallet::<T>::inc_consumers(who).is_err() {
+				log::warn!(target: "runtime::validator-set", "validator {:?} has no account", who);
+			}
+		}
+		pallet_session::Validators::<T>::put(&validators);
+		pallet_session::QueuedKeys::<T>::put(&authorities);
+		<Validators<T>>::put(&validators);
+		// Pin the defaults the chain ran with, so that changing them later does not change it.
+		<BlockTime<T>>::put(<BlockTime<T>>::get());
+		<Ss58Prefix<T>>::put(<Ss58Prefix<T>>::get());
+
+		log::info!(target: "runtime::validator-set", "seeded {} validators", validators.len());
+		let count = authorities.len() as u64;
+		let keys = T::Keys::key_ids().len() as u64;
+		T::DbWeight::get().reads_writes(3 + 2 * count, 5 + count * (2 + keys))
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade(_state: Vec<u8>) -> Result<(), &'static str> {
+		ensure!(!pallet_session::Validators::<T>::get().is_empty(), "no session validators");
+		ensure!(!<Validators<T>>::get().is_empty(), "no validators");
+		Ok(())
+	}
+}
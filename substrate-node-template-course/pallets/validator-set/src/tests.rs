@@ -0,0 +1,59 @@
+use crate::{mock::*, BlocksIn, Error, Event, GenesisSs58Prefix, MinimumPeriod};
+use frame_support::{
+	assert_noop, assert_ok,
+	traits::{ConstU64, Get},
+};
+use pallet_session::SessionManager;
+
+#[test]
+fn genesis_sets_validators_and_block_time() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(ValidatorSet::validators(), vec![1, 2]);
+		assert_eq!(ValidatorSet::block_time(), 3000);
+		assert_eq!(<MinimumPeriod<Test> as Get<u64>>::get(), 1500);
+		assert_eq!(<GenesisSs58Prefix<Test> as Get<u16>>::get(), 1234);
+		// An hour at three seconds per block.
+		assert_eq!(<BlocksIn<Test, ConstU64<3_600_000>> as Get<u64>>::get(), 1200);
+		assert_eq!(<BlocksIn<Test, ConstU64<1>> as Get<u64>>::get(), 1);
+		// Until governance changes the set, sessions keep their validators.
+		assert_eq!(ValidatorSet::new_session(1), None);
+	});
+}
+
+#[test]
+fn added_validator_is_handed_to_the_next_session() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			ValidatorSet::add_validator(RuntimeOrigin::signed(1), 3),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_noop!(
+			ValidatorSet::add_validator(RuntimeOrigin::signed(Governance::get()), 2),
+			Error::<Test>::Duplicate
+		);
+
+		assert_ok!(ValidatorSet::add_validator(RuntimeOrigin::signed(Governance::get()), 3));
+		System::assert_last_event(Event::<Test>::ValidatorAdded { validator: 3 }.into());
+
+		assert_eq!(ValidatorSet::new_session(1), Some(vec![1, 2, 3]));
+		assert_eq!(ValidatorSet::new_session(2), None);
+	});
+}
+
+#[test]
+fn remove_validator_keeps_min_authorities() {
+	new_test_ext().execute_with(|| {
+		let governance = RuntimeOrigin::signed(Governance::get());
+		assert_noop!(
+			ValidatorSet::remove_validator(governance.clone(), 3),
+			Error::<Test>::NotValidator
+		);
+
+		assert_ok!(ValidatorSet::remove_validator(governance.clone(), 1));
+		assert_eq!(ValidatorSet::new_session(1), Some(vec![2]));
+		assert_noop!(
+			ValidatorSet::remove_validator(governance, 2),
+			Error::<Test>::TooFewValidators
+		);
+	});
+}
@@ -0,0 +1,278 @@
+//! Off-chain checks of the product provenance proofs returned by the node's
+//! `supplychain_productProvenance` RPC.
+//!
+//! A proof holds the storage trie nodes of a product and of the companies that owned it, at one
+//! finalized block. [`verify`] checks them against a header the auditor trusts, and
+//! [`verify_finality`] checks that Grandpa finalized that header. Neither needs a node.
+
+use codec::{Decode, Encode};
+use finality_grandpa::voter_set::VoterSet;
+use pallet_template::Product;
+use sc_finality_grandpa::{FinalityProof, GrandpaJustification};
+use serde::{Deserialize, Serialize};
+use sp_core::{
+	crypto::AccountId32,
+	hashing::{blake2_128, twox_128},
+	Bytes, H256,
+};
+use sp_finality_grandpa::AuthorityId;
+use sp_runtime::{
+	generic,
+	traits::{BlakeTwo256, Header as HeaderT},
+	OpaqueExtrinsic,
+};
+use sp_state_machine::{read_proof_check, StorageProof};
+use std::{collections::HashMap, fmt};
+
+/// Header of the supply-chain node's blocks.
+pub type Header = generic::Header<u32, BlakeTwo256>;
+
+/// Block of the supply-chain node, with opaque extrinsics.
+pub type Block = generic::Block<Header, OpaqueExtrinsic>;
+
+/// Name of the supply-chain pallet in the runtime, the first part of its storage keys.
+const PALLET: &[u8] = b"TemplateModule";
+
+/// The result of `supplychain_productProvenance`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProvenanceProof {
+	/// The finalized block the proof is for.
+	pub block_hash: H256,
+	/// The account holding the product.
+	pub account: AccountId32,
+	/// Trie nodes proving the product and the accounts of its owners.
+	pub proof: Vec<Bytes>,
+	/// SCALE encoded Grandpa finality proof of the block, if the node could produce one.
+	pub finality_proof: Option<Bytes>,
+}
+
+/// A product and its owners, as proven by a [`ProvenanceProof`].
+pub struct Provenance {
+	pub product: Product,
+	/// Company ids of the previous owners followed by the current owner, each with the account
+	/// it is registered to, or `None` if no account has registered that id.
+	pub owners: Vec<(u64, Option<AccountId32>)>,
+}
+
+/// Why a proof was rejected.
+#[derive(Debug)]
+pub enum Error {
+	/// The proof is for another block than the trusted header.
+	WrongBlock { trusted: H256, proof: H256 },
+	/// The trie nodes do not prove the product and its owners under the header's state root.
+	InvalidProof(String),
+	/// The proof shows that the account holds no product.
+	NoProduct,
+	/// A proven value does not decode.
+	InvalidValue(String),
+	/// The node did not include a finality proof.
+	NoFinalityProof,
+	/// The finality proof does not show that the authorities finalized the block.
+	InvalidFinalityProof(String),
+}
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Error::WrongBlock { trusted, proof } =>
+				write!(f, "proof is for block {:?}, not the trusted block {:?}", proof, trusted),
+			Error::InvalidProof(e) => write!(f, "invalid storage proof: {}", e),
+			Error::NoProduct => write!(f, "the account holds no product"),
+			Error::InvalidValue(e) => write!(f, "invalid proven value: {}", e),
+			Error::NoFinalityProof => write!(f, "the proof has no finality proof"),
+			Error::InvalidFinalityProof(e) => write!(f, "invalid finality proof: {}", e),
+		}
+	}
+}
+
+impl std::error::Error for Error {}
+
+fn map_key(storage: &[u8], key: &[u8]) -> Vec<u8> {
+	[&twox_128(PALLET)[..], &twox_128(storage)[..], &blake2_128(key)[..], key].concat()
+}
+
+/// Storage key of the product held by `account`.
+pub fn product_key(account: &AccountId32) -> Vec<u8> {
+	map_key(b"AccountToProduct", account.as_ref())
+}
+
+/// Storage key of the account that registered the company `id`.
+pub fn company_account_key(id: u64) -> Vec<u8> {
+	map_key(b"CompanyIdToAccount", &id.encode())
+}
+
+/// Company ids of the previous owners of `product`, followed by its current owner.
+pub fn owner_ids(product: &Product) -> Vec<u64> {
+	product.previous_owners.iter().copied().chain([product.owner]).collect()
+}
+
+fn decode<T: Decode>(value: &[u8]) -> Result<T, Error> {
+	T::decode(&mut &value[..]).map_err(|e| Error::InvalidValue(e.to_string()))
+}
+
+/// Check `proof` against the state root of `trusted` and return the proven provenance.
+pub fn verify(proof: &ProvenanceProof, trusted: &Header) -> Result<Provenance, Error> {
+	if trusted.hash() != proof.block_hash {
+		return Err(Error::WrongBlock { trusted: trusted.hash(), proof: proof.block_hash })
+	}
+	let nodes = StorageProof::new(proof.proof.iter().map(|node| node.0.clone()));
+	let read = |keys: &[Vec<u8>]| -> Result<HashMap<Vec<u8>, Option<Vec<u8>>>, Error> {
+		read_proof_check::<BlakeTwo256, _>(*trusted.state_root(), nodes.clone(), keys)
+			.map_err(|e| Error::InvalidProof(e.to_string()))
+	};
+
+	let product_key = product_key(&proof.account);
+	let product: Product = match read(&[product_key.clone()])?.remove(&product_key).flatten() {
+		Some(value) => decode(&value)?,
+		None => return Err(Error::NoProduct),
+	};
+
+	let ids = owner_ids(&product);
+	let keys: Vec<_> = ids.iter().map(|id| company_account_key(*id)).collect();
+	let values = read(&keys)?;
+	let owners = ids
+		.into_iter()
+		.zip(&keys)
+		.map(|(id, key)| {
+			let account = values.get(key).cloned().flatten().map(|v| decode(&v)).transpose()?;
+			Ok((id, account))
+		})
+		.collect::<Result<_, Error>>()?;
+	Ok(Provenance { product, owners })
+}
+
+/// Check that the Grandpa authority set `set_id` with `authorities` finalized `trusted`, using
+/// the finality proof of `proof`.
+pub fn verify_finality(
+	proof: &ProvenanceProof,
+	trusted: &Header,
+	set_id: u64,
+	authorities: &[AuthorityId],
+) -> Result<(), Error> {
+	let encoded = proof.finality_proof.as_ref().ok_or(Error::NoFinalityProof)?;
+	let finality = FinalityProof::<Header>::decode(&mut &encoded[..])
+		.map_err(|e| Error::InvalidFinalityProof(e.to_string()))?;
+
+	// The justification may be for a descendant, linked to the trusted header by the headers in
+	// between.
+	let mut target = trusted.clone();
+	for header in finality.unknown_headers {
+		if *header.parent_hash() != target.hash() {
+			return Err(Error::InvalidFinalityProof(format!(
+				"header #{} does not extend the trusted header",
+				header.number()
+			)))
+		}
+		target = header;
+	}
+	if target.hash() != finality.block {
+		return Err(Error::InvalidFinalityProof(format!(
+			"justification is for {:?}, not a descendant of the trusted header",
+			finality.block
+		)))
+	}
+
+	let voters = VoterSet::new(authorities.iter().map(|id| (id.clone(), 1)))
+		.ok_or_else(|| Error::InvalidFinalityProof("no authorities given".into()))?;
+	GrandpaJustification::<Block>::decode_and_verify_finalizes(
+		&finality.justification,
+		(target.hash(), *target.number()),
+		set_id,
+		&voters,
+	)
+	.map_err(|e| Error::InvalidFinalityProof(e.to_string()))?;
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_core::storage::Storage;
+	use sp_runtime::StateVersion;
+	use sp_state_machine::{prove_read, InMemoryBackend};
+
+	fn account(seed: u8) -> AccountId32 {
+		AccountId32::new([seed; 32])
+	}
+
+	fn product() -> Product {
+		Product {
+			id: 7,
+			name: b"Steel coil".to_vec(),
+			description: Vec::new(),
+			owner: 2,
+			previous_owners: vec![1],
+		}
+	}
+
+	/// A header over a state holding `product` at account 10 and company 1 at account 1, with a
+	/// proof of `keys`.
+	fn prove(keys: &[Vec<u8>]) -> (Header, ProvenanceProof) {
+		let top = [
+			(product_key(&account(10)), product().encode()),
+			(company_account_key(1), account(1).encode()),
+		]
+		.into_iter()
+		.collect();
+		let backend = InMemoryBackend::<BlakeTwo256>::from((
+			Storage { top, children_default: Default::default() },
+			StateVersion::V1,
+		));
+		let root = *backend.root();
+		let nodes = prove_read(backend, keys).unwrap();
+		let header =
+			Header::new(5, Default::default(), root, Default::default(), Default::default());
+		let proof = ProvenanceProof {
+			block_hash: header.hash(),
+			account: account(10),
+			proof: nodes.into_iter_nodes().map(Into::into).collect(),
+			finality_proof: None,
+		};
+		(header, proof)
+	}
+
+	fn all_keys() -> Vec<Vec<u8>> {
+		vec![product_key(&account(10)), company_account_key(1), company_account_key(2)]
+	}
+
+	#[test]
+	fn proves_product_and_owners() {
+		let (header, proof) = prove(&all_keys());
+		let provenance = verify(&proof, &header).unwrap();
+		assert!(provenance.product == product());
+		assert_eq!(provenance.owners, vec![(1, Some(account(1))), (2, None)]);
+	}
+
+	#[test]
+	fn rejects_other_block() {
+		let (_, proof) = prove(&all_keys());
+		let other = Header::new(
+			5,
+			Default::default(),
+			H256::repeat_byte(1),
+			Default::default(),
+			Default::default(),
+		);
+		assert!(matches!(verify(&proof, &other), Err(Error::WrongBlock { .. })));
+	}
+
+	#[test]
+	fn rejects_incomplete_proof() {
+		let (header, proof) = prove(&[product_key(&account(10))]);
+		assert!(matches!(verify(&proof, &header), Err(Error::InvalidProof(_))));
+	}
+
+	#[test]
+	fn rejects_account_without_product() {
+		let (header, mut proof) = prove(&[product_key(&account(11))]);
+		proof.account = account(11);
+		assert!(matches!(verify(&proof, &header), Err(Error::NoProduct)));
+	}
+
+	#[test]
+	fn finality_needs_a_finality_proof() {
+		let (header, proof) = prove(&all_keys());
+		assert!(matches!(verify_finality(&proof, &header, 0, &[]), Err(Error::NoFinalityProof)));
+	}
+}
@@ -0,0 +1,84 @@
+//! Checks a product provenance proof of the supply-chain node against a trusted header.
+
+use clap::Parser;
+use provenance_verifier::{verify, verify_finality, Header, ProvenanceProof};
+use serde::de::DeserializeOwned;
+use sp_core::{
+	crypto::{Ss58AddressFormat, Ss58Codec},
+	ed25519,
+};
+use sp_finality_grandpa::AuthorityId;
+use std::{fs, path::PathBuf};
+
+#[derive(Debug, Parser)]
+struct Args {
+	/// JSON file holding the result of `supplychain_productProvenance`.
+	#[clap(long)]
+	proof: PathBuf,
+
+	/// JSON file holding the trusted header, as returned by `chain_getHeader`.
+	#[clap(long)]
+	header: PathBuf,
+
+	/// Id of the Grandpa authority set that finalized the block. Together with `--authority`,
+	/// the finality proof is checked as well.
+	#[clap(long, requires = "authority")]
+	set_id: Option<u64>,
+
+	/// Grandpa key of an authority of the set, as an SS58 address. Repeat for every authority.
+	#[clap(long, requires = "set-id")]
+	authority: Vec<String>,
+
+	/// SS58 prefix used to print accounts.
+	#[clap(long, default_value = "42")]
+	ss58_prefix: u16,
+}
+
+fn read<T: DeserializeOwned>(path: &PathBuf) -> Result<T, String> {
+	let contents = fs::read_to_string(path)
+		.map_err(|e| format!("Error reading `{}`: {}", path.display(), e))?;
+	serde_json::from_str(&contents)
+		.map_err(|e| format!("Error parsing `{}`: {}", path.display(), e))
+}
+
+fn main() -> Result<(), String> {
+	let args = Args::parse();
+	let proof: ProvenanceProof = read(&args.proof)?;
+	let header: Header = read(&args.header)?;
+	let format = Ss58AddressFormat::custom(args.ss58_prefix);
+
+	let provenance = verify(&proof, &header).map_err(|e| e.to_string())?;
+	println!("Block #{} {:?}: storage proof verified", header.number, proof.block_hash);
+	println!(
+		"Product {} `{}` held by {}",
+		provenance.product.id,
+		String::from_utf8_lossy(&provenance.product.name),
+		proof.account.to_ss58check_with_version(format)
+	);
+	for (i, (id, account)) in provenance.owners.iter().enumerate() {
+		let role = if i + 1 == provenance.owners.len() { "owner" } else { "previous owner" };
+		match account {
+			Some(account) =>
+				println!("  {} {}: {}", role, id, account.to_ss58check_with_version(format)),
+			None => println!("  {} {}: not registered", role, id),
+		}
+	}
+
+	match args.set_id {
+		Some(set_id) => {
+			let authorities = args
+				.authority
+				.iter()
+				.map(|address| {
+					ed25519::Public::from_ss58check(address)
+						.map(AuthorityId::from)
+						.map_err(|e| format!("Invalid authority `{}`: {:?}", address, e))
+				})
+				.collect::<Result<Vec<_>, _>>()?;
+			verify_finality(&proof, &header, set_id, &authorities).map_err(|e| e.to_string())?;
+			println!("Finality verified against authority set {}", set_id);
+		},
+		None => println!("Finality not checked; pass --set-id and --authority to check it"),
+	}
+	Ok(())
+}
@@ -0,0 +1,140 @@
+//! Chain extension that gives ink! contracts access to the supply-chain pallet.
+//!
+//! The function ids and the SCALE layout of every value written back to the contract must stay in
+//! sync with the `supply-chain-extension` ink! crate.
+
+use codec::Encode;
+use frame_support::traits::Get;
+use pallet_assets::WeightInfo as _;
+use pallet_contracts::chain_extension::{
+	ChainExtension, Environment, Ext, InitState, RetVal, SysConfig, UncheckedFrom,
+};
+use pallet_template::{
+	AccountToCompany, AccountToIOU, AccountToProduct, AccountToSupplyContract, Error,
+};
+use sp_runtime::DispatchError;
+
+use crate::{AccountId, Runtime, TemplateModule};
+
+/// Read the `Company` registered to an account.
+const FUNC_COMPANY: u32 = 1101;
+/// Read the `Product` held by an account.
+const FUNC_PRODUCT: u32 = 1102;
+/// Read the `SupplyContract` held by an account.
+const FUNC_SUPPLY_CONTRACT: u32 = 1103;
+/// Read the `IOU` held by an account.
+const FUNC_IOU: u32 = 1104;
+/// Move a supply contract to a new status. Only allowed for the supply contract held by the calling
+/// contract itself, or by an account that named the calling contract its delegate with
+/// `set_supply_contract_delegate`.
+const FUNC_SET_SUPPLY_CONTRACT_STATUS: u32 = 1105;
+
+/// Status codes returned by [`FUNC_SET_SUPPLY_CONTRACT_STATUS`].
+const STATUS_SUCCESS: u32 = 0;
+const STATUS_SUPPLY_CONTRACT_NOT_FOUND: u32 = 1;
+const STATUS_INVALID_TRANSITION: u32 = 2;
+const STATUS_MULTISIG_REQUIRED: u32 = 3;
+const STATUS_NOT_DELEGATED: u32 = 4;
+const STATUS_PAUSED: u32 = 5;
+const STATUS_NOT_BUYER: u32 = 6;
+
+/// Exposes `pallet_template` storage and supply-contract status transitions to contracts.
+#[derive(Default)]
+pub struct SupplyChainExtension;
+
+impl ChainExtension<Runtime> for SupplyChainExtension {
+	fn call<E>(&mut self, env: Environment<E, InitState>) -> Result<RetVal, DispatchError>
+	where
+		E: Ext<T = Runtime>,
+		<E::T as SysConfig>::AccountId: UncheckedFrom<<E::T as SysConfig>::Hash> + AsRef<[u8]>,
+	{
+		let func_id = env.func_id();
+		let mut env = env.buf_in_buf_out();
+		let read_weight = <Runtime as frame_system::Config>::DbWeight::get().reads(2);
+
+		let output = match func_id {
+			FUNC_COMPANY => {
+				env.charge_weight(read_weight)?;
+				let account: AccountId = env.read_as()?;
+				AccountToCompany::<Runtime>::contains_key(&account)
+					.then(|| TemplateModule::company_info(&account))
+					.encode()
+			},
+			FUNC_PRODUCT => {
+				env.charge_weight(read_weight)?;
+				let account: AccountId = env.read_as()?;
+				AccountToProduct::<Runtime>::contains_key(&account)
+					.then(|| TemplateModule::product_info(&account))
+					.encode()
+			},
+			FUNC_SUPPLY_CONTRACT => {
+				env.charge_weight(read_weight)?;
+				let account: AccountId = env.read_as()?;
+				AccountToSupplyContract::<Runtime>::contains_key(&account)
+					.then(|| TemplateModule::supply_contract_info(&account))
+					.encode()
+			},
+			FUNC_IOU => {
+				env.charge_weight(read_weight)?;
+				let account: AccountId = env.read_as()?;
+				AccountToIOU::<Runtime>::contains_key(&account)
+					.then(|| TemplateModule::iou_info(&account))
+					.encode()
+			},
+			FUNC_SET_SUPPLY_CONTRACT_STATUS => {
+				// Pause flag, delegate, supply contract, multisig, buyer and seller lookups, plus
+				// the release of the escrow, which is refunded if the escrow stays put.
+				let status_weight =
+					<Runtime as frame_system::Config>::DbWeight::get().reads_writes(7, 2);
+				let charged = env.charge_weight(
+					status_weight.saturating_add(
+						<Runtime as pallet_assets::Config>::WeightInfo::transfer(),
+					),
+				)?;
+				let (holder, delivered, contract_fulfilled): (AccountId, bool, bool) =
+					env.read_as()?;
+
+				let call = pallet_template::Call::<Runtime>::update_supply_contract_status {
+					delivered,
+					contract_fulfilled,
+				};
+				if TemplateModule::is_paused(&call) {
+					return Ok(RetVal::Converging(STATUS_PAUSED))
+				}
+				// The contract acts for itself or for holders that delegated to it, never for
+				// whoever happens to call it.
+				let address = env.ext().address().clone();
+				if holder != address &&
+					TemplateModule::supply_contract_delegate(&holder).as_ref() != Some(&address)
+				{
+					return Ok(RetVal::Converging(STATUS_NOT_DELEGATED))
+				}
+				let escrowed = TemplateModule::supply_contract_info(&holder).escrowed;
+				if !(escrowed && contract_fulfilled) {
+					env.adjust_weight(charged, status_weight);
+				}
+
+				let status = match TemplateModule::set_supply_contract_status(
+					&holder,
+					delivered,
+					contract_fulfilled,
+				) {
+					Ok(()) => STATUS_SUCCESS,
+					Err(e) if e == Error::<Runtime>::SupplyContractNotFound.into() =>
+						STATUS_SUPPLY_CONTRACT_NOT_FOUND,
+					Err(e) if e == Error::<Runtime>::InvalidStatusTransition.into() =>
+						STATUS_INVALID_TRANSITION,
+					Err(e) if e == Error::<Runtime>::MultisigRequired.into() =>
+						STATUS_MULTISIG_REQUIRED,
+					Err(e) if e == Error::<Runtime>::NotBuyer.into() => STATUS_NOT_BUYER,
+					Err(e) => return Err(e),
+				};
+				return Ok(RetVal::Converging(status))
+			},
+			_ => return Err(DispatchError::Other("Unimplemented supply-chain func_id")),
+		};
+
+		env.write(&output, false, None)?;
+		Ok(RetVal::Converging(STATUS_SUCCESS))
+	}
+}
@@ -0,0 +1,48 @@
+//! Reads the legal details of supply-chain companies from `pallet_identity`.
+
+use pallet_identity::{Data, Judgement};
+use pallet_template::{CompanyIdentity, LegalDetails};
+use sp_std::prelude::*;
+
+use crate::{AccountId, Identity};
+
+/// Key of the additional identity field holding a company's registration number.
+pub const REGISTRATION_NUMBER_FIELD: &[u8] = b"registration_number";
+
+/// Legal details of accounts whose identity at least one registrar judged `Reasonable` or
+/// `KnownGood`.
+pub struct JudgedIdentity;
+
+impl CompanyIdentity<AccountId> for JudgedIdentity {
+	fn legal_details(who: &AccountId) -> Option<LegalDetails> {
+		let registration = Identity::identity(who)?;
+		let judged = registration
+			.judgements
+			.iter()
+			.any(|(_, j)| matches!(j, Judgement::Reasonable | Judgement::KnownGood));
+		if !judged {
+			return None
+		}
+
+		let info = registration.info;
+		let registration_number = info
+			.additional
+			.iter()
+			.find(|(key, _)| raw(key) == REGISTRATION_NUMBER_FIELD)
+			.map(|(_, value)| raw(value))
+			.unwrap_or_default();
+		Some(LegalDetails {
+			legal_name: raw(&info.legal),
+			website: raw(&info.web),
+			registration_number,
+		})
+	}
+}
+
+/// Bytes of a raw identity field. Hashed and empty fields read as empty.
+fn raw(data: &Data) -> Vec<u8> {
+	match data {
+		Data::Raw(bytes) => bytes.to_vec(),
+		_ => Vec::new(),
+	}
+}
@@ -6,6 +6,12 @@
 #[cfg(feature = "std")]
 include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 
+mod chain_extension;
+mod identity;
+mod sponsor;
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use pallet_contracts::weights::WeightInfo;
 use pallet_grandpa::{
 	fg_primitives, AuthorityId as GrandpaId, AuthorityList as GrandpaAuthorityList,
 };
@@ -15,10 +21,11 @@ use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
 use sp_runtime::{
 	create_runtime_str, generic, impl_opaque_keys,
 	traits::{
-		AccountIdLookup, BlakeTwo256, Block as BlockT, IdentifyAccount, NumberFor, One, Verify,
+		AccountIdLookup, BlakeTwo256, Block as BlockT, Bounded, ConvertInto, IdentifyAccount,
+		NumberFor, OpaqueKeys, Verify,
 	},
 	transaction_validity::{TransactionSource, TransactionValidity},
-	ApplyExtrinsicResult, MultiSignature,
+	ApplyExtrinsicResult, FixedPointNumber, MultiSignature, Perquintill, RuntimeDebug,
 };
 use sp_std::prelude::*;
 #[cfg(feature = "std")]
@@ -29,24 +36,28 @@ use sp_version::RuntimeVersion;
 pub use frame_support::{
 	construct_runtime, parameter_types,
 	traits::{
-		ConstU128, ConstU32, ConstU64, ConstU8, KeyOwnerProofSystem, Randomness, StorageInfo,
+		ConstBool, ConstU128, ConstU16, ConstU32, ConstU64, ConstU8, Contains, EitherOfDiverse,
+		EqualPrivilegeOnly, InstanceFilter, KeyOwnerProofSystem, Nothing, Randomness, StorageInfo,
 	},
 	weights::{
 		constants::{BlockExecutionWeight, ExtrinsicBaseWeight, RocksDbWeight, WEIGHT_PER_SECOND},
-		IdentityFee, Weight,
+		ConstantMultiplier, IdentityFee, Weight,
 	},
-	StorageValue,
+	PalletId, StorageValue,
 };
+use frame_system::EnsureRoot;
 pub use frame_system::Call as SystemCall;
 pub use pallet_balances::Call as BalancesCall;
+pub use pallet_template::Call as TemplateModuleCall;
 pub use pallet_timestamp::Call as TimestampCall;
-use pallet_transaction_payment::{ConstFeeMultiplier, CurrencyAdapter, Multiplier};
+use pallet_transaction_payment::{CurrencyAdapter, Multiplier, TargetedFeeAdjustment};
 #[cfg(any(feature = "std", test))]
 pub use sp_runtime::BuildStorage;
 pub use sp_runtime::{Perbill, Permill};
 
 /// Import the template pallet.
 pub use pallet_template;
+pub use sponsor::ChargeSponsoredTransactionPayment;
 
 /// An index to a block.
 pub type BlockNumber = u32;
@@ -103,29 +114,30 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	//   `spec_version`, and `authoring_version` are the same between Wasm and native.
 	// This value is set to 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
 	//   the compatible custom types.
-	spec_version: 100,
+	spec_version: 102,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
 	transaction_version: 1,
 	state_version: 1,
 };
 
-/// This determines the average expected block time that we are targeting.
-/// Blocks will be produced at a minimum duration defined by `SLOT_DURATION`.
-/// `SLOT_DURATION` is picked up by `pallet_timestamp` which is in turn picked
-/// up by `pallet_aura` to implement `fn slot_duration()`.
+/// This determines the default block time that we are targeting.
+/// The block time of a chain is set by the `blockTime` property of its chain spec and stored by
+/// `pallet_validator_set`, which hands half of it to `pallet_timestamp` as the minimum period.
+/// That is in turn picked up by `pallet_aura` to implement `fn slot_duration()`.
 ///
-/// Change this to adjust the block time.
+/// Chain specs that do not set `blockTime` use this value.
 pub const MILLISECS_PER_BLOCK: u64 = 6000;
 
 // NOTE: Currently it is not possible to change the slot duration after the chain has started.
 //       Attempting to do so will brick block production.
 pub const SLOT_DURATION: u64 = MILLISECS_PER_BLOCK;
 
-// Time is measured by number of blocks.
-pub const MINUTES: BlockNumber = 60_000 / (MILLISECS_PER_BLOCK as BlockNumber);
-pub const HOURS: BlockNumber = MINUTES * 60;
-pub const DAYS: BlockNumber = HOURS * 24;
+// Durations in milliseconds. Chain specs set their own block time, so durations measured in
+// blocks are converted at that block time with `pallet_validator_set::BlocksIn`.
+pub const MINUTE_IN_MILLIS: u64 = 60_000;
+pub const HOUR_IN_MILLIS: u64 = MINUTE_IN_MILLIS * 60;
+pub const DAY_IN_MILLIS: u64 = HOUR_IN_MILLIS * 24;
 
 /// The version information used to identify this runtime when compiled natively.
 #[cfg(feature = "std")]
@@ -135,6 +147,25 @@ pub fn native_version() -> NativeVersion {
 
 const NORMAL_DISPATCH_RATIO: Perbill = Perbill::from_percent(75);
 
+/// We assume that ~10% of the block weight is consumed by `on_initialize` handlers.
+/// This is used to limit the maximal weight of a single extrinsic.
+const AVERAGE_ON_INITIALIZE_RATIO: Perbill = Perbill::from_percent(10);
+
+// Unit = the base number of indivisible units for balances
+pub const UNIT: Balance = 1_000_000_000_000;
+pub const MILLIUNIT: Balance = 1_000_000_000;
+pub const MICROUNIT: Balance = 1_000_000;
+
+/// Calculates the storage deposit for `items` storage entries occupying `bytes` in total.
+pub const fn deposit(items: u32, bytes: u32) -> Balance {
+	(items as Balance * UNIT + (bytes as Balance) * (5 * MILLIUNIT / 100)) / 10
+}
+
+// Prints debug output of the `contracts` pallet to stdout if the node is
+// started with `-lruntime::contracts=debug`. Only built into development runtimes, as the
+// debug buffers cost memory and let RPC callers read contract internals.
+const CONTRACTS_DEBUG_OUTPUT: bool = cfg!(feature = "contracts-debug");
+
 parameter_types! {
 	pub const BlockHashCount: BlockNumber = 2400;
 	pub const Version: RuntimeVersion = VERSION;
@@ -143,14 +174,27 @@ parameter_types! {
 		::with_sensible_defaults(2u64 * WEIGHT_PER_SECOND, NORMAL_DISPATCH_RATIO);
 	pub BlockLength: frame_system::limits::BlockLength = frame_system::limits::BlockLength
 		::max_with_normal_ratio(5 * 1024 * 1024, NORMAL_DISPATCH_RATIO);
-	pub const SS58Prefix: u8 = 42;
+}
+
+/// Rejects the supply-chain calls that governance paused with `TemplateModule::pause_call`.
+///
+/// Calls of every other pallet, in particular System and Balances, are always allowed.
+pub struct MaintenanceFilter;
+
+impl Contains<RuntimeCall> for MaintenanceFilter {
+	fn contains(call: &RuntimeCall) -> bool {
+		match call {
+			RuntimeCall::TemplateModule(call) => !TemplateModule::is_paused(call),
+			_ => true,
+		}
+	}
 }
 
 // Configure FRAME pallets to include in runtime.
 
 impl frame_system::Config for Runtime {
 	/// The basic call filter to use in dispatchable.
-	type BaseCallFilter = frame_support::traits::Everything;
+	type BaseCallFilter = MaintenanceFilter;
 	/// Block & extrinsics weights: base values and limits.
 	type BlockWeights = BlockWeights;
 	/// The maximum length of a block (in bytes).
@@ -193,8 +237,9 @@ impl frame_system::Config for Runtime {
 	type AccountData = pallet_balances::AccountData<Balance>;
 	/// Weight information for the extrinsics of this pallet.
 	type SystemWeightInfo = ();
-	/// This is used as an identifier of the chain. 42 is the generic substrate prefix.
-	type SS58Prefix = SS58Prefix;
+	/// This is used as an identifier of the chain. It is set at genesis with the validator set, so
+	/// that it matches the `ss58Format` of the chain spec; 42 is the generic substrate prefix.
+	type SS58Prefix = pallet_validator_set::GenesisSs58Prefix<Runtime>;
 	/// The set code logic, just the default since we're not a parachain.
 	type OnSetCode = ();
 	type MaxConsumers = frame_support::traits::ConstU32<16>;
@@ -231,10 +276,29 @@ impl pallet_timestamp::Config for Runtime {
 	/// A timestamp: milliseconds since the unix epoch.
 	type Moment = u64;
 	type OnTimestampSet = Aura;
-	type MinimumPeriod = ConstU64<{ SLOT_DURATION / 2 }>;
+	type MinimumPeriod = pallet_validator_set::MinimumPeriod<Runtime>;
 	type WeightInfo = ();
 }
 
+/// Sessions last an hour.
+pub type SessionPeriod = pallet_validator_set::BlocksIn<Runtime, ConstU64<HOUR_IN_MILLIS>>;
+
+parameter_types! {
+	pub const SessionOffset: BlockNumber = 0;
+}
+
+impl pallet_session::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type ValidatorId = AccountId;
+	type ValidatorIdOf = ConvertInto;
+	type ShouldEndSession = pallet_session::PeriodicSessions<SessionPeriod, SessionOffset>;
+	type NextSessionRotation = pallet_session::PeriodicSessions<SessionPeriod, SessionOffset>;
+	type SessionManager = ValidatorSet;
+	type SessionHandler = <opaque::SessionKeys as OpaqueKeys>::KeyTypeIdProviders;
+	type Keys = opaque::SessionKeys;
+	type WeightInfo = pallet_session::weights::SubstrateWeight<Runtime>;
+}
+
 /// Existential deposit.
 pub const EXISTENTIAL_DEPOSIT: u128 = 500;
 
@@ -253,7 +317,14 @@ impl pallet_balances::Config for Runtime {
 }
 
 parameter_types! {
-	pub FeeMultiplier: Multiplier = Multiplier::one();
+	/// Fee charged for every byte of an extrinsic, so that long company descriptions and product
+	/// lists cost more than short calls.
+	pub const TransactionByteFee: Balance = 10 * MICROUNIT;
+	/// Block fullness the fee multiplier steers towards: fees rise above it and fall below it.
+	pub const TargetBlockFullness: Perquintill = Perquintill::from_percent(25);
+	pub AdjustmentVariable: Multiplier = Multiplier::saturating_from_rational(3, 100_000);
+	pub MinimumMultiplier: Multiplier = Multiplier::saturating_from_rational(1, 1_000_000_000u128);
+	pub MaximumMultiplier: Multiplier = Bounded::max_value();
 }
 
 impl pallet_transaction_payment::Config for Runtime {
@@ -261,18 +332,398 @@ impl pallet_transaction_payment::Config for Runtime {
 	type OnChargeTransaction = CurrencyAdapter<Balances, ()>;
 	type OperationalFeeMultiplier = ConstU8<5>;
 	type WeightToFee = IdentityFee<Balance>;
-	type LengthToFee = IdentityFee<Balance>;
-	type FeeMultiplierUpdate = ConstFeeMultiplier<FeeMultiplier>;
+	type LengthToFee = ConstantMultiplier<Balance, TransactionByteFee>;
+	type FeeMultiplierUpdate = TargetedFeeAdjustment<
+		Self,
+		TargetBlockFullness,
+		AdjustmentVariable,
+		MinimumMultiplier,
+		MaximumMultiplier,
+	>;
+}
+
+/// Council motions are open for three days.
+pub type CouncilMotionDuration =
+	pallet_validator_set::BlocksIn<Runtime, ConstU64<{ 3 * DAY_IN_MILLIS }>>;
+
+parameter_types! {
+	pub const CouncilMaxProposals: u32 = 100;
+	pub const CouncilMaxMembers: u32 = 100;
+}
+
+/// The council is the list of accounts kept by `CouncilMembership`, set at genesis and then changed
+/// by the council itself. Its members are usually the accounts of verified companies, but
+/// membership is not tied to company registration.
+type CouncilCollective = pallet_collective::Instance1;
+
+/// Root, or more than half of the council.
+type EnsureRootOrHalfCouncil = EitherOfDiverse<
+	EnsureRoot<AccountId>,
+	pallet_collective::EnsureProportionMoreThan<AccountId, CouncilCollective, 1, 2>,
+>;
+
+/// Root, or at least two thirds of the council, as for the calls in `SuperMajorityCalls`.
+type EnsureRootOrTwoThirdsCouncil = EitherOfDiverse<
+	EnsureRoot<AccountId>,
+	pallet_collective::EnsureProportionAtLeast<AccountId, CouncilCollective, 2, 3>,
+>;
+
+impl pallet_collective::Config<CouncilCollective> for Runtime {
+	type RuntimeOrigin = RuntimeOrigin;
+	type Proposal = RuntimeCall;
+	type RuntimeEvent = RuntimeEvent;
+	type MotionDuration = CouncilMotionDuration;
+	type MaxProposals = CouncilMaxProposals;
+	type MaxMembers = CouncilMaxMembers;
+	type DefaultVote = pallet_collective::PrimeDefaultVote;
+	type WeightInfo = pallet_collective::weights::SubstrateWeight<Runtime>;
+}
+
+impl pallet_membership::Config<pallet_membership::Instance1> for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type AddOrigin = EnsureRootOrTwoThirdsCouncil;
+	type RemoveOrigin = EnsureRootOrTwoThirdsCouncil;
+	type SwapOrigin = EnsureRootOrTwoThirdsCouncil;
+	type ResetOrigin = EnsureRootOrTwoThirdsCouncil;
+	type PrimeOrigin = EnsureRootOrTwoThirdsCouncil;
+	type MembershipInitialized = Council;
+	type MembershipChanged = Council;
+	type MaxMembers = CouncilMaxMembers;
+	type WeightInfo = pallet_membership::weights::SubstrateWeight<Runtime>;
+}
+
+/// Calls a simple majority of the council may dispatch with `Root` origin: the administration of
+/// the supply chain, its identity registrars and its validators. Wrappers such as `Utility`,
+/// `Proxy` or `Scheduler` are left out, so that they cannot smuggle in other calls.
+pub struct SimpleMajorityCalls;
+
+impl Contains<RuntimeCall> for SimpleMajorityCalls {
+	fn contains(call: &RuntimeCall) -> bool {
+		matches!(
+			call,
+			RuntimeCall::TemplateModule(_) | RuntimeCall::Identity(_) | RuntimeCall::ValidatorSet(_)
+		)
+	}
+}
+
+/// Calls that need two thirds of the council: runtime upgrades, council membership and the
+/// administration of assets. Everything else, e.g. forced balance changes or raw storage writes,
+/// needs the whole council.
+pub struct SuperMajorityCalls;
+
+impl Contains<RuntimeCall> for SuperMajorityCalls {
+	fn contains(call: &RuntimeCall) -> bool {
+		matches!(
+			call,
+			RuntimeCall::System(frame_system::Call::set_code { .. }) |
+				RuntimeCall::CouncilMembership(_) |
+				RuntimeCall::Assets(_)
+		)
+	}
+}
+
+/// Council motions are the only way to reach `Root`, e.g. for `System::set_code`.
+impl pallet_motion::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type RuntimeCall = RuntimeCall;
+	type SimpleMajorityOrigin =
+		pallet_collective::EnsureProportionMoreThan<AccountId, CouncilCollective, 1, 2>;
+	type SuperMajorityOrigin =
+		pallet_collective::EnsureProportionAtLeast<AccountId, CouncilCollective, 2, 3>;
+	type UnanimousOrigin =
+		pallet_collective::EnsureProportionAtLeast<AccountId, CouncilCollective, 1, 1>;
+	type SimpleMajorityCalls = SimpleMajorityCalls;
+	type SuperMajorityCalls = SuperMajorityCalls;
 }
 
-impl pallet_sudo::Config for Runtime {
+parameter_types! {
+	pub const DepositPerItem: Balance = deposit(1, 0);
+	pub const DepositPerByte: Balance = deposit(0, 1);
+	// The lazy deletion runs inside on_initialize.
+	pub DeletionWeightLimit: Weight = AVERAGE_ON_INITIALIZE_RATIO *
+		BlockWeights::get().max_block;
+	// The weight needed for decoding the queue should be less or equal than a fifth
+	// of the overall weight dedicated to the lazy deletion.
+	pub DeletionQueueDepth: u32 = ((DeletionWeightLimit::get().ref_time() / (
+			<Runtime as pallet_contracts::Config>::WeightInfo::on_initialize_per_queue_item(1).ref_time() -
+			<Runtime as pallet_contracts::Config>::WeightInfo::on_initialize_per_queue_item(0).ref_time()
+		)) / 5) as u32;
+	pub Schedule: pallet_contracts::Schedule<Runtime> = Default::default();
+}
+
+impl pallet_contracts::Config for Runtime {
+	type Time = Timestamp;
+	type Randomness = RandomnessCollectiveFlip;
+	type Currency = Balances;
 	type RuntimeEvent = RuntimeEvent;
 	type RuntimeCall = RuntimeCall;
+	/// Contracts may not dispatch runtime calls directly. They interact with the supply-chain
+	/// pallet through [`chain_extension::SupplyChainExtension`] instead, which keeps the set of
+	/// reachable calls explicit.
+	type CallFilter = Nothing;
+	type DepositPerItem = DepositPerItem;
+	type DepositPerByte = DepositPerByte;
+	type CallStack = [pallet_contracts::Frame<Self>; 31];
+	type WeightPrice = pallet_transaction_payment::Pallet<Self>;
+	type WeightInfo = pallet_contracts::weights::SubstrateWeight<Self>;
+	type ChainExtension = chain_extension::SupplyChainExtension;
+	type DeletionQueueDepth = DeletionQueueDepth;
+	type DeletionWeightLimit = DeletionWeightLimit;
+	type Schedule = Schedule;
+	type AddressGenerator = pallet_contracts::DefaultAddressGenerator;
+	type ContractAccessWeight = pallet_contracts::DefaultContractAccessWeight<BlockWeights>;
+	type MaxCodeLen = ConstU32<{ 128 * 1024 }>;
+	type MaxStorageKeyLen = ConstU32<128>;
+}
+
+parameter_types! {
+	pub const AssetDeposit: Balance = 100 * UNIT;
+	pub const AssetAccountDeposit: Balance = UNIT;
+	pub const ApprovalDeposit: Balance = UNIT;
+	pub const MetadataDepositBase: Balance = 10 * UNIT;
+	pub const MetadataDepositPerByte: Balance = UNIT;
+}
+
+/// Stablecoin-like assets that supply contracts and IOUs can be settled in.
+impl pallet_assets::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type Balance = Balance;
+	type AssetId = pallet_template::AssetId;
+	type Currency = Balances;
+	type ForceOrigin = EnsureRootOrTwoThirdsCouncil;
+	type AssetDeposit = AssetDeposit;
+	type AssetAccountDeposit = AssetAccountDeposit;
+	type MetadataDepositBase = MetadataDepositBase;
+	type MetadataDepositPerByte = MetadataDepositPerByte;
+	type ApprovalDeposit = ApprovalDeposit;
+	type StringLimit = ConstU32<50>;
+	type Freezer = ();
+	type Extra = ();
+	type WeightInfo = pallet_assets::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+	pub const PreimageMaxSize: u32 = 4096 * 1024;
+	pub const PreimageBaseDeposit: Balance = deposit(2, 64);
+	pub const PreimageByteDeposit: Balance = deposit(0, 1);
+}
+
+impl pallet_preimage::Config for Runtime {
+	type WeightInfo = pallet_preimage::weights::SubstrateWeight<Runtime>;
+	type RuntimeEvent = RuntimeEvent;
+	type Currency = Balances;
+	type ManagerOrigin = EnsureRoot<AccountId>;
+	type MaxSize = PreimageMaxSize;
+	type BaseDeposit = PreimageBaseDeposit;
+	type ByteDeposit = PreimageByteDeposit;
+}
+
+parameter_types! {
+	pub MaximumSchedulerWeight: Weight = Perbill::from_percent(80) *
+		BlockWeights::get().max_block;
+	pub const NoPreimagePostponement: Option<u32> = Some(10);
+}
+
+/// Runs IOU installments and other scheduled calls.
+impl pallet_scheduler::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type RuntimeOrigin = RuntimeOrigin;
+	type PalletsOrigin = OriginCaller;
+	type RuntimeCall = RuntimeCall;
+	type MaximumWeight = MaximumSchedulerWeight;
+	type ScheduleOrigin = EnsureRootOrHalfCouncil;
+	type MaxScheduledPerBlock = ConstU32<50>;
+	type WeightInfo = pallet_scheduler::weights::SubstrateWeight<Runtime>;
+	type OriginPrivilegeCmp = EqualPrivilegeOnly;
+	type PreimageProvider = Preimage;
+	type NoPreimagePostponement = NoPreimagePostponement;
+}
+
+impl pallet_utility::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type RuntimeCall = RuntimeCall;
+	type PalletsOrigin = OriginCaller;
+	type WeightInfo = pallet_utility::weights::SubstrateWeight<Runtime>;
+}
+
+/// The calls a proxy account may make on behalf of the account that added it.
+#[derive(
+	Copy,
+	Clone,
+	Eq,
+	PartialEq,
+	Ord,
+	PartialOrd,
+	Encode,
+	Decode,
+	RuntimeDebug,
+	MaxEncodedLen,
+	scale_info::TypeInfo,
+)]
+pub enum ProxyType {
+	/// Any call.
+	Any,
+	/// Supply-chain calls only, optionally batched, so operations staff can hold delegated keys
+	/// that cannot move funds. Calls that pay, release or commit the company's assets or fees are
+	/// left out.
+	SupplyChain,
+}
+
+impl Default for ProxyType {
+	fn default() -> Self {
+		Self::Any
+	}
+}
+
+impl InstanceFilter<RuntimeCall> for ProxyType {
+	fn filter(&self, c: &RuntimeCall) -> bool {
+		match self {
+			ProxyType::Any => true,
+			ProxyType::SupplyChain => is_supply_chain_proxy_call(c),
+		}
+	}
+
+	fn is_superset(&self, o: &Self) -> bool {
+		match (self, o) {
+			(x, y) if x == y => true,
+			(ProxyType::Any, _) => true,
+			_ => false,
+		}
+	}
+}
+
+/// Whether a `SupplyChain` proxy may make `call`: a `TemplateModule` call that does not move funds,
+/// or a batch made up only of such calls, nested batches included.
+fn is_supply_chain_proxy_call(call: &RuntimeCall) -> bool {
+	use pallet_template::Call as SupplyChainCall;
+
+	match call {
+		RuntimeCall::TemplateModule(
+			SupplyChainCall::fund_supply_contract { .. } |
+			SupplyChainCall::confirm_delivery { .. } |
+			// Fulfilling a contract releases its escrow.
+			SupplyChainCall::update_supply_contract_status { contract_fulfilled: true, .. } |
+			SupplyChainCall::repay_iou { .. } |
+			SupplyChainCall::create_installment_plan { .. } |
+			SupplyChainCall::pay_installment { .. } |
+			SupplyChainCall::sponsor_staff { .. },
+		) => false,
+		RuntimeCall::TemplateModule(_) => true,
+		RuntimeCall::Utility(
+			pallet_utility::Call::batch { calls } |
+			pallet_utility::Call::batch_all { calls } |
+			pallet_utility::Call::force_batch { calls },
+		) => calls.iter().all(is_supply_chain_proxy_call),
+		_ => false,
+	}
+}
+
+parameter_types! {
+	// One storage item; key size 32, value size 8.
+	pub const ProxyDepositBase: Balance = deposit(1, 8);
+	// Additional storage item size of 33 bytes.
+	pub const ProxyDepositFactor: Balance = deposit(0, 33);
+	pub const AnnouncementDepositBase: Balance = deposit(1, 8);
+	pub const AnnouncementDepositFactor: Balance = deposit(0, 66);
+}
+
+impl pallet_proxy::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type RuntimeCall = RuntimeCall;
+	type Currency = Balances;
+	type ProxyType = ProxyType;
+	type ProxyDepositBase = ProxyDepositBase;
+	type ProxyDepositFactor = ProxyDepositFactor;
+	type MaxProxies = ConstU32<32>;
+	type WeightInfo = pallet_proxy::weights::SubstrateWeight<Runtime>;
+	type MaxPending = ConstU32<32>;
+	type CallHasher = BlakeTwo256;
+	type AnnouncementDepositBase = AnnouncementDepositBase;
+	type AnnouncementDepositFactor = AnnouncementDepositFactor;
+}
+
+parameter_types! {
+	// One storage item; key size is 32; value is size 4+4+16+32 bytes = 56 bytes.
+	pub const MultisigDepositBase: Balance = deposit(1, 88);
+	// Additional storage item size of 32 bytes.
+	pub const MultisigDepositFactor: Balance = deposit(0, 32);
+}
+
+impl pallet_multisig::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type RuntimeCall = RuntimeCall;
+	type Currency = Balances;
+	type DepositBase = MultisigDepositBase;
+	type DepositFactor = MultisigDepositFactor;
+	type MaxSignatories = ConstU16<100>;
+	type WeightInfo = pallet_multisig::weights::SubstrateWeight<Runtime>;
+}
+
+/// Company multisigs are the accounts `pallet_multisig` dispatches from.
+pub struct MultisigAccounts;
+
+impl pallet_template::MultiAccountId<AccountId> for MultisigAccounts {
+	fn multi_account_id(who: &[AccountId], threshold: u16) -> AccountId {
+		Multisig::multi_account_id(who, threshold)
+	}
+}
+
+parameter_types! {
+	pub const BasicDeposit: Balance = deposit(1, 258);
+	pub const FieldDeposit: Balance = deposit(0, 66);
+	pub const SubAccountDeposit: Balance = deposit(1, 53);
+	pub const MaxSubAccounts: u32 = 100;
+	pub const MaxAdditionalFields: u32 = 100;
+	pub const MaxRegistrars: u32 = 20;
+}
+
+/// Registrars are added by the council and judge the legal details of supply-chain companies.
+impl pallet_identity::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type Currency = Balances;
+	type BasicDeposit = BasicDeposit;
+	type FieldDeposit = FieldDeposit;
+	type SubAccountDeposit = SubAccountDeposit;
+	type MaxSubAccounts = MaxSubAccounts;
+	type MaxAdditionalFields = MaxAdditionalFields;
+	type MaxRegistrars = MaxRegistrars;
+	type Slashed = ();
+	type ForceOrigin = EnsureRootOrHalfCouncil;
+	type RegistrarOrigin = EnsureRootOrHalfCouncil;
+	type WeightInfo = pallet_identity::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+	pub const SupplyChainPalletId: PalletId = PalletId(*b"supchain");
+	/// Supply contracts worth more than this must be created and settled by a company multisig.
+	pub const MultisigContractThreshold: u64 = 1_000_000;
+}
+
+impl pallet_validator_set::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type AddRemoveOrigin = EnsureRootOrHalfCouncil;
+	type MinAuthorities = ConstU32<1>;
+	type DefaultBlockTime = ConstU64<MILLISECS_PER_BLOCK>;
+	type DefaultSs58Prefix = ConstU16<42>;
 }
 
 /// Configure the pallet-template in pallets/template.
 impl pallet_template::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
+	type VerifyOrigin = EnsureRootOrHalfCouncil;
+	type ArbiterOrigin = EnsureRootOrHalfCouncil;
+	type PauseOrigin = EnsureRootOrHalfCouncil;
+	type AssetBalance = Balance;
+	type Assets = Assets;
+	type PalletId = SupplyChainPalletId;
+	type RuntimeCall = RuntimeCall;
+	type PalletsOrigin = OriginCaller;
+	type Scheduler = Scheduler;
+	type Identity = identity::JudgedIdentity;
+	type RequireJudgedIdentity = ConstBool<false>;
+	type MultiAccountId = MultisigAccounts;
+	type MultisigThreshold = MultisigContractThreshold;
+	type MaxNettedIOUs = ConstU32<64>;
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -286,11 +737,25 @@ construct_runtime!(
 		System: frame_system,
 		RandomnessCollectiveFlip: pallet_randomness_collective_flip,
 		Timestamp: pallet_timestamp,
+		// The validator set and session come before Aura and Grandpa so that their genesis
+		// authorities are taken from the session keys.
+		ValidatorSet: pallet_validator_set,
+		Session: pallet_session,
 		Aura: pallet_aura,
 		Grandpa: pallet_grandpa,
 		Balances: pallet_balances,
 		TransactionPayment: pallet_transaction_payment,
-		Sudo: pallet_sudo,
+		Assets: pallet_assets,
+		Preimage: pallet_preimage,
+		Scheduler: pallet_scheduler,
+		Identity: pallet_identity,
+		Utility: pallet_utility,
+		Proxy: pallet_proxy,
+		Multisig: pallet_multisig,
+		Council: pallet_collective::<Instance1>,
+		CouncilMembership: pallet_membership::<Instance1>,
+		Motion: pallet_motion,
+		Contracts: pallet_contracts,
 		// Include the custom logic from the pallet-template in the runtime.
 		TemplateModule: pallet_template,
 	}
@@ -311,7 +776,7 @@ pub type SignedExtra = (
 	frame_system::CheckEra<Runtime>,
 	frame_system::CheckNonce<Runtime>,
 	frame_system::CheckWeight<Runtime>,
-	pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+	ChargeSponsoredTransactionPayment,
 );
 
 /// Unchecked extrinsic type as expected by this runtime.
@@ -319,6 +784,39 @@ pub type UncheckedExtrinsic =
 	generic::UncheckedExtrinsic<Address, RuntimeCall, Signature, SignedExtra>;
 /// The payload being signed in transactions.
 pub type SignedPayload = generic::SignedPayload<RuntimeCall, SignedExtra>;
+parameter_types! {
+	/// Asset that supply contracts and IOUs created before they carried an asset id are
+	/// denominated in.
+	pub const LegacyContractAsset: pallet_template::AssetId = 0;
+}
+
+/// The Aura authorities of a chain that ran without sessions, with their Grandpa keys, as
+/// validators. Their accounts are those of their Aura keys, as with `authority_keys_from_seed`.
+pub struct AuraAndGrandpaAuthorities;
+
+impl Get<Vec<(AccountId, opaque::SessionKeys)>> for AuraAndGrandpaAuthorities {
+	fn get() -> Vec<(AccountId, opaque::SessionKeys)> {
+		let aura = Aura::authorities();
+		let grandpa = Grandpa::grandpa_authorities();
+		if aura.len() != grandpa.len() {
+			return Vec::new()
+		}
+		aura.into_iter()
+			.zip(grandpa)
+			.filter_map(|(aura, (grandpa, _))| {
+				let account = AccountId::decode(&mut &aura.encode()[..]).ok()?;
+				Some((account, opaque::SessionKeys { aura, grandpa }))
+			})
+			.collect()
+	}
+}
+
+/// Storage migrations run by the next runtime upgrade.
+pub type Migrations = (
+	pallet_template::migrations::v1::MigrateToV1<Runtime, LegacyContractAsset>,
+	pallet_validator_set::migrations::SeedFromAuthorities<Runtime, AuraAndGrandpaAuthorities>,
+);
+
 /// Executive: handles dispatch to the various modules.
 pub type Executive = frame_executive::Executive<
 	Runtime,
@@ -326,6 +824,7 @@ pub type Executive = frame_executive::Executive<
 	frame_system::ChainContext<Runtime>,
 	Runtime,
 	AllPalletsWithSystem,
+	Migrations,
 >;
 
 #[cfg(feature = "runtime-benchmarks")]
@@ -338,7 +837,17 @@ mod benches {
 		[frame_benchmarking, BaselineBench::<Runtime>]
 		[frame_system, SystemBench::<Runtime>]
 		[pallet_balances, Balances]
+		[pallet_assets, Assets]
 		[pallet_timestamp, Timestamp]
+		[pallet_preimage, Preimage]
+		[pallet_scheduler, Scheduler]
+		[pallet_identity, Identity]
+		[pallet_utility, Utility]
+		[pallet_proxy, Proxy]
+		[pallet_multisig, Multisig]
+		[pallet_contracts, Contracts]
+		[pallet_collective, Council]
+		[pallet_membership, CouncilMembership]
 		[pallet_template, TemplateModule]
 	);
 }
@@ -491,6 +1000,71 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_template_runtime_api::SupplyChainApi<Block, AccountId> for Runtime {
+		fn company_details(account: AccountId) -> Option<pallet_template::CompanyDetails> {
+			TemplateModule::company_details(&account)
+		}
+	}
+
+	impl pallet_contracts_rpc_runtime_api::ContractsApi<Block, AccountId, Balance, BlockNumber, Hash>
+		for Runtime
+	{
+		fn call(
+			origin: AccountId,
+			dest: AccountId,
+			value: Balance,
+			gas_limit: u64,
+			storage_deposit_limit: Option<Balance>,
+			input_data: Vec<u8>,
+		) -> pallet_contracts_primitives::ContractExecResult<Balance> {
+			Contracts::bare_call(
+				origin,
+				dest,
+				value,
+				Weight::from_ref_time(gas_limit),
+				storage_deposit_limit,
+				input_data,
+				CONTRACTS_DEBUG_OUTPUT,
+			)
+		}
+
+		fn instantiate(
+			origin: AccountId,
+			value: Balance,
+			gas_limit: u64,
+			storage_deposit_limit: Option<Balance>,
+			code: pallet_contracts_primitives::Code<Hash>,
+			data: Vec<u8>,
+			salt: Vec<u8>,
+		) -> pallet_contracts_primitives::ContractInstantiateResult<AccountId, Balance> {
+			Contracts::bare_instantiate(
+				origin,
+				value,
+				Weight::from_ref_time(gas_limit),
+				storage_deposit_limit,
+				code,
+				data,
+				salt,
+				CONTRACTS_DEBUG_OUTPUT,
+			)
+		}
+
+		fn upload_code(
+			origin: AccountId,
+			code: Vec<u8>,
+			storage_deposit_limit: Option<Balance>,
+		) -> pallet_contracts_primitives::CodeUploadResult<Hash, Balance> {
+			Contracts::bare_upload_code(origin, code, storage_deposit_limit)
+		}
+
+		fn get_storage(
+			address: AccountId,
+			key: Vec<u8>,
+		) -> pallet_contracts_primitives::GetStorageResult {
+			Contracts::get_storage(address, key)
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
 		fn benchmark_metadata(extra: bool) -> (
@@ -557,8 +1131,9 @@ impl_runtime_apis! {
 #[cfg(test)]
 mod tests {
 	use super::*;
-	use frame_support::traits::WhitelistedStorageKeys;
+	use frame_support::{dispatch::GetDispatchInfo, traits::WhitelistedStorageKeys};
 	use sp_core::hexdisplay::HexDisplay;
+	use sp_runtime::{traits::SignedExtension, transaction_validity::InvalidTransaction};
 	use std::collections::HashSet;
 
 	#[test]
@@ -589,4 +1164,157 @@ mod tests {
 			whitelist.contains("26aa394eea5630e07c48ae0c9558cef780d41e5e16056765bc8461851072c9d7")
 		);
 	}
+
+	#[test]
+	fn sponsored_transactions_cannot_tip_or_widen_through_batches() {
+		frame_support::sp_io::TestExternalities::new_empty().execute_with(|| {
+			let (company, staff) = (AccountId::new([1; 32]), AccountId::new([2; 32]));
+			pallet_template::StaffSponsor::<Runtime>::insert(&staff, &company);
+			let call = RuntimeCall::TemplateModule(pallet_template::Call::create_product {
+				id: 1,
+				name: b"widget".to_vec(),
+				description: vec![],
+				owner: 1,
+				previous_owners: vec![],
+			});
+			assert_eq!(ChargeSponsoredTransactionPayment::payer(&staff, &call), company);
+			assert_eq!(
+				ChargeSponsoredTransactionPayment::from(1).validate(
+					&staff,
+					&call,
+					&call.get_dispatch_info(),
+					100
+				),
+				Err(InvalidTransaction::Custom(sponsor::SPONSORED_TIP).into())
+			);
+
+			let remark = RuntimeCall::System(frame_system::Call::remark { remark: vec![] });
+			let batch = |calls| RuntimeCall::Utility(pallet_utility::Call::batch { calls });
+			let nested = batch(vec![call.clone(), batch(vec![remark])]);
+			assert_eq!(ChargeSponsoredTransactionPayment::payer(&staff, &nested), staff);
+			let verify = RuntimeCall::TemplateModule(pallet_template::Call::verify_company {
+				company: staff.clone(),
+				verified: true,
+			});
+			assert_eq!(ChargeSponsoredTransactionPayment::payer(&staff, &verify), staff);
+		});
+	}
+
+	#[test]
+	fn supply_chain_proxies_cannot_move_funds() {
+		let account = AccountId::new([1; 32]);
+		let supply_chain = |call| RuntimeCall::TemplateModule(call);
+		let batch = |calls| RuntimeCall::Utility(pallet_utility::Call::batch { calls });
+		let create_product = supply_chain(pallet_template::Call::create_product {
+			id: 1,
+			name: b"widget".to_vec(),
+			description: vec![],
+			owner: 1,
+			previous_owners: vec![],
+		});
+		let deliver = |contract_fulfilled| {
+			supply_chain(pallet_template::Call::update_supply_contract_status {
+				delivered: true,
+				contract_fulfilled,
+			})
+		};
+		let proxy = ProxyType::SupplyChain;
+
+		assert!(proxy.filter(&create_product));
+		assert!(proxy.filter(&deliver(false)));
+		assert!(proxy.filter(&batch(vec![create_product.clone(), batch(vec![deliver(false)])])));
+
+		for call in [
+			pallet_template::Call::fund_supply_contract { contract: account.clone() },
+			pallet_template::Call::confirm_delivery { contract: account.clone() },
+			pallet_template::Call::repay_iou { iou: account.clone(), amount: 1 },
+			pallet_template::Call::create_installment_plan {
+				iou: account.clone(),
+				installments: 2,
+				period: 10,
+			},
+			pallet_template::Call::pay_installment { iou: account.clone() },
+			pallet_template::Call::sponsor_staff { staff: account.clone() },
+		] {
+			let call = supply_chain(call);
+			assert!(!proxy.filter(&call), "{:?}", call);
+			let nested = batch(vec![create_product.clone(), batch(vec![call])]);
+			assert!(!proxy.filter(&nested), "{:?}", nested);
+		}
+		assert!(!proxy.filter(&deliver(true)));
+		assert!(!proxy.filter(&RuntimeCall::Balances(pallet_balances::Call::transfer {
+			dest: account.clone().into(),
+			value: 1,
+		})));
+		assert!(ProxyType::Any.filter(&deliver(true)));
+	}
+
+	#[test]
+	fn validators_are_seeded_from_aura_and_grandpa_authorities() {
+		use frame_support::traits::OnRuntimeUpgrade;
+		use sp_core::{ed25519, sr25519};
+
+		frame_support::sp_io::TestExternalities::new_empty().execute_with(|| {
+			let aura = AuraId::from(sr25519::Public::from_raw([1; 32]));
+			let grandpa = GrandpaId::from(ed25519::Public::from_raw([2; 32]));
+			frame_support::storage::unhashed::put(
+				&frame_support::storage::storage_prefix(b"Aura", b"Authorities"),
+				&vec![aura.clone()],
+			);
+			frame_support::storage::unhashed::put(
+				fg_primitives::GRANDPA_AUTHORITIES_KEY,
+				&fg_primitives::VersionedAuthorityList::from(vec![(grandpa.clone(), 1)]),
+			);
+
+			pallet_validator_set::migrations::SeedFromAuthorities::<
+				Runtime,
+				AuraAndGrandpaAuthorities,
+			>::on_runtime_upgrade();
+
+			let validator = AccountId::new([1; 32]);
+			assert_eq!(ValidatorSet::validators(), vec![validator.clone()]);
+			assert_eq!(Session::validators(), vec![validator.clone()]);
+			assert_eq!(
+				Session::queued_keys(),
+				vec![(validator.clone(), opaque::SessionKeys { aura, grandpa })]
+			);
+			assert_eq!(ValidatorSet::block_time(), MILLISECS_PER_BLOCK);
+			// A block time of six seconds makes sessions of 600 blocks.
+			assert_eq!(<SessionPeriod as Get<BlockNumber>>::get(), 600);
+
+			// Seeding again does nothing.
+			pallet_validator_set::Validators::<Runtime>::kill();
+			pallet_validator_set::migrations::SeedFromAuthorities::<
+				Runtime,
+				AuraAndGrandpaAuthorities,
+			>::on_runtime_upgrade();
+			assert!(ValidatorSet::validators().is_empty());
+		});
+	}
+
+	#[test]
+	fn half_of_the_council_cannot_change_membership_or_force_assets() {
+		use frame_support::{assert_noop, assert_ok};
+		use pallet_collective::RawOrigin;
+		use sp_runtime::DispatchError;
+
+		frame_support::sp_io::TestExternalities::new_empty().execute_with(|| {
+			// The origins of council proposals that passed with three and four of five votes.
+			let council = |ayes| {
+				RuntimeOrigin::from(RawOrigin::<AccountId, CouncilCollective>::Members(ayes, 5))
+			};
+			let member = AccountId::new([1; 32]);
+			let force_create =
+				|origin| Assets::force_create(origin, 7, member.clone().into(), true, 1);
+
+			assert_noop!(
+				CouncilMembership::add_member(council(3), member.clone().into()),
+				DispatchError::BadOrigin
+			);
+			assert_noop!(force_create(council(3)), DispatchError::BadOrigin);
+
+			assert_ok!(CouncilMembership::add_member(council(4), member.clone().into()));
+			assert_ok!(force_create(council(4)));
+		});
+	}
 }
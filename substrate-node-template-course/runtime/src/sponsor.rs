@@ -0,0 +1,153 @@
+//! Transaction fee payment in which a company pays for the supply-chain calls of its staff.
+//!
+//! Staff accounts registered with `TemplateModule::sponsor_staff` do not need native tokens to
+//! call into the supply-chain pallet, directly or through a `pallet_utility` batch: the fee is
+//! withdrawn from the sponsoring company instead. Sponsored transactions cannot tip, so that staff
+//! cannot spend the company's funds on priority, and calls only governance can make or that are
+//! paused are never sponsored.
+//! Every other call, and every call from an account without a sponsor, is paid by its signer
+//! exactly as with `ChargeTransactionPayment`.
+
+use codec::{Decode, Encode};
+use pallet_transaction_payment::{ChargeTransactionPayment, OnChargeTransaction};
+use scale_info::TypeInfo;
+use sp_runtime::{
+	traits::{DispatchInfoOf, PostDispatchInfoOf, SignedExtension},
+	transaction_validity::{
+		InvalidTransaction, TransactionValidity, TransactionValidityError, ValidTransaction,
+	},
+	DispatchResult, RuntimeDebug,
+};
+
+use crate::{AccountId, Balance, Runtime, RuntimeCall, System, TemplateModule, TransactionPayment};
+
+type OnCharge = <Runtime as pallet_transaction_payment::Config>::OnChargeTransaction;
+type LiquidityInfo = <OnCharge as OnChargeTransaction<Runtime>>::LiquidityInfo;
+
+/// `InvalidTransaction::Custom` code of a sponsored transaction with a tip.
+pub const SPONSORED_TIP: u8 = 1;
+
+/// Charges the transaction fee to the sponsor of the signer for `TemplateModule` calls, and to
+/// the signer otherwise.
+///
+/// Encoded like `ChargeTransactionPayment`: a compact tip.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct ChargeSponsoredTransactionPayment(#[codec(compact)] Balance);
+
+impl From<Balance> for ChargeSponsoredTransactionPayment {
+	fn from(tip: Balance) -> Self {
+		Self(tip)
+	}
+}
+
+impl ChargeSponsoredTransactionPayment {
+	/// The account that pays the fee of `call` signed by `who`.
+	pub fn payer(who: &AccountId, call: &RuntimeCall) -> AccountId {
+		if is_supply_chain_call(call) {
+			if let Some(sponsor) = TemplateModule::sponsor_of(who) {
+				return sponsor
+			}
+		}
+		who.clone()
+	}
+
+	fn withdraw_fee(
+		&self,
+		who: &AccountId,
+		call: &RuntimeCall,
+		info: &DispatchInfoOf<RuntimeCall>,
+		len: usize,
+	) -> Result<(Balance, AccountId, LiquidityInfo), TransactionValidityError> {
+		let tip = self.0;
+		let fee = TransactionPayment::compute_fee(len as u32, info, tip);
+		let payer = Self::payer(who, call);
+		if payer != *who && tip != 0 {
+			return Err(InvalidTransaction::Custom(SPONSORED_TIP).into())
+		}
+		let imbalance = OnCharge::withdraw_fee(&payer, call, info, fee, tip)?;
+		Ok((fee, payer, imbalance))
+	}
+}
+
+/// Whether `call` is a sponsored `TemplateModule` call or a batch made up only of such calls.
+///
+/// Batches are checked call by call, nested batches included, so wrapping calls in a batch never
+/// sponsors more than sending them one by one would.
+fn is_supply_chain_call(call: &RuntimeCall) -> bool {
+	use pallet_template::Call as SupplyChainCall;
+
+	match call {
+		RuntimeCall::TemplateModule(
+			SupplyChainCall::verify_company { .. } |
+			SupplyChainCall::arbitrate_supply_contract { .. } |
+			SupplyChainCall::pause_call { .. } |
+			SupplyChainCall::unpause_call { .. },
+		) => false,
+		RuntimeCall::TemplateModule(call) => !TemplateModule::is_paused(call),
+		RuntimeCall::Utility(
+			pallet_utility::Call::batch { calls } |
+			pallet_utility::Call::batch_all { calls } |
+			pallet_utility::Call::force_batch { calls },
+		) => !calls.is_empty() && calls.iter().all(is_supply_chain_call),
+		_ => false,
+	}
+}
+
+impl SignedExtension for ChargeSponsoredTransactionPayment {
+	const IDENTIFIER: &'static str = "ChargeSponsoredTransactionPayment";
+	type AccountId = AccountId;
+	type Call = RuntimeCall;
+	type AdditionalSigned = ();
+	/// The tip, the account that paid the fee and the withdrawn fee.
+	type Pre = (Balance, AccountId, LiquidityInfo);
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> TransactionValidity {
+		let (final_fee, _, _) = self.withdraw_fee(who, call, info, len)?;
+		let priority =
+			ChargeTransactionPayment::<Runtime>::get_priority(info, len, self.0, final_fee);
+		Ok(ValidTransaction { priority, ..Default::default() })
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		let (_, payer, imbalance) = self.withdraw_fee(who, call, info, len)?;
+		Ok((self.0, payer, imbalance))
+	}
+
+	fn post_dispatch(
+		maybe_pre: Option<Self::Pre>,
+		info: &DispatchInfoOf<Self::Call>,
+		post_info: &PostDispatchInfoOf<Self::Call>,
+		len: usize,
+		_result: &DispatchResult,
+	) -> Result<(), TransactionValidityError> {
+		if let Some((tip, payer, imbalance)) = maybe_pre {
+			let actual_fee =
+				TransactionPayment::compute_actual_fee(len as u32, info, post_info, tip);
+			OnCharge::correct_and_deposit_fee(&payer, info, post_info, actual_fee, tip, imbalance)?;
+			System::deposit_event(
+				pallet_transaction_payment::Event::<Runtime>::TransactionFeePaid {
+					who: payer,
+					actual_fee,
+					tip,
+				},
+			);
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,149 @@
+//! ink! side of the node runtime's supply-chain chain extension.
+//!
+//! Contracts built with [`SupplyChainEnvironment`] can read the `Company`, `Product`,
+//! `SupplyContract` and `IOU` records stored by `pallet_template`. They can also move a supply
+//! contract to a new status if they hold it themselves, or if its holder named them as delegate
+//! with `set_supply_contract_delegate`. The types below mirror the pallet's SCALE layout and the
+//! function ids mirror `runtime/src/chain_extension.rs`; both sides must change together.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use ink::{
+    env::{chain_extension::FromStatusCode, DefaultEnvironment, Environment},
+    prelude::vec::Vec,
+    primitives::AccountId,
+};
+
+/// A company registered with `register_company`.
+#[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct Company {
+    pub id: u64,
+    pub name: Vec<u8>,
+    pub about_me: Vec<u8>,
+    pub verified: bool,
+}
+
+/// A product created with `create_product`.
+#[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct Product {
+    pub id: u64,
+    pub name: Vec<u8>,
+    pub description: Vec<u8>,
+    pub owner: u64,
+    pub previous_owners: Vec<u64>,
+}
+
+/// A supply contract created with `create_supply_contract`.
+#[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct SupplyContract {
+    pub id: u64,
+    pub seller_id: u64,
+    pub buyer_id: u64,
+    pub products: Vec<u64>,
+    pub delivered: bool,
+    pub iou: u64,
+    pub contract_value: u64,
+    pub contract_fulfilled: bool,
+    pub asset_id: u32,
+    pub escrowed: bool,
+}
+
+/// An IOU created with `create_iou`.
+#[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct Iou {
+    pub id: u64,
+    pub debtor: u64,
+    pub creditor: u64,
+    pub amount: u64,
+    pub asset_id: u32,
+    pub delinquent: bool,
+}
+
+#[ink::chain_extension]
+pub trait SupplyChainExtension {
+    type ErrorCode = SupplyChainError;
+
+    /// The company registered to `account`, if any.
+    #[ink(extension = 1101, handle_status = false)]
+    fn company(account: AccountId) -> Option<Company>;
+
+    /// The product held by `account`, if any.
+    #[ink(extension = 1102, handle_status = false)]
+    fn product(account: AccountId) -> Option<Product>;
+
+    /// The supply contract held by `account`, if any.
+    #[ink(extension = 1103, handle_status = false)]
+    fn supply_contract(account: AccountId) -> Option<SupplyContract>;
+
+    /// The IOU held by `account`, if any.
+    #[ink(extension = 1104, handle_status = false)]
+    fn iou(account: AccountId) -> Option<Iou>;
+
+    /// Move the supply contract held by `holder` to a new status. `holder` must be this contract
+    /// or an account that named this contract as its delegate.
+    #[ink(extension = 1105)]
+    fn set_supply_contract_status(
+        holder: AccountId,
+        delivered: bool,
+        contract_fulfilled: bool,
+    ) -> Result<(), SupplyChainError>;
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum SupplyChainError {
+    /// The holder does not hold a supply contract.
+    SupplyContractNotFound,
+    /// Supply contracts can only move forward: undelivered, delivered, then fulfilled.
+    InvalidStatusTransition,
+    /// Fulfilling a contract above the runtime's multisig threshold needs a company multisig,
+    /// which a contract cannot be.
+    MultisigRequired,
+    /// The holder is not this contract and has not named it as delegate.
+    NotDelegated,
+    /// Governance paused `update_supply_contract_status`.
+    Paused,
+    /// The runtime returned a status code this crate does not know about.
+    Unknown(u32),
+}
+
+impl FromStatusCode for SupplyChainError {
+    fn from_status_code(status_code: u32) -> Result<(), Self> {
+        match status_code {
+            0 => Ok(()),
+            1 => Err(Self::SupplyContractNotFound),
+            2 => Err(Self::InvalidStatusTransition),
+            3 => Err(Self::MultisigRequired),
+            4 => Err(Self::NotDelegated),
+            5 => Err(Self::Paused),
+            code => Err(Self::Unknown(code)),
+        }
+    }
+}
+
+impl From<scale::Error> for SupplyChainError {
+    fn from(_: scale::Error) -> Self {
+        panic!("encountered unexpected invalid SCALE encoding")
+    }
+}
+
+/// Contract environment of the node runtime, with [`SupplyChainExtension`] enabled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum SupplyChainEnvironment {}
+
+impl Environment for SupplyChainEnvironment {
+    const MAX_EVENT_TOPICS: usize = <DefaultEnvironment as Environment>::MAX_EVENT_TOPICS;
+
+    type AccountId = <DefaultEnvironment as Environment>::AccountId;
+    type Balance = <DefaultEnvironment as Environment>::Balance;
+    type Hash = <DefaultEnvironment as Environment>::Hash;
+    type BlockNumber = <DefaultEnvironment as Environment>::BlockNumber;
+    type Timestamp = <DefaultEnvironment as Environment>::Timestamp;
+
+    type ChainExtension = SupplyChainExtension;
+}